@@ -1,18 +1,93 @@
 //! Data type and methods for building and describing an analysis.
 //!
 //! Not every possible analysis is in this data.
+use crate::app::config::CustomParcelSource;
 use metfor::{
-    Celsius, HectoPascal, IntHelicityM2pS2, JpKg, Km, Meters, MetersPSec, Mm, WindUV,
+    Celsius, HectoPascal, IntHelicityM2pS2, JpKg, Km, Meters, MetersPSec, Mm, Quantity, WindUV,
 };
 use optional::{none, some, Optioned};
 use sounding_analysis::{
     average_parcel, bunkers_storm_motion, dcape, effective_inflow_layer,
-    experimental::fire_briggs::{briggs_plume_heating_analysis, BriggsPlumeHeatingAnalysis},
-    hot_dry_windy, lift_parcel, mean_wind, mixed_layer_parcel, most_unstable_parcel,
-    precipitable_water, robust_convective_parcel_ascent, sr_helicity, surface_parcel, 
-    Layer, PFTAnalysis, ParcelAscentAnalysis, ParcelProfile, PrecipType, Sounding,
+    experimental::fire_briggs::briggs_plume_heating_analysis, hot_dry_windy, lift_parcel,
+    linear_interpolate_sounding, mean_wind, most_unstable_parcel, precipitable_water,
+    pressure_layer, robust_convective_parcel_ascent, sr_helicity, surface_parcel, Layer, Parcel,
+    ParcelAscentAnalysis, ParcelProfile, PrecipType, Sounding,
 };
-use std::collections::HashMap;
+use std::{collections::HashMap, hash::Hasher};
+
+// Module for saving analysis values to disk between sessions
+pub(crate) mod cache;
+
+// Module for describing the values in the provider analysis
+pub(crate) mod provider;
+
+// Module for comparing two soundings level by level
+pub(crate) mod difference;
+
+// Module for summarizing the spread of ensemble members
+pub(crate) mod ensemble;
+
+// Module for interpolating between soundings in time
+pub(crate) mod interpolate;
+
+// Module for measuring a layer between two levels
+pub(crate) mod measure;
+
+// Module for the results of the plume analyses
+pub(crate) mod plume;
+use plume::{BriggsPlumeHeating, Pft};
+
+/// User adjustable parameters that go into the analysis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnalysisSettings {
+    /// Moisture ratio used for the PFT.
+    pub pft_moisture_ratio: f64,
+    /// Moisture ratio for the lower bound of the Briggs plume heating analysis.
+    pub briggs_low_moisture_ratio: f64,
+    /// Moisture ratio for the upper bound of the Briggs plume heating analysis.
+    pub briggs_high_moisture_ratio: f64,
+    /// Depth of the layer above ground level used for the mean wind.
+    pub mean_wind_depth: Meters,
+    /// Depth of the layer above ground level used for the storm relative helicity.
+    pub srh_depth: Meters,
+    /// Depth of the layer above the surface used for the mixed layer parcel.
+    pub mixed_layer_depth: HectoPascal,
+}
+
+impl Default for AnalysisSettings {
+    fn default() -> Self {
+        AnalysisSettings {
+            // 15 because that is the ratio used in the paper.
+            pft_moisture_ratio: 15.0,
+            briggs_low_moisture_ratio: 8.0,
+            briggs_high_moisture_ratio: 15.0,
+            mean_wind_depth: Meters(6000.0),
+            srh_depth: Meters(3000.0),
+            mixed_layer_depth: HectoPascal(100.0),
+        }
+    }
+}
+
+impl AnalysisSettings {
+    /// A hash of the settings so we can tell if stored values were made with different ones.
+    ///
+    /// This is saved in the on-disk cache, so it must not change between builds.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = cache::StableHasher::default();
+        for val in [
+            self.pft_moisture_ratio,
+            self.briggs_low_moisture_ratio,
+            self.briggs_high_moisture_ratio,
+            self.mean_wind_depth.unpack(),
+            self.srh_depth.unpack(),
+            self.mixed_layer_depth.unpack(),
+        ] {
+            hasher.write_u64(val.to_bits());
+        }
+
+        hasher.finish()
+    }
+}
 
 /// Convenient package for commonly requested analysis values.
 ///
@@ -28,17 +103,17 @@ pub struct Analysis {
     right_mover: Optioned<WindUV<MetersPSec>>,
     left_mover: Optioned<WindUV<MetersPSec>>,
     mean_wind: Optioned<WindUV<MetersPSec>>,
-    sr_helicity_3k_rm: Optioned<IntHelicityM2pS2>,
-    sr_helicity_3k_lm: Optioned<IntHelicityM2pS2>,
+    sr_helicity_sfc_rm: Optioned<IntHelicityM2pS2>,
+    sr_helicity_sfc_lm: Optioned<IntHelicityM2pS2>,
     effective_inflow_layer: Option<Layer>,
     sr_helicity_eff_rm: Optioned<IntHelicityM2pS2>,
     sr_helicity_eff_lm: Optioned<IntHelicityM2pS2>,
 
     // Fire weather indicies
     hdw: Optioned<f64>,
-    pft: Option<PFTAnalysis>,
-    briggs_plume_heating_low: Option<BriggsPlumeHeatingAnalysis>,
-    briggs_plume_heating_high: Option<BriggsPlumeHeatingAnalysis>,
+    pft: Option<Pft>,
+    briggs_plume_heating_low: Option<BriggsPlumeHeating>,
+    briggs_plume_heating_high: Option<BriggsPlumeHeating>,
     max_p: HectoPascal, // Keep track of the lowest level in the sounding.
 
     // Downburst
@@ -53,6 +128,11 @@ pub struct Analysis {
     convective: Option<ParcelAscentAnalysis>,
     effective: Option<ParcelAscentAnalysis>,
 
+    // User defined parcels, where each one starts and, once they are lifted, their ascents in
+    // the same order.
+    custom_parcel_sources: Vec<CustomParcelSource>,
+    custom_parcels: Option<Vec<Option<ParcelAscentAnalysis>>>,
+
     // Precipitation
     provider_wx_code: Option<PrecipType>,
     bourgouin_wx_code: Option<PrecipType>,
@@ -60,16 +140,26 @@ pub struct Analysis {
 
     // Provider analysis
     provider_analysis: HashMap<&'static str, f64>,
+
+    // Key for the cache entry of the file this came from, if any.
+    source_key: Option<u64>,
+
+    // Made up by interpolating between other soundings rather than loaded from a file.
+    synthetic: bool,
+
+    // The sounding was edited after the values were computed. They are kept for display until
+    // the fill in methods replace them.
+    stale_basic: bool,
+    stale_plume: bool,
+
+    // Parameters used when filling in the analysis
+    settings: AnalysisSettings,
 }
 
 impl Analysis {
     /// Create a new `Analysis`.
     pub fn new(snd: Sounding) -> Self {
-        let max_p = snd
-            .bottom_up()
-            .filter_map(|dr| dr.pressure.into_option())
-            .next()
-            .unwrap_or(HectoPascal(0.0));
+        let max_p = lowest_pressure(&snd);
 
         Analysis {
             sounding: snd,
@@ -78,8 +168,8 @@ impl Analysis {
             right_mover: none(),
             left_mover: none(),
             mean_wind: none(),
-            sr_helicity_3k_rm: none(),
-            sr_helicity_3k_lm: none(),
+            sr_helicity_sfc_rm: none(),
+            sr_helicity_sfc_lm: none(),
             effective_inflow_layer: None,
             sr_helicity_eff_rm: none(),
             sr_helicity_eff_lm: none(),
@@ -100,11 +190,23 @@ impl Analysis {
             convective: None,
             effective: None,
 
+            custom_parcel_sources: vec![],
+            custom_parcels: None,
+
             provider_wx_code: None,
             bourgouin_wx_code: None,
             nssl_wx_code: None,
 
             provider_analysis: HashMap::new(),
+
+            source_key: None,
+
+            synthetic: false,
+
+            stale_basic: false,
+            stale_plume: false,
+
+            settings: AnalysisSettings::default(),
         }
     }
 
@@ -133,14 +235,14 @@ impl Analysis {
         self.mean_wind
     }
 
-    /// Get the storm relative helicity for a right mover storm
-    pub fn sr_helicity_3k_rm(&self) -> Optioned<IntHelicityM2pS2> {
-        self.sr_helicity_3k_rm
+    /// Get the storm relative helicity in the surface layer for a right mover storm
+    pub fn sr_helicity_sfc_rm(&self) -> Optioned<IntHelicityM2pS2> {
+        self.sr_helicity_sfc_rm
     }
 
-    /// Get the storm relative helicity for a left mover storm
-    pub fn sr_helicity_3k_lm(&self) -> Optioned<IntHelicityM2pS2> {
-        self.sr_helicity_3k_lm
+    /// Get the storm relative helicity in the surface layer for a left mover storm
+    pub fn sr_helicity_sfc_lm(&self) -> Optioned<IntHelicityM2pS2> {
+        self.sr_helicity_sfc_lm
     }
 
     /// Get the effective inflow layer
@@ -211,17 +313,17 @@ impl Analysis {
     }
 
     /// Get the PFT.
-    pub fn pft(&self) -> Option<&PFTAnalysis> {
+    pub fn pft(&self) -> Option<&Pft> {
         self.pft.as_ref()
     }
 
     /// Get the plumes analysis
-    pub fn briggs_plume_heating_low(&self) -> &Option<BriggsPlumeHeatingAnalysis> {
+    pub fn briggs_plume_heating_low(&self) -> &Option<BriggsPlumeHeating> {
         &self.briggs_plume_heating_low
     }
 
     /// Get the plumes analysis
-    pub fn briggs_plume_heating_high(&self) -> &Option<BriggsPlumeHeatingAnalysis> {
+    pub fn briggs_plume_heating_high(&self) -> &Option<BriggsPlumeHeating> {
         &self.briggs_plume_heating_high
     }
 
@@ -260,6 +362,19 @@ impl Analysis {
         self.effective.as_ref()
     }
 
+    /// Get the ascent of a user defined parcel, if it has been lifted.
+    pub fn custom_parcel_analysis(
+        &self,
+        source: CustomParcelSource,
+    ) -> Option<&ParcelAscentAnalysis> {
+        let index = self
+            .custom_parcel_sources
+            .iter()
+            .position(|&src| src == source)?;
+
+        self.custom_parcels.as_ref()?.get(index)?.as_ref()
+    }
+
     /// Get the downburst profile
     pub fn downburst_profile(&self) -> Option<&ParcelProfile> {
         self.downburst_profile.as_ref()
@@ -281,6 +396,120 @@ impl Analysis {
         &self.provider_analysis
     }
 
+    /// Analysis of a modified version of this sounding.
+    ///
+    /// The provider analysis and settings are kept. The values computed from the original sounding
+    /// are kept too, so there is something to show while they are recomputed, but the fill in
+    /// methods replace them instead of skipping them. It is not associated with any file in the
+    /// on-disk cache.
+    pub fn with_modified_sounding(&self, snd: Sounding) -> Self {
+        let max_p = lowest_pressure(&snd);
+
+        Analysis {
+            sounding: snd,
+            max_p,
+            source_key: None,
+            stale_basic: true,
+            stale_plume: true,
+            ..self.clone()
+        }
+    }
+
+    /// Were the basic values computed from an earlier version of the sounding?
+    pub fn is_stale(&self) -> bool {
+        self.stale_basic
+    }
+
+    /// Mark whether this was made up from other soundings instead of loaded from a file.
+    pub fn with_synthetic(self, synthetic: bool) -> Self {
+        Analysis { synthetic, ..self }
+    }
+
+    /// Was this made up from other soundings instead of loaded from a file?
+    pub fn is_synthetic(&self) -> bool {
+        self.synthetic
+    }
+
+    /// Set the key used to store this analysis in the on-disk cache.
+    pub fn with_source_key(self, source_key: u64) -> Self {
+        Analysis {
+            source_key: Some(source_key),
+            ..self
+        }
+    }
+
+    /// Get the key used to store this analysis in the on-disk cache.
+    pub fn source_key(&self) -> Option<u64> {
+        self.source_key
+    }
+
+    /// Set the parameters used when filling in the analysis.
+    pub fn with_settings(self, settings: AnalysisSettings) -> Self {
+        Analysis { settings, ..self }
+    }
+
+    /// Get the parameters used when filling in the analysis.
+    pub fn settings(&self) -> AnalysisSettings {
+        self.settings
+    }
+
+    /// Change the parameters and clear only the values that depend on the ones that changed.
+    ///
+    /// Call one of the fill in methods afterwards to recompute them.
+    pub fn update_settings_mut(&mut self, settings: AnalysisSettings) {
+        let old = self.settings;
+        self.settings = settings;
+
+        if old.pft_moisture_ratio != settings.pft_moisture_ratio {
+            self.pft = None;
+        }
+
+        if old.briggs_low_moisture_ratio != settings.briggs_low_moisture_ratio {
+            self.briggs_plume_heating_low = None;
+        }
+
+        if old.briggs_high_moisture_ratio != settings.briggs_high_moisture_ratio {
+            self.briggs_plume_heating_high = None;
+        }
+
+        if old.mean_wind_depth != settings.mean_wind_depth {
+            self.mean_wind = none();
+        }
+
+        if old.srh_depth != settings.srh_depth {
+            self.sr_helicity_sfc_rm = none();
+            self.sr_helicity_sfc_lm = none();
+        }
+
+        if old.mixed_layer_depth != settings.mixed_layer_depth {
+            self.mixed_layer = None;
+        }
+    }
+
+    /// Set the user defined parcels to lift.
+    pub fn with_custom_parcels(mut self, sources: &[CustomParcelSource]) -> Self {
+        self.update_custom_parcels_mut(sources);
+        self
+    }
+
+    /// Get where the user defined parcels start.
+    pub fn custom_parcel_sources(&self) -> &[CustomParcelSource] {
+        &self.custom_parcel_sources
+    }
+
+    /// Change the user defined parcels, returns `true` if they changed.
+    ///
+    /// Call one of the fill in methods afterwards to lift them.
+    pub fn update_custom_parcels_mut(&mut self, sources: &[CustomParcelSource]) -> bool {
+        if self.custom_parcel_sources == sources {
+            return false;
+        }
+
+        self.custom_parcel_sources = sources.to_vec();
+        self.custom_parcels = None;
+        true
+    }
+
     /// Get a reference to the sounding.
     pub fn sounding(&self) -> &Sounding {
         &self.sounding
@@ -288,6 +517,28 @@ impl Analysis {
 
     /// Analyze the sounding to get as much information as you can.
     pub fn fill_in_missing_analysis_mut(&mut self) {
+        self.fill_in_basic_analysis_mut();
+        self.fill_in_plume_analysis_mut();
+    }
+
+    /// Fill in everything except the plume analyses, which are by far the slowest to compute.
+    pub fn fill_in_basic_analysis_mut(&mut self) {
+        // Start over if the sounding was edited since the values were computed.
+        if self.stale_basic {
+            *self = Analysis {
+                pft: self.pft.take(),
+                briggs_plume_heating_low: self.briggs_plume_heating_low.take(),
+                briggs_plume_heating_high: self.briggs_plume_heating_high.take(),
+                provider_analysis: std::mem::take(&mut self.provider_analysis),
+                custom_parcel_sources: std::mem::take(&mut self.custom_parcel_sources),
+                source_key: self.source_key,
+                synthetic: self.synthetic,
+                stale_plume: self.stale_plume,
+                settings: self.settings,
+                ..Analysis::new(self.sounding.clone())
+            };
+        }
+
         self.precipitable_water = self
             .precipitable_water
             .or_else(|| Optioned::from(precipitable_water(&self.sounding).ok()));
@@ -307,7 +558,10 @@ impl Analysis {
         }
 
         if self.mixed_layer.is_none() {
-            self.mixed_layer = match mixed_layer_parcel(&self.sounding) {
+            let top_p = HectoPascal(self.max_p.unpack() - self.settings.mixed_layer_depth.unpack());
+            self.mixed_layer = match pressure_layer(&self.sounding, self.max_p, top_p)
+                .and_then(|layer| average_parcel(&self.sounding, &layer))
+            {
                 Ok(parcel) => lift_parcel(parcel, &self.sounding).ok(),
                 Err(_) => None,
             };
@@ -409,23 +663,24 @@ impl Analysis {
 
         // Fill in the mean wind
         if self.mean_wind.is_none() {
-            if let Some(layer) = &sounding_analysis::layer_agl(&self.sounding, Meters(6000.0)).ok()
+            if let Some(layer) =
+                &sounding_analysis::layer_agl(&self.sounding, self.settings.mean_wind_depth).ok()
             {
                 self.mean_wind = Optioned::from(mean_wind(layer, &self.sounding).ok());
             }
         }
 
         // Fill in the storm relative helicity
-        if self.sr_helicity_3k_rm.is_none() || self.sr_helicity_3k_lm.is_none() {
+        if self.sr_helicity_sfc_rm.is_none() || self.sr_helicity_sfc_lm.is_none() {
             if let (Some(layer), Some(sm), Some(lm)) = (
-                &sounding_analysis::layer_agl(&self.sounding, Meters(3000.0)).ok(),
+                &sounding_analysis::layer_agl(&self.sounding, self.settings.srh_depth).ok(),
                 self.right_mover.into_option(),
                 self.left_mover.into_option(),
             ) {
-                self.sr_helicity_3k_rm =
+                self.sr_helicity_sfc_rm =
                     Optioned::from(sr_helicity(layer, sm, self.sounding()).ok());
 
-                self.sr_helicity_3k_lm =
+                self.sr_helicity_sfc_lm =
                     Optioned::from(sr_helicity(layer, lm, self.sounding()).ok());
             }
         }
@@ -459,25 +714,133 @@ impl Analysis {
                 };
         }
 
+        // Lift the user defined parcels
+        if self.custom_parcels.is_none() {
+            self.custom_parcels = Some(
+                self.custom_parcel_sources
+                    .iter()
+                    .map(|&source| lift_custom_parcel(source, &self.sounding))
+                    .collect(),
+            );
+        }
+    }
+
+    /// Fill in the PFT and Briggs plume analyses.
+    pub fn fill_in_plume_analysis_mut(&mut self) {
+        if self.stale_plume {
+            self.pft = None;
+            self.briggs_plume_heating_low = None;
+            self.briggs_plume_heating_high = None;
+            self.stale_plume = false;
+        }
+
         // Fill in the PFT.
         if self.pft.is_none() {
-            // 15 because that is the ratio used in the paper.
-            self.pft = sounding_analysis::pft_analysis(self.sounding(), 15.0).ok();
+            self.pft =
+                sounding_analysis::pft_analysis(self.sounding(), self.settings.pft_moisture_ratio)
+                    .ok()
+                    .map(Pft::from);
         }
 
         // Fill in the experimental fire weather parameters.
         if self.briggs_plume_heating_low.is_none() {
-            self.briggs_plume_heating_low =
-                briggs_plume_heating_analysis(self.sounding(), Some(8.0)).ok()
+            self.briggs_plume_heating_low = briggs_plume_heating_analysis(
+                self.sounding(),
+                Some(self.settings.briggs_low_moisture_ratio),
+            )
+            .ok()
+            .map(BriggsPlumeHeating::from);
         }
 
         if self.briggs_plume_heating_high.is_none() {
-            self.briggs_plume_heating_high =
-                briggs_plume_heating_analysis(self.sounding(), Some(15.0)).ok()
+            self.briggs_plume_heating_high = briggs_plume_heating_analysis(
+                self.sounding(),
+                Some(self.settings.briggs_high_moisture_ratio),
+            )
+            .ok()
+            .map(BriggsPlumeHeating::from);
         }
     }
 }
 
+/// The pressure at the lowest level in a sounding.
+fn lowest_pressure(snd: &Sounding) -> HectoPascal {
+    snd.bottom_up()
+        .filter_map(|dr| dr.pressure.into_option())
+        .next()
+        .unwrap_or(HectoPascal(0.0))
+}
+
+/// The lowest level with both a pressure and a height.
+pub fn surface(snd: &Sounding) -> Option<(HectoPascal, Meters)> {
+    snd.bottom_up()
+        .find_map(|row| row.pressure.into_option().zip(row.height.into_option()))
+}
+
+/// Interpolate the pressure `agl` meters above the surface, linear in the log of pressure.
+pub fn pressure_at_height(snd: &Sounding, agl: f64) -> Option<HectoPascal> {
+    let (sfc_p, sfc_h) = surface(snd)?;
+    let target = sfc_h.unpack() + agl;
+
+    let levels = snd
+        .bottom_up()
+        .filter_map(|row| row.pressure.into_option().zip(row.height.into_option()))
+        .map(|(p, h)| (p.unpack(), h.unpack()));
+
+    let (mut p0, mut h0) = (sfc_p.unpack(), sfc_h.unpack());
+    if target <= h0 {
+        return Some(sfc_p);
+    }
+
+    for (p1, h1) in levels {
+        if h1 >= target && h1 > h0 {
+            let frac = (target - h0) / (h1 - h0);
+            let ln_p = p0.ln() + frac * (p1.ln() - p0.ln());
+            return Some(HectoPascal(ln_p.exp()));
+        }
+        p0 = p1;
+        h0 = h1;
+    }
+
+    None
+}
+
+/// Lift a user defined parcel through the sounding.
+fn lift_custom_parcel(source: CustomParcelSource, snd: &Sounding) -> Option<ParcelAscentAnalysis> {
+    let parcel = match source {
+        CustomParcelSource::Pressure {
+            pressure,
+            temperature,
+            dew_point,
+        } => Parcel {
+            temperature,
+            pressure,
+            dew_point,
+        },
+        CustomParcelSource::Height {
+            height,
+            temperature,
+            dew_point,
+        } => Parcel {
+            temperature,
+            pressure: pressure_at_height(snd, height.unpack())?,
+            dew_point,
+        },
+        CustomParcelSource::MixedLayer { depth } => {
+            let sfc_p = snd.bottom_up().find_map(|row| row.pressure.into_option())?;
+            let top_p = HectoPascal(sfc_p.unpack() - depth.unpack());
+            pressure_layer(snd, sfc_p, top_p)
+                .and_then(|layer| average_parcel(snd, &layer))
+                .ok()?
+        }
+        CustomParcelSource::Level { pressure } => linear_interpolate_sounding(snd, pressure)
+            .ok()
+            .and_then(Parcel::from_datarow)?,
+    };
+
+    lift_parcel(parcel, snd).ok()
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) enum Intensity {
     Light,
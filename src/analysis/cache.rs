@@ -0,0 +1,502 @@
+//! Persistent on-disk cache of computed analysis values.
+//!
+//! Entries are stored per source file, keyed by a hash of the file's contents, so re-opening a
+//! file we have already viewed, even a copy of it or one that was moved, skips the work in
+//! `Analysis::fill_in_missing_analysis_mut`. Entries made with different analysis settings are
+//! ignored.
+//!
+//! Everything is stored except the parcel ascents, which can't be rebuilt from their parts. Their
+//! starting points are stored instead and they are lifted again when the cache is applied, which
+//! is quick compared to finding the starting points and to the plume analyses.
+use super::{
+    plume::{BriggsPlumeHeating, Pft},
+    Analysis,
+};
+use metfor::{
+    Celsius, HectoPascal, IntHelicityM2pS2, JpKg, Meters, MetersPSec, Mm, Quantity, WindUV,
+};
+use optional::Optioned;
+use serde_derive::{Deserialize, Serialize};
+use sounding_analysis::{
+    lift_parcel, linear_interpolate_sounding, Layer, Parcel, ParcelAscentAnalysis, ParcelProfile,
+    PrecipType, Sounding,
+};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::File,
+    hash::Hasher,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+/// Bump this any time the analysis changes in a way that makes old cache entries invalid.
+const CACHE_VERSION: u32 = 2;
+
+const CACHE_DIR_NAME: &str = "sonde";
+
+/// A 64 bit FNV-1a hasher.
+///
+/// Unlike `DefaultHasher` its output never changes between releases of Rust, so it is safe to
+/// use for keys that are saved to disk.
+#[derive(Clone, Copy, Debug)]
+pub struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        StableHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn write_u32(&mut self, val: u32) {
+        self.write(&val.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, val: u64) {
+        self.write(&val.to_le_bytes());
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Get the key for the cache entry of a file from a hash of its contents.
+pub fn file_key(path: &Path) -> Result<u64, Box<dyn Error>> {
+    let contents = std::fs::read(path)?;
+
+    let mut hasher = StableHasher::default();
+    hasher.write_u32(CACHE_VERSION);
+    hasher.write(&contents);
+
+    Ok(hasher.finish())
+}
+
+/// Apply any cached values for the file with `key` to the analyses loaded from it.
+pub fn apply_cached(key: u64, anals: &mut [Analysis]) {
+    let mut cache = match load_cache_file(key) {
+        Some(cache) => cache,
+        None => return,
+    };
+
+    for anal in anals.iter_mut() {
+        if cache.settings != anal.settings.fingerprint() {
+            continue;
+        }
+
+        if let Some(entry) = cache.entries.remove(&sounding_id(anal.sounding())) {
+            entry.apply(anal);
+        }
+    }
+}
+
+/// Save the values for all the analyses that came from the file with `key`.
+pub fn store<'a, I>(key: u64, anals: I) -> Result<(), Box<dyn Error>>
+where
+    I: Iterator<Item = &'a Analysis>,
+{
+    let path = match cache_file_path(key) {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let mut anals = anals.peekable();
+    let settings = match anals.peek() {
+        Some(anal) => anal.settings.fingerprint(),
+        None => return Ok(()),
+    };
+
+    let cache = CacheFile {
+        version: CACHE_VERSION,
+        settings,
+        entries: anals
+            .map(|anal| (sounding_id(anal.sounding()), CachedAnalysis::from(anal)))
+            .collect(),
+    };
+
+    // Nothing to do if the file was opened again without computing anything new.
+    if load_cache_file(key).is_some_and(|old| old == cache) {
+        return Ok(());
+    }
+
+    let serialized = serde_yaml::to_string(&cache)?;
+    File::create(path)?.write_all(serialized.as_bytes())?;
+
+    Ok(())
+}
+
+fn cache_file_path(key: u64) -> Option<PathBuf> {
+    dirs::cache_dir().map(|path| path.join(CACHE_DIR_NAME).join(format!("{:016x}.yml", key)))
+}
+
+fn load_cache_file(key: u64) -> Option<CacheFile> {
+    let mut serialized = String::new();
+    File::open(cache_file_path(key)?)
+        .and_then(|mut f| f.read_to_string(&mut serialized))
+        .ok()?;
+
+    serde_yaml::from_str::<CacheFile>(&serialized)
+        .ok()
+        .filter(|cache| cache.version == CACHE_VERSION)
+}
+
+/// A file may hold many soundings, so identify them by valid time and lead time.
+fn sounding_id(snd: &Sounding) -> String {
+    let vt = snd
+        .valid_time()
+        .map(|vt| vt.format("%Y-%m-%dT%H:%M").to_string())
+        .unwrap_or_default();
+    let lt = snd
+        .lead_time()
+        .into_option()
+        .map(|lt| lt.to_string())
+        .unwrap_or_default();
+
+    format!("{}/{}", vt, lt)
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    settings: u64,
+    entries: HashMap<String, CachedAnalysis>,
+}
+
+/// Starting point of a parcel, (pressure hPa, temperature C, dew point C).
+type CachedParcel = (f64, f64, f64);
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct CachedAnalysis {
+    precipitable_water: Option<f64>,
+    convective_t: Option<f64>,
+    right_mover: Option<(f64, f64)>,
+    left_mover: Option<(f64, f64)>,
+    mean_wind: Option<(f64, f64)>,
+    sr_helicity_sfc_rm: Option<f64>,
+    sr_helicity_sfc_lm: Option<f64>,
+    // (bottom, top) pressure in hPa.
+    effective_inflow_layer: Option<(f64, f64)>,
+    sr_helicity_eff_rm: Option<f64>,
+    sr_helicity_eff_lm: Option<f64>,
+
+    hdw: Option<f64>,
+    pft: Option<Pft>,
+    briggs_plume_heating_low: Option<BriggsPlumeHeating>,
+    briggs_plume_heating_high: Option<BriggsPlumeHeating>,
+
+    dcape: Option<f64>,
+    downrush_t: Option<f64>,
+    downburst_profile: Option<CachedProfile>,
+
+    mixed_layer: Option<CachedParcel>,
+    surface: Option<CachedParcel>,
+    most_unstable: Option<CachedParcel>,
+    convective: Option<CachedParcel>,
+    effective: Option<CachedParcel>,
+
+    // WMO codes of the precipitation types.
+    provider_wx_code: Option<u8>,
+    bourgouin_wx_code: Option<u8>,
+    nssl_wx_code: Option<u8>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct CachedProfile {
+    pressure: Vec<HectoPascal>,
+    height: Vec<Meters>,
+    parcel_t: Vec<Celsius>,
+    environment_t: Vec<Celsius>,
+}
+
+impl From<&Analysis> for CachedAnalysis {
+    fn from(anal: &Analysis) -> Self {
+        fn wind(val: Optioned<WindUV<MetersPSec>>) -> Option<(f64, f64)> {
+            val.into_option()
+                .map(|WindUV { u, v }| (u.unpack(), v.unpack()))
+        }
+
+        fn parcel(anal: &Option<ParcelAscentAnalysis>) -> Option<CachedParcel> {
+            anal.as_ref().map(|anal| {
+                let pcl = anal.parcel();
+                (
+                    pcl.pressure.unpack(),
+                    pcl.temperature.unpack(),
+                    pcl.dew_point.unpack(),
+                )
+            })
+        }
+
+        fn scalar<T: Quantity + optional::Noned>(val: Optioned<T>) -> Option<f64> {
+            val.into_option().map(Quantity::unpack)
+        }
+
+        fn layer(val: &Option<Layer>) -> Option<(f64, f64)> {
+            let layer = val.as_ref()?;
+            Some((
+                layer.bottom.pressure.into_option()?.unpack(),
+                layer.top.pressure.into_option()?.unpack(),
+            ))
+        }
+
+        fn profile(val: &Option<ParcelProfile>) -> Option<CachedProfile> {
+            val.as_ref().map(|pp| CachedProfile {
+                pressure: pp.pressure.clone(),
+                height: pp.height.clone(),
+                parcel_t: pp.parcel_t.clone(),
+                environment_t: pp.environment_t.clone(),
+            })
+        }
+
+        fn wx_code(val: Option<PrecipType>) -> Option<u8> {
+            val.map(|wx| wx as u8)
+        }
+
+        CachedAnalysis {
+            precipitable_water: scalar(anal.precipitable_water),
+            convective_t: scalar(anal.convective_t),
+            right_mover: wind(anal.right_mover),
+            left_mover: wind(anal.left_mover),
+            mean_wind: wind(anal.mean_wind),
+            sr_helicity_sfc_rm: scalar(anal.sr_helicity_sfc_rm),
+            sr_helicity_sfc_lm: scalar(anal.sr_helicity_sfc_lm),
+            effective_inflow_layer: layer(&anal.effective_inflow_layer),
+            sr_helicity_eff_rm: scalar(anal.sr_helicity_eff_rm),
+            sr_helicity_eff_lm: scalar(anal.sr_helicity_eff_lm),
+
+            hdw: anal.hdw.into_option(),
+            pft: anal.pft.clone(),
+            briggs_plume_heating_low: anal.briggs_plume_heating_low.clone(),
+            briggs_plume_heating_high: anal.briggs_plume_heating_high.clone(),
+
+            dcape: scalar(anal.dcape),
+            downrush_t: scalar(anal.downrush_t),
+            downburst_profile: profile(&anal.downburst_profile),
+
+            mixed_layer: parcel(&anal.mixed_layer),
+            surface: parcel(&anal.surface),
+            most_unstable: parcel(&anal.most_unstable),
+            convective: parcel(&anal.convective),
+            effective: parcel(&anal.effective),
+
+            provider_wx_code: wx_code(anal.provider_wx_code),
+            bourgouin_wx_code: wx_code(anal.bourgouin_wx_code),
+            nssl_wx_code: wx_code(anal.nssl_wx_code),
+        }
+    }
+}
+
+impl CachedAnalysis {
+    /// Fill in the analysis with the cached values, values already present are not replaced.
+    fn apply(&self, anal: &mut Analysis) {
+        fn wind(val: Option<(f64, f64)>) -> Optioned<WindUV<MetersPSec>> {
+            Optioned::from(val.map(|(u, v)| WindUV {
+                u: MetersPSec(u),
+                v: MetersPSec(v),
+            }))
+        }
+
+        fn lift(val: Option<CachedParcel>, snd: &Sounding) -> Option<ParcelAscentAnalysis> {
+            val.and_then(|(p, t, dp)| {
+                let pcl = Parcel {
+                    pressure: HectoPascal(p),
+                    temperature: Celsius(t),
+                    dew_point: Celsius(dp),
+                };
+                lift_parcel(pcl, snd).ok()
+            })
+        }
+
+        fn helicity(val: Option<f64>) -> Optioned<IntHelicityM2pS2> {
+            Optioned::from(val.map(IntHelicityM2pS2))
+        }
+
+        fn layer(val: Option<(f64, f64)>, snd: &Sounding) -> Option<Layer> {
+            let (bottom, top) = val?;
+            Some(Layer {
+                bottom: linear_interpolate_sounding(snd, HectoPascal(bottom)).ok()?,
+                top: linear_interpolate_sounding(snd, HectoPascal(top)).ok()?,
+            })
+        }
+
+        fn profile(val: &Option<CachedProfile>) -> Option<ParcelProfile> {
+            val.as_ref().map(|pp| ParcelProfile {
+                pressure: pp.pressure.clone(),
+                height: pp.height.clone(),
+                parcel_t: pp.parcel_t.clone(),
+                environment_t: pp.environment_t.clone(),
+            })
+        }
+
+        fn wx_code(val: Option<u8>) -> Option<PrecipType> {
+            val.map(PrecipType::from)
+        }
+
+        anal.precipitable_water = anal
+            .precipitable_water
+            .or_else(|| Optioned::from(self.precipitable_water.map(Mm)));
+        anal.convective_t = anal
+            .convective_t
+            .or_else(|| Optioned::from(self.convective_t.map(Celsius)));
+        anal.right_mover = anal.right_mover.or_else(|| wind(self.right_mover));
+        anal.left_mover = anal.left_mover.or_else(|| wind(self.left_mover));
+        anal.mean_wind = anal.mean_wind.or_else(|| wind(self.mean_wind));
+        anal.sr_helicity_sfc_rm = anal
+            .sr_helicity_sfc_rm
+            .or_else(|| helicity(self.sr_helicity_sfc_rm));
+        anal.sr_helicity_sfc_lm = anal
+            .sr_helicity_sfc_lm
+            .or_else(|| helicity(self.sr_helicity_sfc_lm));
+        if anal.effective_inflow_layer.is_none() {
+            anal.effective_inflow_layer = layer(self.effective_inflow_layer, &anal.sounding);
+        }
+        anal.sr_helicity_eff_rm = anal
+            .sr_helicity_eff_rm
+            .or_else(|| helicity(self.sr_helicity_eff_rm));
+        anal.sr_helicity_eff_lm = anal
+            .sr_helicity_eff_lm
+            .or_else(|| helicity(self.sr_helicity_eff_lm));
+
+        anal.hdw = anal.hdw.or_else(|| Optioned::from(self.hdw));
+        if anal.pft.is_none() {
+            anal.pft = self.pft.clone();
+        }
+        if anal.briggs_plume_heating_low.is_none() {
+            anal.briggs_plume_heating_low = self.briggs_plume_heating_low.clone();
+        }
+        if anal.briggs_plume_heating_high.is_none() {
+            anal.briggs_plume_heating_high = self.briggs_plume_heating_high.clone();
+        }
+
+        anal.dcape = anal.dcape.or_else(|| Optioned::from(self.dcape.map(JpKg)));
+        anal.downrush_t = anal
+            .downrush_t
+            .or_else(|| Optioned::from(self.downrush_t.map(Celsius)));
+        if anal.downburst_profile.is_none() {
+            anal.downburst_profile = profile(&self.downburst_profile);
+        }
+
+        anal.provider_wx_code = anal
+            .provider_wx_code
+            .or_else(|| wx_code(self.provider_wx_code));
+        anal.bourgouin_wx_code = anal
+            .bourgouin_wx_code
+            .or_else(|| wx_code(self.bourgouin_wx_code));
+        anal.nssl_wx_code = anal.nssl_wx_code.or_else(|| wx_code(self.nssl_wx_code));
+
+        let snd = &anal.sounding;
+        if anal.mixed_layer.is_none() {
+            anal.mixed_layer = lift(self.mixed_layer, snd);
+        }
+        if anal.surface.is_none() {
+            anal.surface = lift(self.surface, snd);
+        }
+        if anal.most_unstable.is_none() {
+            anal.most_unstable = lift(self.most_unstable, snd);
+        }
+        if anal.convective.is_none() {
+            anal.convective = lift(self.convective, snd);
+        }
+        if anal.effective.is_none() {
+            anal.effective = lift(self.effective, snd);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use metfor::GigaWatts;
+
+    #[test]
+    fn test_stable_hasher() {
+        for (input, expected) in [
+            ("", 0xcbf2_9ce4_8422_2325),
+            ("a", 0xaf63_dc4c_8601_ec8c),
+            ("foobar", 0x8594_4171_f739_67e8),
+        ] {
+            let mut hasher = StableHasher::default();
+            hasher.write(input.as_bytes());
+            assert_eq!(hasher.finish(), expected);
+        }
+    }
+
+    #[test]
+    fn test_file_key_round_trip() {
+        let path = std::env::temp_dir().join(format!("sonde_cache_key_{}", std::process::id()));
+
+        std::fs::write(&path, "version one").unwrap();
+        let key = file_key(&path).unwrap();
+        assert_eq!(key, file_key(&path).unwrap());
+
+        // Same size, different contents.
+        std::fs::write(&path, "version two").unwrap();
+        let new_key = file_key(&path).unwrap();
+        assert_ne!(key, new_key);
+        assert_eq!(new_key, file_key(&path).unwrap());
+
+        // A copy finds the same entry.
+        let copy = path.with_extension("copy");
+        std::fs::copy(&path, &copy).unwrap();
+        assert_eq!(new_key, file_key(&copy).unwrap());
+
+        std::fs::remove_file(&copy).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(file_key(&path).is_err());
+    }
+
+    #[test]
+    fn test_cached_analysis_round_trip() {
+        let mut anal = Analysis::new(Sounding::new());
+        anal.precipitable_water = optional::some(Mm(25.4));
+        anal.right_mover = optional::some(WindUV {
+            u: MetersPSec(10.0),
+            v: MetersPSec(-5.0),
+        });
+        anal.sr_helicity_eff_rm = optional::some(IntHelicityM2pS2(150.0));
+        anal.hdw = optional::some(42.0);
+        anal.dcape = optional::some(JpKg(800.0));
+        anal.nssl_wx_code = Some(PrecipType::None);
+        anal.pft = Some(Pft {
+            pft: GigaWatts(5.0),
+            sp_curve: vec![(HectoPascal(900.0), Celsius(20.0))],
+            theta_curve: vec![(HectoPascal(600.0), Celsius(-5.0))],
+            p_fc: HectoPascal(600.0),
+            z_fc: Meters(4000.0),
+            theta_e_fc: metfor::Kelvin(330.0),
+            theta_ml: metfor::Kelvin(310.0),
+            q_ml: 0.008,
+            u_ml: MetersPSec(7.5),
+            d_theta: 1.5,
+        });
+
+        let cached = CachedAnalysis::from(&anal);
+        let serialized = serde_yaml::to_string(&cached).unwrap();
+        let cached_back: CachedAnalysis = serde_yaml::from_str(&serialized).unwrap();
+        assert_eq!(cached, cached_back);
+
+        let mut anal_back = Analysis::new(Sounding::new());
+        cached_back.apply(&mut anal_back);
+        assert_eq!(anal_back.precipitable_water, anal.precipitable_water);
+        assert_eq!(anal_back.right_mover, anal.right_mover);
+        assert_eq!(anal_back.sr_helicity_eff_rm, anal.sr_helicity_eff_rm);
+        assert_eq!(anal_back.hdw, anal.hdw);
+        assert_eq!(anal_back.dcape, anal.dcape);
+        assert_eq!(anal_back.nssl_wx_code, anal.nssl_wx_code);
+        assert_eq!(anal_back.pft, anal.pft);
+        assert!(anal_back.convective_t.is_none());
+        assert!(anal_back.mixed_layer.is_none());
+    }
+}
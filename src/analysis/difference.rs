@@ -0,0 +1,307 @@
+//! Level by level differences between two soundings.
+//!
+//! Both soundings are interpolated to a common set of pressure levels covering the part of the
+//! atmosphere they have in common, and then summarized as mean differences, or biases, over a few
+//! standard layers.
+use metfor::{HectoPascal, Knots, Quantity, WindSpdDir, WindUV};
+use sounding_analysis::{linear_interpolate_sounding, DataRow, Sounding};
+
+/// Spacing of the common pressure levels.
+const LEVEL_SPACING: HectoPascal = HectoPascal(10.0);
+
+/// Tops of the layers the biases are averaged over, each layer starts at the top of the one below
+/// it and the lowest one starts at the bottom of the profile.
+const BIAS_LAYER_TOPS: [HectoPascal; 5] = [
+    HectoPascal(850.0),
+    HectoPascal(700.0),
+    HectoPascal(500.0),
+    HectoPascal(300.0),
+    HectoPascal(200.0),
+];
+
+/// Differences between two soundings on common pressure levels, ordered from the bottom up.
+///
+/// Temperature and dew point differences are in Celsius. The wind difference is the vector
+/// difference of the winds, so its speed is never negative.
+#[derive(Clone, Debug, Default)]
+pub struct DifferenceProfile {
+    pub pressure: Vec<HectoPascal>,
+    pub temperature: Vec<Option<f64>>,
+    pub dew_point: Vec<Option<f64>>,
+    pub wind: Vec<Option<WindSpdDir<Knots>>>,
+}
+
+/// Mean differences over a layer.
+#[derive(Clone, Copy, Debug)]
+pub struct LayerBias {
+    pub bottom: HectoPascal,
+    pub top: HectoPascal,
+    pub temperature: Option<f64>,
+    pub dew_point: Option<f64>,
+    pub wind: Option<WindSpdDir<Knots>>,
+}
+
+/// The differences of each value at a single level, `sndg` minus `reference`.
+pub fn level_difference(
+    sndg: &DataRow,
+    reference: &DataRow,
+) -> (Option<f64>, Option<f64>, Option<WindSpdDir<Knots>>) {
+    let temperature = sndg
+        .temperature
+        .into_option()
+        .zip(reference.temperature.into_option())
+        .map(|(t, r)| t.unpack() - r.unpack());
+
+    let dew_point = sndg
+        .dew_point
+        .into_option()
+        .zip(reference.dew_point.into_option())
+        .map(|(dp, r)| dp.unpack() - r.unpack());
+
+    let wind = sndg
+        .wind
+        .into_option()
+        .zip(reference.wind.into_option())
+        .map(|(w, r)| {
+            let (w, r) = (WindUV::<Knots>::from(w), WindUV::<Knots>::from(r));
+            WindSpdDir::from(WindUV {
+                u: Knots(w.u.unpack() - r.u.unpack()),
+                v: Knots(w.v.unpack() - r.v.unpack()),
+            })
+        });
+
+    (temperature, dew_point, wind)
+}
+
+/// Interpolate both soundings to common pressure levels and find `sndg` minus `reference`.
+pub fn difference(sndg: &Sounding, reference: &Sounding) -> DifferenceProfile {
+    let mut diff = DifferenceProfile::default();
+
+    for p in common_levels(&[sndg, reference]) {
+        let (row, ref_row) = match (
+            linear_interpolate_sounding(sndg, p),
+            linear_interpolate_sounding(reference, p),
+        ) {
+            (Ok(row), Ok(ref_row)) => (row, ref_row),
+            _ => continue,
+        };
+
+        let (temperature, dew_point, wind) = level_difference(&row, &ref_row);
+
+        diff.pressure.push(p);
+        diff.temperature.push(temperature);
+        diff.dew_point.push(dew_point);
+        diff.wind.push(wind);
+    }
+
+    diff
+}
+
+/// Average the differences over standard layers, skipping layers with no levels in them.
+pub fn layer_biases(diff: &DifferenceProfile) -> Vec<LayerBias> {
+    let bottom = match diff.pressure.first() {
+        Some(&bottom) => bottom,
+        None => return vec![],
+    };
+
+    let mean = |vals: &[Option<f64>], range: std::ops::Range<usize>| -> Option<f64> {
+        let (sum, count) = vals[range]
+            .iter()
+            .flatten()
+            .fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+
+        (count > 0).then_some(sum / count as f64)
+    };
+
+    // Average the wind differences as vectors too.
+    let mean_wind = |range: std::ops::Range<usize>| -> Option<WindSpdDir<Knots>> {
+        let (u, v, count) =
+            diff.wind[range]
+                .iter()
+                .flatten()
+                .fold((0.0, 0.0, 0), |(u, v, count), &wind| {
+                    let uv = WindUV::<Knots>::from(wind);
+                    (u + uv.u.unpack(), v + uv.v.unpack(), count + 1)
+                });
+
+        (count > 0).then(|| {
+            WindSpdDir::from(WindUV {
+                u: Knots(u / count as f64),
+                v: Knots(v / count as f64),
+            })
+        })
+    };
+
+    let mut biases = vec![];
+    let mut layer_bottom = bottom;
+    for &layer_top in BIAS_LAYER_TOPS.iter().filter(|&&top| top < bottom) {
+        let start = diff.pressure.iter().position(|&p| p <= layer_bottom);
+        let end = diff.pressure.iter().rposition(|&p| p >= layer_top);
+
+        if let (Some(start), Some(end)) = (start, end) {
+            if start <= end {
+                let range = start..(end + 1);
+                biases.push(LayerBias {
+                    bottom: layer_bottom,
+                    top: layer_top,
+                    temperature: mean(&diff.temperature, range.clone()),
+                    dew_point: mean(&diff.dew_point, range.clone()),
+                    wind: mean_wind(range),
+                });
+            }
+        }
+
+        layer_bottom = layer_top;
+    }
+
+    biases
+}
+
+/// Pressure levels, from the bottom up, covering the part of the atmosphere every sounding in
+/// `sndgs` has data for.
+pub fn common_levels(sndgs: &[&Sounding]) -> Vec<HectoPascal> {
+    let range = sndgs
+        .iter()
+        .map(|sndg| pressure_range(sndg))
+        .try_fold((f64::INFINITY, 0.0f64), |(bottom, top), range| {
+            range.map(|(b, t)| (bottom.min(b.unpack()), top.max(t.unpack())))
+        });
+
+    let (bottom, top) = match range {
+        Some((bottom, top)) if bottom.is_finite() && bottom > top => (bottom, top),
+        _ => return vec![],
+    };
+
+    // Start at the bottom, then step up along even multiples of the spacing.
+    let spacing = LEVEL_SPACING.unpack();
+    let first_even = (bottom / spacing).floor() * spacing;
+    std::iter::once(bottom)
+        .chain(
+            (0..)
+                .map(|i| first_even - i as f64 * spacing)
+                .skip_while(|&p| p >= bottom)
+                .take_while(|&p| p >= top),
+        )
+        .map(HectoPascal)
+        .collect()
+}
+
+/// The highest and lowest pressure in a sounding.
+fn pressure_range(sndg: &Sounding) -> Option<(HectoPascal, HectoPascal)> {
+    sndg.pressure_profile()
+        .iter()
+        .filter_map(|p| p.into_option())
+        .fold(None, |range, p| match range {
+            None => Some((p, p)),
+            Some((bottom, top)) => Some((
+                HectoPascal(bottom.unpack().max(p.unpack())),
+                HectoPascal(top.unpack().min(p.unpack())),
+            )),
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use metfor::Celsius;
+
+    fn row(temperature: f64, direction: f64, speed: f64) -> DataRow {
+        DataRow {
+            temperature: optional::some(Celsius(temperature)),
+            wind: optional::some(WindSpdDir {
+                speed: Knots(speed),
+                direction,
+            }),
+            ..DataRow::default()
+        }
+    }
+
+    fn assert_wind(wind: Option<WindSpdDir<Knots>>, direction: f64, speed: f64) {
+        let wind = wind.unwrap();
+        println!("wind = {:?}", wind);
+        assert!((wind.speed.unpack() - speed).abs() < 1.0e-6);
+        if speed > 0.0 {
+            let delta = (wind.direction - direction).rem_euclid(360.0);
+            assert!(delta < 1.0e-6 || delta > 360.0 - 1.0e-6);
+        }
+    }
+
+    #[test]
+    fn test_level_difference() {
+        // Same speed from opposite directions, the speeds alone would show no difference.
+        let (t, dp, wind) = level_difference(&row(12.0, 270.0, 20.0), &row(10.0, 90.0, 20.0));
+        assert!((t.unwrap() - 2.0).abs() < 1.0e-9);
+        assert!(dp.is_none());
+        assert_wind(wind, 270.0, 40.0);
+
+        // A north wind minus an east wind leaves a northwest wind.
+        let (_, _, wind) = level_difference(&row(0.0, 360.0, 10.0), &row(0.0, 90.0, 10.0));
+        assert_wind(wind, 315.0, 200.0f64.sqrt());
+
+        // Identical winds have no difference.
+        let (_, _, wind) = level_difference(&row(0.0, 225.0, 35.0), &row(0.0, 225.0, 35.0));
+        assert_wind(wind, 0.0, 0.0);
+    }
+
+    #[test]
+    fn test_layer_biases() {
+        let wind = |direction, speed| {
+            Some(WindSpdDir {
+                speed: Knots(speed),
+                direction,
+            })
+        };
+
+        let diff = DifferenceProfile {
+            pressure: vec![HectoPascal(1000.0), HectoPascal(900.0), HectoPascal(800.0)],
+            temperature: vec![Some(1.0), Some(3.0), None],
+            dew_point: vec![None, Some(-2.0), Some(4.0)],
+            wind: vec![wind(270.0, 10.0), wind(90.0, 10.0), wind(180.0, 5.0)],
+        };
+
+        let biases = layer_biases(&diff);
+        assert_eq!(biases.len(), 2);
+
+        let (low, high) = (&biases[0], &biases[1]);
+        assert_eq!(
+            (low.bottom, low.top),
+            (HectoPascal(1000.0), HectoPascal(850.0))
+        );
+        assert!((low.temperature.unwrap() - 2.0).abs() < 1.0e-9);
+        assert!((low.dew_point.unwrap() + 2.0).abs() < 1.0e-9);
+        // Opposite winds cancel when averaged as vectors.
+        assert_wind(low.wind, 0.0, 0.0);
+
+        assert_eq!(
+            (high.bottom, high.top),
+            (HectoPascal(850.0), HectoPascal(700.0))
+        );
+        assert!(high.temperature.is_none());
+        assert!((high.dew_point.unwrap() - 4.0).abs() < 1.0e-9);
+        assert_wind(high.wind, 180.0, 5.0);
+    }
+
+    #[test]
+    fn test_common_levels() {
+        let sndg = |pressures: &[f64]| {
+            Sounding::new().with_pressure_profile(
+                pressures
+                    .iter()
+                    .map(|&p| optional::some(HectoPascal(p)))
+                    .collect(),
+            )
+        };
+
+        let low = sndg(&[1000.0, 850.0, 500.0, 300.0]);
+        let high = sndg(&[955.0, 700.0, 200.0]);
+
+        let levels = common_levels(&[&low, &high]);
+        assert_eq!(levels.first(), Some(&HectoPascal(955.0)));
+        assert_eq!(levels.get(1), Some(&HectoPascal(950.0)));
+        assert_eq!(levels.last(), Some(&HectoPascal(300.0)));
+        assert_eq!(levels.len(), 67);
+        assert!(levels.windows(2).all(|pair| pair[0] > pair[1]));
+
+        assert!(common_levels(&[&low, &Sounding::new()]).is_empty());
+    }
+}
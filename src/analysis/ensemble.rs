@@ -0,0 +1,197 @@
+//! Statistics describing the spread of an ensemble of soundings valid at the same time.
+use super::difference::common_levels;
+use metfor::{HectoPascal, Quantity};
+use sounding_analysis::{linear_interpolate_sounding, PrecipType, Sounding};
+
+/// Smallest, middle, and largest value of the members at one level.
+#[derive(Clone, Copy, Debug)]
+pub struct Spread {
+    pub min: f64,
+    pub median: f64,
+    pub max: f64,
+}
+
+/// The spread of the members' temperature and dew point in Celsius, ordered from the bottom up.
+#[derive(Clone, Debug, Default)]
+pub struct Envelope {
+    pub pressure: Vec<HectoPascal>,
+    pub temperature: Vec<Option<Spread>>,
+    pub dew_point: Vec<Option<Spread>>,
+}
+
+/// Interpolate every member to common pressure levels and find the spread at each one.
+pub fn envelope(members: &[&Sounding]) -> Envelope {
+    let mut env = Envelope::default();
+
+    for p in common_levels(members) {
+        let rows: Vec<_> = members
+            .iter()
+            .filter_map(|sndg| linear_interpolate_sounding(sndg, p).ok())
+            .collect();
+
+        let temperature = rows
+            .iter()
+            .filter_map(|row| row.temperature.into_option())
+            .map(|t| t.unpack())
+            .collect();
+        let dew_point = rows
+            .iter()
+            .filter_map(|row| row.dew_point.into_option())
+            .map(|dp| dp.unpack())
+            .collect();
+
+        env.pressure.push(p);
+        env.temperature.push(spread(temperature));
+        env.dew_point.push(spread(dew_point));
+    }
+
+    env
+}
+
+/// Find the spread of some values, or `None` if there are none.
+pub fn spread(mut vals: Vec<f64>) -> Option<Spread> {
+    vals.sort_by(|a, b| a.total_cmp(b));
+
+    Some(Spread {
+        min: *vals.first()?,
+        median: percentile(&vals, 50.0)?,
+        max: *vals.last()?,
+    })
+}
+
+/// Linearly interpolated percentile, from 0 to 100, of values sorted in ascending order.
+pub fn percentile(sorted: &[f64], pct: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+
+    let rank = pct.clamp(0.0, 100.0) / 100.0 * (sorted.len() - 1) as f64;
+    let below = rank.floor() as usize;
+    let above = rank.ceil() as usize;
+    let weight = rank - below as f64;
+
+    Some(sorted[below] * (1.0 - weight) + sorted[above] * weight)
+}
+
+/// Count how many members forecast each precipitation type, most votes first.
+pub fn precip_type_votes<I>(types: I) -> Vec<(PrecipType, usize)>
+where
+    I: Iterator<Item = PrecipType>,
+{
+    let mut votes: Vec<(PrecipType, usize)> = vec![];
+
+    for precip_type in types {
+        match votes.iter_mut().find(|(pt, _)| *pt == precip_type) {
+            Some((_, count)) => *count += 1,
+            None => votes.push((precip_type, 1)),
+        }
+    }
+
+    // Stable sort, so ties stay in the order they were first seen.
+    votes.sort_by(|(_, left), (_, right)| right.cmp(left));
+
+    votes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use metfor::Celsius;
+    use optional::{none, some};
+
+    fn assert_spread(spread: Option<Spread>, min: f64, median: f64, max: f64) {
+        let spread = spread.unwrap();
+        println!("{:?}", spread);
+        assert!((spread.min - min).abs() < 1.0e-6);
+        assert!((spread.median - median).abs() < 1.0e-6);
+        assert!((spread.max - max).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let vals = [1.0, 2.0, 3.0, 4.0];
+
+        assert_eq!(percentile(&vals, 0.0), Some(1.0));
+        assert_eq!(percentile(&vals, 25.0), Some(1.75));
+        assert_eq!(percentile(&vals, 50.0), Some(2.5));
+        assert_eq!(percentile(&vals, 100.0), Some(4.0));
+        assert_eq!(percentile(&vals, 150.0), Some(4.0));
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+
+    #[test]
+    fn test_spread() {
+        assert_spread(spread(vec![3.0, -1.0, 2.0]), -1.0, 2.0, 3.0);
+        assert_spread(spread(vec![5.0]), 5.0, 5.0, 5.0);
+        assert!(spread(vec![]).is_none());
+    }
+
+    #[test]
+    fn test_envelope() {
+        let sndg = |pressure: &[f64], temperature: &[f64], dew_point: &[Option<f64>]| {
+            Sounding::new()
+                .with_pressure_profile(pressure.iter().map(|&p| some(HectoPascal(p))).collect())
+                .with_temperature_profile(temperature.iter().map(|&t| some(Celsius(t))).collect())
+                .with_dew_point_profile(
+                    dew_point
+                        .iter()
+                        .map(|dp| dp.map(|dp| some(Celsius(dp))).unwrap_or_else(none))
+                        .collect(),
+                )
+        };
+
+        let levels = [1000.0, 900.0, 800.0];
+        let warm = sndg(&levels, &[22.0, 12.0, 2.0], &[None, None, None]);
+        let middle = sndg(
+            &levels,
+            &[20.0, 10.0, 0.0],
+            &[Some(10.0), Some(5.0), Some(0.0)],
+        );
+        // Higher ground, so the envelope starts at 950 hPa.
+        let cold = sndg(
+            &[950.0, 900.0, 800.0],
+            &[13.0, 8.0, -2.0],
+            &[Some(5.0), Some(3.0), Some(-5.0)],
+        );
+
+        let env = envelope(&[&warm, &middle, &cold]);
+        assert_eq!(env.pressure.first(), Some(&HectoPascal(950.0)));
+        assert_eq!(env.pressure.last(), Some(&HectoPascal(800.0)));
+        assert_eq!(env.pressure.len(), 16);
+        assert_eq!(env.temperature.len(), env.pressure.len());
+        assert_eq!(env.dew_point.len(), env.pressure.len());
+
+        // Half way between the two lowest levels of the other members.
+        assert_spread(env.temperature[0], 13.0, 15.0, 17.0);
+        assert_spread(env.dew_point[0], 5.0, 6.25, 7.5);
+
+        assert_spread(*env.temperature.last().unwrap(), -2.0, 0.0, 2.0);
+        assert_spread(*env.dew_point.last().unwrap(), -5.0, -2.5, 0.0);
+
+        let env = envelope(&[&warm]);
+        assert!(env.dew_point.iter().all(|dp| dp.is_none()));
+    }
+
+    #[test]
+    fn test_precip_type_votes() {
+        let rain = PrecipType::from(61);
+        let snow = PrecipType::from(71);
+
+        let votes = precip_type_votes(
+            [
+                PrecipType::None,
+                rain,
+                snow,
+                snow,
+                rain,
+                snow,
+                PrecipType::None,
+            ]
+            .into_iter(),
+        );
+
+        // Ties stay in the order they were first seen.
+        assert_eq!(votes, vec![(snow, 3), (PrecipType::None, 2), (rain, 2)]);
+        assert!(precip_type_votes(std::iter::empty()).is_empty());
+    }
+}
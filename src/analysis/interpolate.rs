@@ -0,0 +1,329 @@
+//! Synthetic soundings at times between two loaded ones.
+//!
+//! The later sounding is interpolated to the pressure levels of the earlier one, and then every
+//! value is interpolated linearly in time level by level. Winds are interpolated by components so
+//! a wind that veers through calm doesn't swing the long way around.
+use super::Analysis;
+use chrono::NaiveDateTime;
+use metfor::{Knots, Quantity, WindSpdDir, WindUV};
+use optional::{none, some, Noned, Optioned};
+use sounding_analysis::{linear_interpolate_sounding, Sounding};
+use std::collections::HashMap;
+
+/// Build an analysis of the sounding valid at `valid_time`, which must be between the valid times
+/// of `before` and `after`.
+///
+/// The result has none of the analysis filled in, and it is marked as synthetic.
+pub fn interpolate_in_time(
+    before: &Analysis,
+    after: &Analysis,
+    valid_time: NaiveDateTime,
+) -> Option<Analysis> {
+    let start = before.sounding().valid_time()?;
+    let end = after.sounding().valid_time()?;
+    if start >= end || valid_time < start || valid_time > end {
+        return None;
+    }
+
+    let weight = (valid_time - start).num_seconds() as f64 / (end - start).num_seconds() as f64;
+
+    let lead_time: Option<i32> = before.sounding().lead_time().into_option().map(|lt| {
+        let hours = (valid_time - start).num_minutes() as f64 / 60.0;
+        lt + hours.round() as i32
+    });
+
+    let description = format!(
+        "{} (interpolated between {} and {})",
+        before.sounding().source_description().unwrap_or("Sounding"),
+        start.format("%d/%H%MZ"),
+        end.format("%d/%H%MZ"),
+    );
+
+    let snd = interpolate_sounding(before.sounding(), after.sounding(), weight)
+        .with_valid_time(Some(valid_time))
+        .with_lead_time(Optioned::from(lead_time))
+        .with_source_description(Some(description));
+
+    let provider_analysis = before
+        .provider_analysis()
+        .iter()
+        .filter_map(|(&key, &val)| {
+            let other = *after.provider_analysis().get(key)?;
+            Some((key, lerp(val, other, weight)))
+        })
+        .collect::<HashMap<_, _>>();
+
+    Some(
+        Analysis::new(snd)
+            .with_provider_analysis(provider_analysis)
+            .with_settings(before.settings())
+            .with_custom_parcels(before.custom_parcel_sources())
+            .with_synthetic(true),
+    )
+}
+
+/// Interpolate every level of `before` a fraction `weight` of the way towards `after`.
+fn interpolate_sounding(before: &Sounding, after: &Sounding, weight: f64) -> Sounding {
+    let num_levels = before.pressure_profile().len();
+
+    let mut temperature = Vec::with_capacity(num_levels);
+    let mut wet_bulb = Vec::with_capacity(num_levels);
+    let mut dew_point = Vec::with_capacity(num_levels);
+    let mut theta_e = Vec::with_capacity(num_levels);
+    let mut wind = Vec::with_capacity(num_levels);
+    let mut pvv = Vec::with_capacity(num_levels);
+    let mut height = Vec::with_capacity(num_levels);
+    let mut cloud_fraction = Vec::with_capacity(num_levels);
+
+    // Not every profile is present in every sounding, so missing ones are treated as missing
+    // values.
+    fn level<T: Noned + Copy>(profile: &[Optioned<T>], i: usize) -> Optioned<T> {
+        profile.get(i).copied().unwrap_or_else(none)
+    }
+
+    for (i, p) in before.pressure_profile().iter().enumerate() {
+        let other = match p
+            .into_option()
+            .and_then(|p| linear_interpolate_sounding(after, p).ok())
+        {
+            Some(row) => row,
+            None => {
+                temperature.push(none());
+                wet_bulb.push(none());
+                dew_point.push(none());
+                theta_e.push(none());
+                wind.push(none());
+                pvv.push(none());
+                height.push(none());
+                cloud_fraction.push(none());
+                continue;
+            }
+        };
+
+        let t = level(before.temperature_profile(), i);
+        let wb = level(before.wet_bulb_profile(), i);
+        let dp = level(before.dew_point_profile(), i);
+        let te = level(before.theta_e_profile(), i);
+        let w = level(before.wind_profile(), i);
+        let omega = level(before.pvv_profile(), i);
+        let hgt = level(before.height_profile(), i);
+        let cloud = level(before.cloud_fraction_profile(), i);
+
+        temperature.push(blend(t, other.temperature, weight));
+        wet_bulb.push(blend(wb, other.wet_bulb, weight));
+        dew_point.push(blend(dp, other.dew_point, weight));
+        theta_e.push(blend(te, other.theta_e, weight));
+        wind.push(blend_wind(w, other.wind, weight));
+        pvv.push(blend(omega, other.pvv, weight));
+        height.push(blend(hgt, other.height, weight));
+        cloud_fraction.push(
+            match (cloud.into_option(), other.cloud_fraction.into_option()) {
+                (Some(c0), Some(c1)) => some(lerp(c0, c1, weight)),
+                _ => none(),
+            },
+        );
+    }
+
+    before
+        .clone()
+        .with_temperature_profile(temperature)
+        .with_wet_bulb_profile(wet_bulb)
+        .with_dew_point_profile(dew_point)
+        .with_theta_e_profile(theta_e)
+        .with_wind_profile(wind)
+        .with_pvv_profile(pvv)
+        .with_height_profile(height)
+        .with_cloud_fraction_profile(cloud_fraction)
+        .with_station_pressure(blend(
+            before.station_pressure(),
+            after.station_pressure(),
+            weight,
+        ))
+        .with_sfc_temperature(blend(
+            before.sfc_temperature(),
+            after.sfc_temperature(),
+            weight,
+        ))
+        .with_sfc_dew_point(blend(before.sfc_dew_point(), after.sfc_dew_point(), weight))
+        .with_sfc_wind(blend_wind(before.sfc_wind(), after.sfc_wind(), weight))
+}
+
+fn lerp(start: f64, end: f64, weight: f64) -> f64 {
+    start + weight * (end - start)
+}
+
+fn blend<T>(start: Optioned<T>, end: Optioned<T>, weight: f64) -> Optioned<T>
+where
+    T: Quantity + Noned,
+{
+    match (start.into_option(), end.into_option()) {
+        (Some(start), Some(end)) => some(T::pack(lerp(start.unpack(), end.unpack(), weight))),
+        _ => none(),
+    }
+}
+
+fn blend_wind(
+    start: Optioned<WindSpdDir<Knots>>,
+    end: Optioned<WindSpdDir<Knots>>,
+    weight: f64,
+) -> Optioned<WindSpdDir<Knots>> {
+    match (start.into_option(), end.into_option()) {
+        (Some(start), Some(end)) => {
+            let start = WindUV::<Knots>::from(start);
+            let end = WindUV::<Knots>::from(end);
+
+            some(WindSpdDir::<Knots>::from(WindUV {
+                u: Knots(lerp(start.u.unpack(), end.u.unpack(), weight)),
+                v: Knots(lerp(start.v.unpack(), end.v.unpack(), weight)),
+            }))
+        }
+        _ => none(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::NaiveDate;
+    use metfor::{Celsius, HectoPascal, Meters};
+
+    fn time(hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 7, 1)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    fn sndg(
+        hour: u32,
+        pressure: &[f64],
+        temperature: &[f64],
+        height: &[f64],
+        wind: f64,
+    ) -> Sounding {
+        Sounding::new()
+            .with_valid_time(Some(time(hour)))
+            .with_lead_time(some(hour as i32))
+            .with_pressure_profile(pressure.iter().map(|&p| some(HectoPascal(p))).collect())
+            .with_temperature_profile(temperature.iter().map(|&t| some(Celsius(t))).collect())
+            .with_height_profile(height.iter().map(|&h| some(Meters(h))).collect())
+            .with_wind_profile(
+                pressure
+                    .iter()
+                    .map(|_| {
+                        some(WindSpdDir {
+                            speed: Knots(10.0),
+                            direction: wind,
+                        })
+                    })
+                    .collect(),
+            )
+    }
+
+    fn before() -> Analysis {
+        let snd = sndg(
+            0,
+            &[1000.0, 900.0, 800.0],
+            &[20.0, 12.0, 4.0],
+            &[100.0, 1000.0, 2000.0],
+            270.0,
+        );
+
+        Analysis::new(snd).with_provider_analysis(HashMap::from([("CAPE", 100.0), ("LI", -2.0)]))
+    }
+
+    fn after() -> Analysis {
+        // The ground is higher, so 1000 hPa is below ground and none of the levels match.
+        let snd = sndg(
+            6,
+            &[950.0, 850.0, 750.0],
+            &[10.0, 2.0, -6.0],
+            &[800.0, 1800.0, 2800.0],
+            90.0,
+        );
+
+        Analysis::new(snd).with_provider_analysis(HashMap::from([("CAPE", 400.0)]))
+    }
+
+    fn values<T: Quantity + Noned>(profile: &[Optioned<T>]) -> Vec<Option<f64>> {
+        profile
+            .iter()
+            .map(|val| val.into_option().map(|val| val.unpack()))
+            .collect()
+    }
+
+    fn assert_close(vals: Vec<Option<f64>>, expected: &[Option<f64>]) {
+        println!("{:?} == {:?}", vals, expected);
+        assert_eq!(vals.len(), expected.len());
+        for (val, expected) in vals.into_iter().zip(expected) {
+            match (val, expected) {
+                (Some(val), Some(expected)) => assert!((val - expected).abs() < 1.0e-6),
+                (val, expected) => assert_eq!(val, *expected),
+            }
+        }
+    }
+
+    #[test]
+    fn test_interpolate_onto_pressure_levels() {
+        let anal = interpolate_in_time(&before(), &after(), time(2)).unwrap();
+        let snd = anal.sounding();
+
+        assert!(anal.is_synthetic());
+        assert_eq!(snd.valid_time(), Some(time(2)));
+        assert_eq!(snd.lead_time().into_option(), Some(2));
+
+        // The levels of the earlier sounding are kept.
+        assert_close(
+            values(snd.pressure_profile()),
+            &[Some(1000.0), Some(900.0), Some(800.0)],
+        );
+
+        // The later sounding is 6C and 1300 m at 900 hPa and -2C and 2300 m at 800 hPa, this is a
+        // third of the way there. There is nothing to interpolate towards below its ground.
+        assert_close(
+            values(snd.temperature_profile()),
+            &[None, Some(10.0), Some(2.0)],
+        );
+        assert_close(
+            values(snd.height_profile()),
+            &[None, Some(1100.0), Some(2100.0)],
+        );
+
+        // A west wind turning to an east wind slows down instead of veering through north.
+        let winds: Vec<Option<WindSpdDir<Knots>>> =
+            snd.wind_profile().iter().map(|w| w.into_option()).collect();
+        assert!(winds[0].is_none());
+        for wind in winds[1..].iter() {
+            let wind = wind.unwrap();
+            assert!((wind.speed.unpack() - 10.0 / 3.0).abs() < 1.0e-6);
+            assert!((wind.direction - 270.0).abs() < 1.0e-6);
+        }
+
+        // Only the values both soundings have are interpolated.
+        let provider = anal.provider_analysis();
+        assert_eq!(provider.len(), 1);
+        assert!((provider["CAPE"] - 200.0).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_interpolate_at_the_ends() {
+        let (before, after) = (before(), after());
+
+        let anal = interpolate_in_time(&before, &after, time(0)).unwrap();
+        assert_close(
+            values(anal.sounding().temperature_profile()),
+            &[None, Some(12.0), Some(4.0)],
+        );
+
+        let anal = interpolate_in_time(&before, &after, time(6)).unwrap();
+        assert_close(
+            values(anal.sounding().temperature_profile()),
+            &[None, Some(6.0), Some(-2.0)],
+        );
+
+        // Only times between the two soundings, in order.
+        assert!(interpolate_in_time(&before, &after, time(7)).is_none());
+        assert!(interpolate_in_time(&after, &before, time(2)).is_none());
+        assert!(interpolate_in_time(&before, &before, time(0)).is_none());
+    }
+}
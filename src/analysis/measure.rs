@@ -0,0 +1,238 @@
+//! Properties of a layer between two levels picked on the skew-t.
+//!
+//! The layer is bounded by values interpolated to the two levels, and the averages are weighted
+//! by pressure using the levels in the sounding between them.
+use metfor::{mixing_ratio, rh, HectoPascal, Knots, Meters, Mm, Quantity, WindSpdDir, WindUV};
+use sounding_analysis::{mean_wind, pressure_layer, DataRow, Sounding};
+
+/// Acceleration of gravity, m/s^2.
+const GRAVITY: f64 = 9.81;
+
+/// Measurements of a layer, any that are missing data are `None`.
+#[derive(Clone, Copy, Debug)]
+pub struct LayerMeasurement {
+    pub bottom: HectoPascal,
+    pub top: HectoPascal,
+    /// Geopotential thickness of the layer.
+    pub thickness: Option<Meters>,
+    /// Temperature decrease with height, C/km.
+    pub lapse_rate: Option<f64>,
+    /// Mean relative humidity, percent.
+    pub mean_rh: Option<f64>,
+    /// Mean mixing ratio, g/kg.
+    pub mean_mixing_ratio: Option<f64>,
+    /// Wind at the top minus the wind at the bottom.
+    pub bulk_shear: Option<WindSpdDir<Knots>>,
+    pub mean_wind: Option<WindSpdDir<Knots>>,
+    /// Precipitable water in the layer.
+    pub precipitable_water: Option<Mm>,
+}
+
+impl LayerMeasurement {
+    /// Pressure depth of the layer.
+    pub fn depth(&self) -> HectoPascal {
+        HectoPascal(self.bottom.unpack() - self.top.unpack())
+    }
+}
+
+/// Measure the layer between two pressure levels, given in either order.
+pub fn measure_layer(snd: &Sounding, p0: HectoPascal, p1: HectoPascal) -> Option<LayerMeasurement> {
+    let (bottom_p, top_p) = if p0 > p1 { (p0, p1) } else { (p1, p0) };
+    if bottom_p == top_p {
+        return None;
+    }
+
+    let layer = pressure_layer(snd, bottom_p, top_p).ok()?;
+    let (bottom, top) = (layer.bottom, layer.top);
+
+    // The ends of the layer with the levels in between, from the bottom up.
+    let rows: Vec<DataRow> = std::iter::once(bottom)
+        .chain(snd.bottom_up().filter(|row| {
+            row.pressure
+                .into_option()
+                .map(|p| p < bottom_p && p > top_p)
+                .unwrap_or(false)
+        }))
+        .chain(std::iter::once(top))
+        .collect();
+
+    let thickness = bottom
+        .height
+        .into_option()
+        .zip(top.height.into_option())
+        .map(|(b, t)| Meters(t.unpack() - b.unpack()));
+
+    let lapse_rate = bottom
+        .temperature
+        .into_option()
+        .zip(top.temperature.into_option())
+        .zip(thickness)
+        .filter(|(_, dz)| dz.unpack() > 0.0)
+        .map(|((b, t), dz)| (b.unpack() - t.unpack()) / (dz.unpack() / 1000.0));
+
+    let mean_rh = pressure_weighted_mean(&rows, |row| {
+        let (t, dp) = (row.temperature.into_option()?, row.dew_point.into_option()?);
+        rh(t, dp).map(|rh| rh * 100.0)
+    });
+
+    let mean_mixing_ratio = pressure_weighted_mean(&rows, |row| {
+        mixing_ratio(row.dew_point.into_option()?, row.pressure.into_option()?)
+    });
+
+    // The mean mixing ratio times the mass of the layer, kg/m^2 is the same as mm of water.
+    let precipitable_water =
+        mean_mixing_ratio.map(|mw| Mm(mw * layer_depth_pa(bottom_p, top_p) / GRAVITY));
+
+    let bulk_shear = bottom
+        .wind
+        .into_option()
+        .zip(top.wind.into_option())
+        .map(|(b, t)| {
+            let (b, t) = (WindUV::<Knots>::from(b), WindUV::<Knots>::from(t));
+            WindSpdDir::from(WindUV {
+                u: Knots(t.u.unpack() - b.u.unpack()),
+                v: Knots(t.v.unpack() - b.v.unpack()),
+            })
+        });
+
+    let mean_wind = mean_wind(&layer, snd).ok().map(WindSpdDir::<Knots>::from);
+
+    Some(LayerMeasurement {
+        bottom: bottom_p,
+        top: top_p,
+        thickness,
+        lapse_rate,
+        mean_rh,
+        mean_mixing_ratio: mean_mixing_ratio.map(|mw| mw * 1000.0),
+        bulk_shear,
+        mean_wind,
+        precipitable_water,
+    })
+}
+
+/// Average a value over the rows using the trapezoid rule in pressure, skipping the parts of the
+/// layer where it is missing.
+fn pressure_weighted_mean<F>(rows: &[DataRow], value: F) -> Option<f64>
+where
+    F: Fn(&DataRow) -> Option<f64>,
+{
+    let (sum, weight) = rows
+        .windows(2)
+        .filter_map(|pair| {
+            let (p0, p1) = (
+                pair[0].pressure.into_option()?,
+                pair[1].pressure.into_option()?,
+            );
+            let (v0, v1) = (value(&pair[0])?, value(&pair[1])?);
+            let dp = p0.unpack() - p1.unpack();
+
+            Some(((v0 + v1) / 2.0 * dp, dp))
+        })
+        .fold((0.0, 0.0), |(sum, weight), (val, dp)| {
+            (sum + val, weight + dp)
+        });
+
+    if weight > 0.0 {
+        Some(sum / weight)
+    } else {
+        None
+    }
+}
+
+fn layer_depth_pa(bottom: HectoPascal, top: HectoPascal) -> f64 {
+    (bottom.unpack() - top.unpack()) * 100.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use metfor::Celsius;
+    use optional::some;
+
+    /// Saturated, cooling 6C/km above 900 hPa, and the wind picking up 5 knots every 100 hPa.
+    fn sndg() -> Sounding {
+        let pressure = [1000.0, 900.0, 800.0, 700.0];
+        let height = [100.0, 1000.0, 2000.0, 3000.0];
+        let temperature = [20.0, 14.0, 8.0, 2.0];
+        let speed = [5.0, 10.0, 15.0, 20.0];
+
+        Sounding::new()
+            .with_pressure_profile(pressure.iter().map(|&p| some(HectoPascal(p))).collect())
+            .with_height_profile(height.iter().map(|&h| some(Meters(h))).collect())
+            .with_temperature_profile(temperature.iter().map(|&t| some(Celsius(t))).collect())
+            .with_dew_point_profile(temperature.iter().map(|&t| some(Celsius(t))).collect())
+            .with_wind_profile(
+                speed
+                    .iter()
+                    .map(|&spd| {
+                        some(WindSpdDir {
+                            speed: Knots(spd),
+                            direction: 270.0,
+                        })
+                    })
+                    .collect(),
+            )
+    }
+
+    fn assert_close(val: Option<f64>, expected: f64) {
+        println!("{:?} == {}", val, expected);
+        assert!((val.unwrap() - expected).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_measure_layer() {
+        let snd = sndg();
+        let layer = measure_layer(&snd, HectoPascal(900.0), HectoPascal(700.0)).unwrap();
+
+        assert_eq!(layer.bottom, HectoPascal(900.0));
+        assert_eq!(layer.top, HectoPascal(700.0));
+        assert_eq!(layer.depth(), HectoPascal(200.0));
+
+        // 3000 m - 1000 m, and 12C cooler over those 2 km.
+        assert_close(layer.thickness.map(|dz| dz.unpack()), 2000.0);
+        assert_close(layer.lapse_rate, 6.0);
+
+        assert_close(layer.mean_rh, 100.0);
+
+        // Trapezoids over the two 100 hPa layers, with the middle level counted in both.
+        let w = |t: f64, p: f64| mixing_ratio(Celsius(t), HectoPascal(p)).unwrap();
+        let mean_w = (w(14.0, 900.0) + 2.0 * w(8.0, 800.0) + w(2.0, 700.0)) / 4.0;
+        assert_close(layer.mean_mixing_ratio, mean_w * 1000.0);
+        assert_close(
+            layer.precipitable_water.map(|pw| pw.unpack()),
+            mean_w * 20_000.0 / 9.81,
+        );
+
+        let shear = layer.bulk_shear.unwrap();
+        assert_close(Some(shear.speed.unpack()), 10.0);
+        assert_close(Some(shear.direction), 270.0);
+
+        let mean_wind = layer.mean_wind.unwrap();
+        assert_close(Some(mean_wind.speed.unpack()), 15.0);
+        assert_close(Some(mean_wind.direction), 270.0);
+    }
+
+    #[test]
+    fn test_measure_layer_ends() {
+        let snd = sndg();
+
+        // The levels can be picked in either order.
+        let up = measure_layer(&snd, HectoPascal(1000.0), HectoPascal(800.0)).unwrap();
+        let down = measure_layer(&snd, HectoPascal(800.0), HectoPascal(1000.0)).unwrap();
+        assert_eq!((up.bottom, up.top), (down.bottom, down.top));
+        assert_eq!(up.mean_mixing_ratio, down.mean_mixing_ratio);
+
+        // 1900 m deep with 12C of cooling.
+        assert_close(up.thickness.map(|dz| dz.unpack()), 1900.0);
+        assert_close(up.lapse_rate, 12.0 / 1.9);
+
+        assert!(measure_layer(&snd, HectoPascal(850.0), HectoPascal(850.0)).is_none());
+
+        // Without heights there is no thickness or lapse rate, but the rest still works.
+        let snd = snd.with_height_profile(vec![optional::none(); 4]);
+        let layer = measure_layer(&snd, HectoPascal(900.0), HectoPascal(700.0)).unwrap();
+        assert!(layer.thickness.is_none());
+        assert!(layer.lapse_rate.is_none());
+        assert_close(layer.mean_rh, 100.0);
+    }
+}
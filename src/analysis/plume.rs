@@ -0,0 +1,94 @@
+//! The results of the plume analyses in a form that can be saved in the on-disk cache.
+//!
+//! The types from `sounding_analysis` can't be serialized, so only the values this program uses
+//! are copied out of them.
+use metfor::{Celsius, GigaWatts, HectoPascal, Kelvin, Meters, MetersPSec, Quantity};
+use serde_derive::{Deserialize, Serialize};
+use sounding_analysis::{experimental::fire_briggs::BriggsPlumeHeatingAnalysis, PFTAnalysis};
+
+/// The pyrocumulonimbus firepower threshold and the curves used to find it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Pft {
+    /// The firepower threshold.
+    pub pft: GigaWatts,
+    /// The SP-curve, (pressure, temperature) pairs.
+    pub sp_curve: Vec<(HectoPascal, Celsius)>,
+    /// The potential temperature curve through the free convection level.
+    pub theta_curve: Vec<(HectoPascal, Celsius)>,
+    /// Pressure at the free convection level.
+    pub p_fc: HectoPascal,
+    /// Height of the free convection level.
+    pub z_fc: Meters,
+    /// Equivalent potential temperature at the free convection level.
+    pub theta_e_fc: Kelvin,
+    /// Mean potential temperature of the mixed layer.
+    pub theta_ml: Kelvin,
+    /// Mean specific humidity of the mixed layer.
+    pub q_ml: f64,
+    /// Mean wind speed in the mixed layer.
+    pub u_ml: MetersPSec,
+    /// Potential temperature increase needed to reach the free convection level, in Kelvin.
+    pub d_theta: f64,
+}
+
+impl From<PFTAnalysis> for Pft {
+    fn from(pft: PFTAnalysis) -> Self {
+        Pft {
+            pft: pft.pft,
+            sp_curve: pft.sp_curve,
+            theta_curve: pft.theta_curve,
+            p_fc: HectoPascal::from(pft.p_fc),
+            z_fc: Meters::from(pft.z_fc),
+            theta_e_fc: Kelvin::from(pft.theta_e_fc),
+            theta_ml: Kelvin::from(pft.theta_ml),
+            q_ml: pft.q_ml,
+            u_ml: MetersPSec::from(pft.u_ml),
+            d_theta: pft.d_theta.unpack(),
+        }
+    }
+}
+
+/// The Briggs plume heating analysis over a range of fire powers.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BriggsPlumeHeating {
+    /// The fire powers the plumes were lifted with.
+    pub fire_power: Vec<GigaWatts>,
+    /// The Briggs buoyancy parameter for each fire power.
+    pub betas: Vec<f64>,
+    /// The fraction of the plume's buoyancy that came from latent heat for each fire power.
+    pub wet_ratio: Vec<Option<f64>>,
+    /// The height of the equilibrium level for each fire power.
+    pub el_heights: Vec<Option<Meters>>,
+    /// The height of the lifting condensation level for each fire power.
+    pub lcl_heights: Vec<Option<Meters>>,
+
+    // Starting values for lifting a plume with any fire power in the range.
+    /// Potential temperature at the surface.
+    pub starting_theta: Kelvin,
+    /// Specific humidity at the surface.
+    pub starting_sh: f64,
+    /// Moisture added to the plume for each unit of heat.
+    pub moisture_ratio: Option<f64>,
+    /// Surface elevation.
+    pub sfc_height: Meters,
+    /// Surface pressure.
+    pub p_sfc: HectoPascal,
+}
+
+impl From<BriggsPlumeHeatingAnalysis> for BriggsPlumeHeating {
+    fn from(anal: BriggsPlumeHeatingAnalysis) -> Self {
+        BriggsPlumeHeating {
+            fire_power: anal.fire_power,
+            betas: anal.betas,
+            wet_ratio: anal.wet_ratio,
+            el_heights: anal.el_heights,
+            lcl_heights: anal.lcl_heights,
+
+            starting_theta: anal.starting_theta,
+            starting_sh: anal.starting_sh,
+            moisture_ratio: anal.moisture_ratio,
+            sfc_height: anal.sfc_height,
+            p_sfc: anal.p_sfc,
+        }
+    }
+}
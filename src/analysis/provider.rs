@@ -0,0 +1,180 @@
+//! Human readable descriptions of the values in the provider analysis.
+//!
+//! The keys come from the file loaders, e.g. the surface section of a Bufkit file, and follow a
+//! `CamelCaseUnits` naming scheme. Keys we know about get a hand written description, anything
+//! else is described by splitting the key into words and recognizing the units suffix.
+
+/// Description and units of a provider analysis value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProviderField {
+    pub description: String,
+    pub units: &'static str,
+}
+
+/// Keys with a hand written description, (key, description, units).
+const KNOWN_FIELDS: &[(&str, &str, &str)] = &[
+    // Indexes from the station header of each Bufkit sounding.
+    ("Showalter", "Showalter index", "\u{00b0}C"),
+    ("LI", "Lifted index", "\u{00b0}C"),
+    ("SWeT", "Severe weather threat index", ""),
+    ("K", "K index", ""),
+    ("LCL", "Lifting condensation level", "hPa"),
+    (
+        "LCLTemperatureK",
+        "Lifting condensation level temperature",
+        "K",
+    ),
+    ("LFC", "Level of free convection", "hPa"),
+    ("EquilibriumLevelHPa", "Equilibrium level", "hPa"),
+    ("PWAT", "Precipitable water", "mm"),
+    ("TotalTotals", "Total totals index", ""),
+    ("CAPE", "Convective available potential energy", "J/kg"),
+    ("CIN", "Convective inhibition", "J/kg"),
+    ("BulkRichardsonNumber", "Bulk Richardson number", ""),
+    // Values from the Bufkit surface section.
+    ("MslpHPa", "Mean sea level pressure", "hPa"),
+    ("StationPressureHPa", "Station pressure", "hPa"),
+    ("SkinTemperatureC", "Skin temperature", "\u{00b0}C"),
+    ("Temperature2mC", "2-m temperature", "\u{00b0}C"),
+    ("DewPoint2mC", "2-m dew point", "\u{00b0}C"),
+    ("SpecificHumidity2mGpKg", "2-m specific humidity", "g/kg"),
+    ("UWind10mMps", "10-m u wind component", "m/s"),
+    ("VWind10mMps", "10-m v wind component", "m/s"),
+    ("Precipitation1HrMm", "1-hour precipitation", "mm"),
+    (
+        "ConvectivePrecip1HrMm",
+        "1-hour convective precipitation",
+        "mm",
+    ),
+    (
+        "SnowFall1HrKgPerM2",
+        "1-hour snowfall water equivalent",
+        "kg/m\u{00b2}",
+    ),
+    (
+        "SnowRatio",
+        "Fraction of precipitation falling as snow",
+        "%",
+    ),
+    ("Runoff1HrMm", "1-hour runoff", "mm"),
+    ("LowCloudPct", "Low cloud cover", "%"),
+    ("MidCloudPct", "Middle cloud cover", "%"),
+    ("HiCloudPct", "High cloud cover", "%"),
+    ("CloudBasePressureHPa", "Cloud base", "hPa"),
+    ("VisibilityKm", "Visibility", "km"),
+    ("StormUMps", "Storm motion u component", "m/s"),
+    ("StormVMps", "Storm motion v component", "m/s"),
+    (
+        "SrHelicityM2pS2",
+        "Storm relative helicity",
+        "m\u{00b2}/s\u{00b2}",
+    ),
+    ("WxSymbolCode", "Weather symbol code", ""),
+];
+
+/// Units suffixes, longer suffixes must come before any suffix they end with.
+const UNIT_SUFFIXES: [(&str, &str); 18] = [
+    ("HPa", "hPa"),
+    ("Hpa", "hPa"),
+    ("Pa", "Pa"),
+    ("Mm", "mm"),
+    ("Cm", "cm"),
+    ("Km", "km"),
+    ("Knots", "kt"),
+    ("Kt", "kt"),
+    ("MPS", "m/s"),
+    ("Mps", "m/s"),
+    ("Percent", "%"),
+    ("Pct", "%"),
+    ("Wm2", "W/m\u{00b2}"),
+    ("GpKg", "g/kg"),
+    ("M", "m"),
+    ("C", "\u{00b0}C"),
+    ("F", "\u{00b0}F"),
+    ("K", "K"),
+];
+
+/// Describe a key from the provider analysis.
+pub fn describe(key: &str) -> ProviderField {
+    if let Some(&(_, description, units)) = KNOWN_FIELDS.iter().find(|(k, _, _)| *k == key) {
+        return ProviderField {
+            description: description.to_owned(),
+            units,
+        };
+    }
+
+    // Only strip a suffix if it starts a new word, so "LFC" isn't read as "LF" in degrees C.
+    let (name, units) = UNIT_SUFFIXES
+        .iter()
+        .filter_map(|&(suffix, units)| {
+            let name = key.strip_suffix(suffix)?;
+            let last = name.chars().last()?;
+            (last.is_lowercase() || last.is_ascii_digit()).then_some((name, units))
+        })
+        .next()
+        .unwrap_or((key, ""));
+
+    ProviderField {
+        description: split_words(name),
+        units,
+    }
+}
+
+/// Put spaces between the words of a camel case name.
+fn split_words(name: &str) -> String {
+    let mut words = String::with_capacity(name.len() + 8);
+
+    let mut prev: Option<char> = None;
+    for c in name.chars() {
+        if let Some(p) = prev {
+            let new_word = (c.is_uppercase() && (p.is_lowercase() || p.is_ascii_digit()))
+                || (c.is_ascii_digit() && p.is_alphabetic());
+            if new_word {
+                words.push(' ');
+            }
+        }
+        words.push(c);
+        prev = Some(c);
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_field(key: &str, description: &str, units: &str) {
+        let field = describe(key);
+        println!("{} => {:?}", key, field);
+        assert_eq!(field.description, description);
+        assert_eq!(field.units, units);
+    }
+
+    #[test]
+    fn test_describe_known_fields() {
+        assert_field("CAPE", "Convective available potential energy", "J/kg");
+        assert_field("Precipitation1HrMm", "1-hour precipitation", "mm");
+        assert_field("WxSymbolCode", "Weather symbol code", "");
+
+        for (i, &(key, _, _)) in KNOWN_FIELDS.iter().enumerate() {
+            assert!(
+                KNOWN_FIELDS[(i + 1)..].iter().all(|(k, _, _)| *k != key),
+                "{} is listed twice",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn test_describe_unknown_fields() {
+        assert_field("FreezingLevelM", "Freezing Level", "m");
+        assert_field("SurfacePressureHPa", "Surface Pressure", "hPa");
+        assert_field("Precip3HrMm", "Precip 3 Hr", "mm");
+        assert_field("WindGustKnots", "Wind Gust", "kt");
+        assert_field("Haines", "Haines", "");
+
+        // An acronym ending in a unit letter isn't split.
+        assert_field("MLFC", "MLFC", "");
+    }
+}
@@ -1,30 +1,61 @@
 //! Module for storing and manipulating the application state. This state is globally shared
 //! via smart pointers.
 use crate::{
-    analysis::Analysis,
+    analysis::{cache, interpolate, Analysis},
+    coords,
     errors::SondeError,
     gui::{
         self,
-        profiles::{CloudContext, RHOmegaContext, WindSpeedContext},
-        FirePlumeContext, FirePlumeEnergyContext, HodoContext, PlotContext, PlotContextExt,
-        SkewTContext,
+        profiles::{CloudContext, DifferenceContext, RHOmegaContext, WindSpeedContext},
+        FirePlumeContext, FirePlumeEnergyContext, HodoContext, MeteogramContext, PlotContext,
+        PlotContextExt, SkewTContext, TimeHeightContext,
     },
 };
+use chrono::NaiveDateTime;
 use crossbeam_channel::TryRecvError;
 use gtk::{
     glib::{self, Object},
     prelude::IsA,
     Builder,
 };
+use itertools::Itertools;
+use metfor::{HectoPascal, Quantity};
 use sounding_analysis::{self};
 use std::{
     cell::{Cell, Ref, RefCell},
+    collections::HashMap,
     rc::Rc,
 };
 
+// Module for scheduling the background analysis of loaded soundings
+mod analysis_scheduler;
+use analysis_scheduler::{AnalysisResult, AnalysisScheduler, AnalysisStage};
+
 // Module for configuring application
 pub mod config;
-use self::config::Config;
+use self::config::{Config, CustomParcelSource, EnsembleStyle, SampleStep};
+
+// Module for keeping track of the user defined parcels
+mod custom_parcels;
+use custom_parcels::CustomParcelTool;
+
+// Module for interactively editing the displayed sounding
+mod editor;
+pub use editor::EditTarget;
+use editor::SoundingEditor;
+
+// Module for measuring a layer dragged out on the skew-t
+mod layer_tool;
+use layer_tool::LayerTool;
+
+// Module for drawing reference lines through points on the skew-t
+mod reference_lines;
+use reference_lines::ReferenceLines;
+
+// Module for grouping the loaded soundings by station
+mod stations;
+pub use stations::StationKey;
+use stations::Stations;
 
 // Module for loading data
 // make it pub so it can be used for bench tests too.
@@ -47,9 +78,37 @@ pub struct AppContext {
     currently_displayed_index: Cell<usize>,
     last_sample: RefCell<Sample>,
 
-    // The number of the times we've called open. Helps keep threads synced.
+    // Levels pinned for sampling, they stay put as the displayed time changes.
+    pinned_samples: RefCell<Vec<HectoPascal>>,
+
+    // Soundings for the stations that aren't selected, the list holds the selected station's.
+    stations: RefCell<Stations>,
+
+    // Soundings to overlay as ghosts, pinned from the list or from another run or model.
+    pinned_ghosts: RefCell<Vec<Rc<RefCell<Analysis>>>>,
+    comparison_list: RefCell<Vec<Rc<RefCell<Analysis>>>>,
+
+    // Sounding the displayed one is compared against on the difference profile.
+    difference_reference: RefCell<Option<Rc<RefCell<Analysis>>>>,
+
+    // The number of the times we've started filling in the analysis. Helps keep threads synced.
     load_calls: Cell<usize>,
 
+    // Runs the analysis in the background, starting with the displayed sounding.
+    analysis_scheduler: AnalysisScheduler,
+
+    // State of the sounding editor and its undo history.
+    editor: RefCell<SoundingEditor>,
+
+    // Layer being measured on the skew-t.
+    layer_tool: RefCell<LayerTool>,
+
+    // Reference lines placed on the skew-t.
+    reference_lines: RefCell<ReferenceLines>,
+
+    // Whether clicks on the skew-t add custom parcels.
+    custom_parcel_tool: RefCell<CustomParcelTool>,
+
     // Last Drawing area to have focus, for use with focus buttons
     last_focus: Cell<ZoomableDrawingAreas>,
 
@@ -76,6 +135,15 @@ pub struct AppContext {
 
     // Handle to wind speed profile context
     pub wind_speed: WindSpeedContext,
+
+    // Handle to the difference profile context
+    pub difference: DifferenceContext,
+
+    // Handle to the time-height cross section context
+    pub time_height: TimeHeightContext,
+
+    // Handle to the meteogram context
+    pub meteogram: MeteogramContext,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -84,6 +152,8 @@ pub enum ZoomableDrawingAreas {
     Hodo,
     FirePlume,
     FirePlumeEnergy,
+    TimeHeight,
+    Meteogram,
 }
 
 impl AppContext {
@@ -94,7 +164,17 @@ impl AppContext {
             list: RefCell::new(vec![]),
             currently_displayed_index: Cell::new(0),
             last_sample: RefCell::new(Sample::None),
+            pinned_samples: RefCell::new(vec![]),
+            stations: RefCell::new(Stations::default()),
+            pinned_ghosts: RefCell::new(vec![]),
+            comparison_list: RefCell::new(vec![]),
+            difference_reference: RefCell::new(None),
             load_calls: Cell::new(0),
+            analysis_scheduler: AnalysisScheduler::new(),
+            editor: RefCell::new(SoundingEditor::default()),
+            layer_tool: RefCell::new(LayerTool::default()),
+            reference_lines: RefCell::new(ReferenceLines::default()),
+            custom_parcel_tool: RefCell::new(CustomParcelTool::default()),
             last_focus: Cell::new(ZoomableDrawingAreas::SkewT),
             gui: RefCell::new(None),
             skew_t: SkewTContext::new(),
@@ -104,6 +184,9 @@ impl AppContext {
             rh_omega: RHOmegaContext::new(),
             cloud: CloudContext::new(),
             wind_speed: WindSpeedContext::new(),
+            difference: DifferenceContext::new(),
+            time_height: TimeHeightContext::new(),
+            meteogram: MeteogramContext::new(),
         })
     }
 
@@ -123,55 +206,225 @@ impl AppContext {
             .ok_or(SondeError::WidgetLoadError(widget_id))
     }
 
+    /// Make a widget built in code available through `fetch_widget`.
+    pub fn expose_widget<T>(&self, widget_id: &'static str, widget: &T)
+    where
+        T: IsA<Object>,
+    {
+        if let Some(builder) = self.gui.borrow().as_ref() {
+            builder.expose_object(widget_id, widget);
+        }
+    }
+
     pub fn load_data<I>(acp: AppContextPointer, src: I)
     where
         I: Iterator<Item = Analysis>,
     {
-        // Copy in the list and make sure it is sorted.
+        // Group by station and copy the first station into the list, sorted by valid time.
         {
-            let list: &mut Vec<_> = &mut acp.list.borrow_mut();
-            *list = src.map(RefCell::new).map(Rc::new).collect();
-            list.sort_by_key(|anal| anal.borrow().sounding().valid_time());
+            let (settings, sources): (_, Vec<CustomParcelSource>) = {
+                let config = acp.config.borrow();
+                let sources = config.custom_parcels.iter().map(|pcl| pcl.source).collect();
+                (config.analysis_settings(), sources)
+            };
+            let anals = src
+                .map(|mut anal| {
+                    anal.update_settings_mut(settings);
+                    anal.update_custom_parcels_mut(&sources);
+                    anal
+                })
+                .map(RefCell::new)
+                .map(Rc::new);
+            *acp.list.borrow_mut() = acp.stations.borrow_mut().replace(anals);
         }
 
+        // The ghosts, comparison soundings, and difference reference were chosen to go with the
+        // old data.
+        acp.pinned_ghosts.borrow_mut().clear();
+        acp.comparison_list.borrow_mut().clear();
+        *acp.difference_reference.borrow_mut() = None;
+
         acp.currently_displayed_index.set(0);
+        acp.editor.borrow_mut().reset();
 
         acp.set_currently_displayed(0);
         acp.mark_background_dirty();
+        gui::update_station_selector(&acp);
 
         // Once everything we need for this thread is taken care of, fill in any missing data
         // in the analysis.
-        let pool = threadpool::ThreadPool::default();
-        let (tx, rx) = crossbeam_channel::unbounded();
+        Self::fill_in_analysis(&acp);
+    }
+
+    /// Load soundings from another run or model, or ensemble members, to compare with the ones
+    /// that have the same valid time.
+    pub fn load_comparison_data<I>(acp: &AppContextPointer, src: I)
+    where
+        I: Iterator<Item = Analysis>,
+    {
+        let settings = acp.config.borrow().analysis_settings();
+        *acp.comparison_list.borrow_mut() = src
+            .map(|anal| anal.with_settings(settings))
+            .map(RefCell::new)
+            .map(Rc::new)
+            .collect();
+
+        acp.mark_data_dirty();
+        acp.update_all_gui();
+
+        Self::fill_in_comparison_analysis(acp, acp.load_calls.get());
+    }
+
+    /// Apply the analysis settings from the configuration to everything loaded and recompute
+    /// whatever they affect in the background.
+    pub fn update_analysis_settings(acp: &AppContextPointer) {
+        let settings = acp.config.borrow().analysis_settings();
+
+        // The soundings for the other stations are updated too, so the values that depend on
+        // the settings are filled in again when they are selected.
+        let changed = {
+            let (list, comparison_list) = (acp.list.borrow(), acp.comparison_list.borrow());
+            let stations = acp.stations.borrow();
+
+            let mut changed = false;
+            for anal in list
+                .iter()
+                .chain(comparison_list.iter())
+                .chain(stations.unselected())
+            {
+                let mut anal = anal.borrow_mut();
+                if anal.settings() != settings {
+                    anal.update_settings_mut(settings);
+                    changed = true;
+                }
+            }
+
+            changed
+        };
+
+        if changed {
+            acp.mark_data_dirty();
+            acp.update_all_gui();
+            Self::fill_in_analysis(acp);
+        }
+    }
+
+    /// Fill in the missing values of every analysis in the list on a background thread.
+    fn fill_in_analysis(acp: &AppContextPointer) {
+        // Anything left over from a previous call is no longer needed.
+        acp.analysis_scheduler.clear();
         let num_loads = acp.load_calls.get() + 1;
         acp.load_calls.set(num_loads);
 
-        for (i, anal) in acp.list.borrow().iter().enumerate() {
-            let anal = anal.borrow().clone();
-            let tx = tx.clone();
-            pool.execute(move || {
-                let mut anal = anal;
-                anal.fill_in_missing_analysis_mut();
-                tx.send((num_loads, i, anal)).unwrap();
-            });
+        let targets = acp.list.borrow().iter().cloned().enumerate().collect();
+        Self::schedule_analysis(acp, num_loads, targets, AnalysisStage::Plume, true);
+
+        Self::fill_in_comparison_analysis(acp, num_loads);
+    }
+
+    /// Fill in the basic analysis of the comparison soundings on a background thread. They are
+    /// only used for ensemble statistics, so the expensive plume analysis is skipped.
+    fn fill_in_comparison_analysis(acp: &AppContextPointer, load_id: usize) {
+        // Put them after everything in the list so they don't hold up the displayed sounding.
+        let offset = acp.list.borrow().len();
+        let targets = acp
+            .comparison_list
+            .borrow()
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(i, anal)| (offset + i, anal))
+            .collect();
+
+        Self::schedule_analysis(acp, load_id, targets, AnalysisStage::Basic, false);
+    }
+
+    /// Fill in the missing values of the displayed analysis without disturbing the others.
+    fn fill_in_displayed_analysis(acp: &AppContextPointer) {
+        let index = acp.currently_displayed_index.get();
+        if let Some(anal) = acp.get_sounding_for_display() {
+            Self::schedule_analysis(
+                acp,
+                acp.load_calls.get(),
+                vec![(index, anal)],
+                AnalysisStage::Plume,
+                false,
+            );
         }
+    }
+
+    // Results are stored back into the same `Rc` they were made from, so if an entry in the list
+    // is replaced while it is being analyzed the stale results never end up in the list. Stages
+    // after `last_stage` are not run.
+    fn schedule_analysis(
+        acp: &AppContextPointer,
+        load_id: usize,
+        targets: Vec<(usize, Rc<RefCell<Analysis>>)>,
+        last_stage: AnalysisStage,
+        save_cache: bool,
+    ) {
+        let (tx, rx) = crossbeam_channel::unbounded();
 
-        let acp = Rc::clone(&acp);
+        for (i, anal) in targets.iter() {
+            acp.analysis_scheduler.submit(
+                *i,
+                AnalysisStage::Basic,
+                anal.borrow().clone(),
+                tx.clone(),
+            );
+        }
+
+        let targets: HashMap<usize, Rc<RefCell<Analysis>>> = targets.into_iter().collect();
+        let mut remaining = targets.len() * last_stage.number();
+
+        let acp = Rc::clone(acp);
         glib::idle_add_local(move || loop {
+            if load_id != acp.load_calls.get() {
+                return glib::ControlFlow::Break;
+            }
+
+            if remaining == 0 {
+                if save_cache {
+                    acp.save_analysis_cache();
+                }
+                return glib::ControlFlow::Break;
+            }
+
             match rx.try_recv() {
-                Ok((loaded_on, i, anal)) => {
-                    if loaded_on == acp.load_calls.get() {
-                        // Nest scope to force borrows to end - otherwise it panics!
-                        {
-                            let a: &RefCell<Analysis> = &acp.list.borrow_mut()[i];
-                            let a: &mut Analysis = &mut RefCell::borrow_mut(a);
-                            *a = anal;
-                        }
-
-                        if acp.currently_displayed_index.get() == i {
-                            acp.mark_data_dirty();
-                            acp.update_all_gui();
-                        }
+                Ok(AnalysisResult {
+                    index: i,
+                    stage,
+                    anal,
+                }) => {
+                    // Queue up the next stage starting from where this one left off.
+                    if let Some(next_stage) = stage.next().filter(|&next| next <= last_stage) {
+                        acp.analysis_scheduler
+                            .submit(i, next_stage, anal.clone(), tx.clone());
+                    }
+
+                    let target = &targets[&i];
+                    *target.borrow_mut() = anal;
+
+                    remaining -= 1;
+
+                    if acp
+                        .get_sounding_for_display()
+                        .is_some_and(|displayed| Rc::ptr_eq(&displayed, target))
+                    {
+                        acp.mark_data_dirty();
+                        acp.update_all_gui();
+                    } else if acp
+                        .ensemble_members()
+                        .iter()
+                        .any(|member| Rc::ptr_eq(member, target))
+                    {
+                        // The ensemble statistics use the analysis of every member.
+                        acp.mark_data_dirty();
+                        acp.update_all_gui();
+                    } else {
+                        // The meteogram plots the analysis of every sounding in the list.
+                        acp.meteogram.mark_data_dirty();
+                        gui::draw_all(&acp);
                     }
                 }
                 Err(TryRecvError::Empty) => return glib::ControlFlow::Continue,
@@ -180,6 +433,28 @@ impl AppContext {
         });
     }
 
+    /// Save the analysis of everything loaded so reopening the same files is quick.
+    fn save_analysis_cache(&self) {
+        // A file may hold soundings for several stations, so every station is saved together.
+        let (list, stations) = (self.list.borrow(), self.stations.borrow());
+        let anals: Vec<Ref<'_, Analysis>> = list
+            .iter()
+            .chain(stations.unselected())
+            .map(|anal| anal.borrow())
+            .collect();
+
+        for key in anals.iter().filter_map(|anal| anal.source_key()).unique() {
+            let from_file = anals
+                .iter()
+                .filter(|anal| anal.source_key() == Some(key))
+                .map(|anal| &**anal);
+
+            if let Err(err) = cache::store(key, from_file) {
+                eprintln!("Error saving analysis cache: {}", err);
+            }
+        }
+    }
+
     /// Is there any data to plot?
     pub fn plottable(&self) -> bool {
         !self.list.borrow().is_empty()
@@ -226,11 +501,72 @@ impl AppContext {
         }
     }
 
+    /// Display the sounding at `idx` in the list, if there is one.
+    pub fn display_sounding(&self, idx: usize) {
+        if idx < self.list.borrow().len() {
+            self.set_currently_displayed(idx);
+        }
+    }
+
+    /// Insert a synthetic sounding valid at `valid_time`, interpolated between the loaded soundings
+    /// on either side of it, and display it. If a sounding is already valid at that time it is
+    /// displayed instead.
+    pub fn insert_interpolated(acp: &AppContextPointer, valid_time: NaiveDateTime) {
+        let (index, anal) = {
+            let list = acp.list.borrow();
+
+            // The list is sorted by valid time, with any missing valid times first.
+            let index = list.partition_point(|anal| {
+                anal.borrow()
+                    .sounding()
+                    .valid_time()
+                    .is_none_or(|vt| vt < valid_time)
+            });
+
+            let after = match list.get(index) {
+                Some(after) => after.borrow(),
+                None => return,
+            };
+
+            if after.sounding().valid_time() == Some(valid_time) {
+                drop(after);
+                drop(list);
+                acp.display_sounding(index);
+                return;
+            }
+
+            let before = match index.checked_sub(1).and_then(|i| list.get(i)) {
+                Some(before) => before.borrow(),
+                None => return,
+            };
+
+            match interpolate::interpolate_in_time(&before, &after, valid_time) {
+                Some(anal) => (index, anal),
+                None => return,
+            }
+        };
+
+        acp.list
+            .borrow_mut()
+            .insert(index, Rc::new(RefCell::new(anal)));
+
+        // The edit history is tied to an index in the list, which may have just shifted.
+        acp.editor.borrow_mut().reset();
+
+        acp.set_currently_displayed(index);
+        Self::fill_in_displayed_analysis(acp);
+    }
+
     #[inline]
     fn set_currently_displayed(&self, idx: usize) {
         self.currently_displayed_index.set(idx);
+        self.analysis_scheduler.set_focus(idx);
         self.update_sample();
         self.mark_data_dirty();
+        // The height axis is labeled from the displayed sounding.
+        if self.config.borrow().show_height_axis {
+            self.mark_background_dirty();
+        }
         self.update_all_gui();
     }
 
@@ -278,6 +614,135 @@ impl AppContext {
             .map(Rc::clone)
     }
 
+    /// Get the index in the list of the displayed sounding.
+    pub fn currently_displayed_index(&self) -> usize {
+        self.currently_displayed_index.get()
+    }
+
+    /// Get every loaded analysis, in time order, for plots of the whole series.
+    pub fn loaded_soundings(&self) -> Vec<Rc<RefCell<Analysis>>> {
+        self.list.borrow().clone()
+    }
+
+    /// Keep the displayed sounding on the plots as a ghost.
+    pub fn pin_displayed_ghost(&self) {
+        if let Some(anal) = self.get_sounding_for_display() {
+            let mut pinned = self.pinned_ghosts.borrow_mut();
+            if !pinned.iter().any(|ghost| Rc::ptr_eq(ghost, &anal)) {
+                pinned.push(anal);
+            }
+        }
+
+        self.mark_data_dirty();
+        gui::draw_all(self);
+    }
+
+    /// Remove the pinned ghosts and any soundings loaded for comparison.
+    pub fn clear_ghosts(&self) {
+        self.pinned_ghosts.borrow_mut().clear();
+        self.comparison_list.borrow_mut().clear();
+
+        self.mark_data_dirty();
+        gui::draw_all(self);
+    }
+
+    /// Use the displayed sounding as the reference for the difference profile.
+    pub fn set_difference_reference(&self) {
+        *self.difference_reference.borrow_mut() = self.get_sounding_for_display();
+
+        self.mark_data_dirty();
+        gui::draw_all(self);
+    }
+
+    /// Go back to comparing against the sounding loaded for comparison at the same valid time.
+    pub fn clear_difference_reference(&self) {
+        *self.difference_reference.borrow_mut() = None;
+
+        self.mark_data_dirty();
+        gui::draw_all(self);
+    }
+
+    /// Get the sounding to subtract from the displayed one for the difference profile.
+    ///
+    /// This is the one the user chose, or if they haven't chosen one, the sounding loaded for
+    /// comparison with the same valid time.
+    pub fn difference_reference(&self) -> Option<Rc<RefCell<Analysis>>> {
+        let displayed = self.get_sounding_for_display()?;
+
+        if let Some(reference) = self.difference_reference.borrow().as_ref() {
+            return (!Rc::ptr_eq(reference, &displayed)).then(|| Rc::clone(reference));
+        }
+
+        self.comparison_members(&displayed).into_iter().next()
+    }
+
+    /// Get the soundings to draw faded behind the displayed one.
+    pub fn ghost_soundings(&self) -> Vec<Rc<RefCell<Analysis>>> {
+        let displayed = match self.get_sounding_for_display() {
+            Some(anal) => anal,
+            None => return vec![],
+        };
+
+        let config = self.config.borrow();
+        let list = self.list.borrow();
+        let idx = self.currently_displayed_index.get();
+
+        let mut ghosts = vec![];
+
+        if config.show_previous_ghost && idx > 0 {
+            ghosts.extend(list.get(idx - 1).cloned());
+        }
+
+        if config.show_next_ghost {
+            ghosts.extend(list.get(idx + 1).cloned());
+        }
+
+        ghosts.extend(
+            self.pinned_ghosts
+                .borrow()
+                .iter()
+                .filter(|ghost| !Rc::ptr_eq(ghost, &displayed))
+                .cloned(),
+        );
+
+        if config.ensemble_style == EnsembleStyle::Spaghetti {
+            ghosts.extend(self.comparison_members(&displayed));
+        }
+
+        ghosts
+    }
+
+    /// Get the displayed sounding and every comparison sounding with the same valid time, or
+    /// nothing if there are no comparison soundings for this time.
+    pub fn ensemble_members(&self) -> Vec<Rc<RefCell<Analysis>>> {
+        let displayed = match self.get_sounding_for_display() {
+            Some(anal) => anal,
+            None => return vec![],
+        };
+
+        let members = self.comparison_members(&displayed);
+        if members.is_empty() {
+            return members;
+        }
+
+        std::iter::once(displayed).chain(members).collect()
+    }
+
+    /// Get the comparison soundings with the same valid time as `displayed`.
+    fn comparison_members(&self, displayed: &Rc<RefCell<Analysis>>) -> Vec<Rc<RefCell<Analysis>>> {
+        let valid_time = match displayed.borrow().sounding().valid_time() {
+            Some(valid_time) => valid_time,
+            None => return vec![],
+        };
+
+        self.comparison_list
+            .borrow()
+            .iter()
+            .filter(|anal| anal.borrow().sounding().valid_time() == Some(valid_time))
+            .cloned()
+            .collect()
+    }
+
     pub fn get_sample(&self) -> Ref<'_, Sample> {
         self.last_sample.borrow()
     }
@@ -288,6 +753,170 @@ impl AppContext {
         self.mark_overlay_dirty();
     }
 
+    /// Move the active sample up the displayed sounding.
+    pub fn step_sample_up(&self) {
+        self.step_sample(true);
+    }
+
+    /// Move the active sample down the displayed sounding.
+    pub fn step_sample_down(&self) {
+        self.step_sample(false);
+    }
+
+    // Step a data level or a pressure step at a time, starting from the bottom going up or the
+    // top going down when there isn't a sample yet.
+    fn step_sample(&self, up: bool) {
+        let anal = match self.get_sounding_for_display() {
+            Some(anal) => anal,
+            None => return,
+        };
+        let anal = anal.borrow();
+        let snd = anal.sounding();
+
+        let (sample_step, step) = {
+            let config = self.config.borrow();
+            (config.sample_step, config.sample_step_pressure.unpack())
+        };
+
+        let levels: Vec<f64> = snd
+            .pressure_profile()
+            .iter()
+            .filter_map(|p| p.into_option())
+            .map(|p| p.unpack())
+            .filter(|&p| p <= anal.max_pressure().unpack())
+            .collect();
+        if levels.is_empty() {
+            return;
+        }
+
+        let bottom = levels.iter().copied().fold(f64::MIN, f64::max);
+        let top = levels.iter().copied().fold(f64::MAX, f64::min);
+
+        let current = match *self.get_sample() {
+            Sample::Sounding { data, .. } => data.pressure.into_option().map(|p| p.unpack()),
+            Sample::FirePlume { .. } | Sample::None => None,
+        };
+
+        let next = match (current, sample_step) {
+            (None, _) => {
+                if up {
+                    bottom
+                } else {
+                    top
+                }
+            }
+            (Some(p), SampleStep::DataLevel) => {
+                const TOLERANCE: f64 = 1.0e-3;
+                if up {
+                    levels
+                        .iter()
+                        .copied()
+                        .filter(|&lvl| lvl < p - TOLERANCE)
+                        .fold(top, f64::max)
+                } else {
+                    levels
+                        .iter()
+                        .copied()
+                        .filter(|&lvl| lvl > p + TOLERANCE)
+                        .fold(bottom, f64::min)
+                }
+            }
+            (Some(p), SampleStep::Pressure) => {
+                if up {
+                    (p - step).max(top)
+                } else {
+                    (p + step).min(bottom)
+                }
+            }
+        };
+
+        let sample = sounding_analysis::linear_interpolate_sounding(snd, HectoPascal(next))
+            .ok()
+            .map(|data| create_sample_sounding(data, &anal))
+            .unwrap_or(Sample::None);
+
+        self.set_sample(sample);
+        gui::draw_all(self);
+    }
+
+    /// Pin the level of the active sample so it's marked and compared across all the times.
+    pub fn pin_sample(&self) {
+        let pressure = match self.sample_pressure() {
+            Some(pressure) => pressure,
+            None => return,
+        };
+
+        // Clicking on a level that is already pinned shouldn't list it twice.
+        if self.pinned_sample_index(pressure).is_some() {
+            return;
+        }
+
+        {
+            let mut pinned = self.pinned_samples.borrow_mut();
+            pinned.push(pressure);
+            pinned.sort_by(|a, b| b.unpack().total_cmp(&a.unpack()));
+        }
+
+        self.mark_overlay_dirty();
+        gui::draw_all(self);
+        gui::update_text_views(self);
+    }
+
+    /// Remove the pinned level at the active sample, so a single level can be unpinned by
+    /// hovering over it.
+    pub fn unpin_sample(&self) {
+        let index = match self
+            .sample_pressure()
+            .and_then(|pressure| self.pinned_sample_index(pressure))
+        {
+            Some(index) => index,
+            None => return,
+        };
+
+        self.pinned_samples.borrow_mut().remove(index);
+
+        self.mark_overlay_dirty();
+        gui::draw_all(self);
+        gui::update_text_views(self);
+    }
+
+    /// The pressure of the active sample, if it is on a sounding.
+    fn sample_pressure(&self) -> Option<HectoPascal> {
+        match *self.get_sample() {
+            Sample::Sounding { data, .. } => data.pressure.into_option(),
+            Sample::FirePlume { .. } | Sample::None => None,
+        }
+    }
+
+    /// Find the pinned level closest to `pressure`, if it is close enough to be the same level.
+    fn pinned_sample_index(&self, pressure: HectoPascal) -> Option<usize> {
+        // hPa
+        const TOLERANCE: f64 = 2.0;
+
+        self.pinned_samples
+            .borrow()
+            .iter()
+            .map(|pinned| (pinned.unpack() - pressure.unpack()).abs())
+            .enumerate()
+            .filter(|&(_, diff)| diff <= TOLERANCE)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(index, _)| index)
+    }
+
+    /// Remove all the pinned sample levels.
+    pub fn clear_pinned_samples(&self) {
+        self.pinned_samples.borrow_mut().clear();
+
+        self.mark_overlay_dirty();
+        gui::draw_all(self);
+        gui::update_text_views(self);
+    }
+
+    /// Get the pinned sample levels, from the bottom up.
+    pub fn pinned_samples(&self) -> Vec<HectoPascal> {
+        self.pinned_samples.borrow().clone()
+    }
+
     pub fn set_last_focus(&self, zoomable: ZoomableDrawingAreas) {
         self.last_focus.set(zoomable);
     }
@@ -300,6 +929,8 @@ impl AppContext {
             Hodo => self.hodo.zoom_in(),
             FirePlume => self.fire_plume.zoom_in(),
             FirePlumeEnergy => self.fire_plume_energy.zoom_in(),
+            TimeHeight => self.time_height.zoom_in(),
+            Meteogram => self.meteogram.zoom_in(),
         }
 
         self.mark_background_dirty();
@@ -314,12 +945,56 @@ impl AppContext {
             Hodo => self.hodo.zoom_out(),
             FirePlume => self.fire_plume.zoom_out(),
             FirePlumeEnergy => self.fire_plume_energy.zoom_out(),
+            TimeHeight => self.time_height.zoom_out(),
+            Meteogram => self.meteogram.zoom_out(),
         }
 
         self.mark_background_dirty();
         gui::draw_all(self);
     }
 
+    /// Go back to the whole plot in the drawing area that last had focus.
+    pub fn reset_zoom(&self) {
+        use ZoomableDrawingAreas::*;
+
+        let origin = coords::XYCoords::origin();
+        match self.last_focus.get() {
+            SkewT => self.skew_t.zoom(origin, 1.0),
+            Hodo => self.hodo.zoom(origin, 1.0),
+            FirePlume => self.fire_plume.zoom(origin, 1.0),
+            FirePlumeEnergy => self.fire_plume_energy.zoom(origin, 1.0),
+            TimeHeight => self.time_height.zoom(origin, 1.0),
+            Meteogram => self.meteogram.zoom(origin, 1.0),
+        }
+
+        self.mark_background_dirty();
+        gui::draw_all(self);
+    }
+
+    /// Apply the skew-T pressure range, temperature range, and skew from the configuration.
+    pub fn update_skew_t_geometry(&self) {
+        let geometry = self.config.borrow().skew_t_geometry();
+        match geometry {
+            Ok(geometry) => self.skew_t.set_geometry(geometry),
+            Err(err) => eprintln!("Keeping the current skew-t geometry: {}", err),
+        }
+
+        self.mark_background_dirty();
+        self.mark_data_dirty();
+        self.mark_overlay_dirty();
+        gui::draw_all(self);
+    }
+
+    /// Redraw the plots, text, and inputs after the display units change.
+    pub fn update_units(acp: &AppContextPointer) {
+        acp.mark_background_dirty();
+        acp.mark_data_dirty();
+        acp.mark_overlay_dirty();
+        gui::draw_all(acp);
+        gui::update_text_views(acp);
+        gui::update_control_area_units(acp);
+    }
+
     pub fn mark_data_dirty(&self) {
         self.hodo.mark_data_dirty();
         self.fire_plume.mark_data_dirty();
@@ -328,6 +1003,9 @@ impl AppContext {
         self.rh_omega.mark_data_dirty();
         self.cloud.mark_data_dirty();
         self.wind_speed.mark_data_dirty();
+        self.difference.mark_data_dirty();
+        self.time_height.mark_data_dirty();
+        self.meteogram.mark_data_dirty();
     }
 
     pub fn mark_overlay_dirty(&self) {
@@ -338,6 +1016,9 @@ impl AppContext {
         self.rh_omega.mark_overlay_dirty();
         self.cloud.mark_overlay_dirty();
         self.wind_speed.mark_overlay_dirty();
+        self.difference.mark_overlay_dirty();
+        self.time_height.mark_overlay_dirty();
+        self.meteogram.mark_overlay_dirty();
     }
 
     pub fn mark_background_dirty(&self) {
@@ -348,5 +1029,8 @@ impl AppContext {
         self.rh_omega.mark_background_dirty();
         self.cloud.mark_background_dirty();
         self.wind_speed.mark_background_dirty();
+        self.difference.mark_background_dirty();
+        self.time_height.mark_background_dirty();
+        self.meteogram.mark_background_dirty();
     }
 }
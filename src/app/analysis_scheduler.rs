@@ -0,0 +1,152 @@
+//! Schedule the background analysis of loaded soundings.
+//!
+//! Jobs are not run in the order they were submitted. Whenever a worker thread is free it takes
+//! the job closest to the currently displayed sounding, so the sounding on screen is analyzed
+//! first, then its neighbors. Analysis is also split into stages so the quick indexes for every
+//! sounding near the focus are available before the slow plume analyses are started.
+use crate::analysis::Analysis;
+use crossbeam_channel::Sender;
+use std::sync::{Arc, Mutex};
+use threadpool::ThreadPool;
+
+/// The stages of analysis, in the order they are run for a single sounding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AnalysisStage {
+    /// Parcels, stability indexes, kinematics, and precipitation type.
+    Basic,
+    /// The PFT and Briggs plume analyses, these are the expensive ones.
+    Plume,
+}
+
+impl AnalysisStage {
+    /// The stage to run after this one, if any.
+    pub fn next(self) -> Option<AnalysisStage> {
+        match self {
+            AnalysisStage::Basic => Some(AnalysisStage::Plume),
+            AnalysisStage::Plume => None,
+        }
+    }
+
+    /// The number of stages up to and including this one.
+    pub fn number(self) -> usize {
+        match self {
+            AnalysisStage::Basic => 1,
+            AnalysisStage::Plume => 2,
+        }
+    }
+
+    fn run(self, anal: &mut Analysis) {
+        match self {
+            AnalysisStage::Basic => anal.fill_in_basic_analysis_mut(),
+            AnalysisStage::Plume => anal.fill_in_plume_analysis_mut(),
+        }
+    }
+}
+
+/// The results of a job sent back to the main thread.
+pub struct AnalysisResult {
+    /// Index of the sounding in the application list.
+    pub index: usize,
+    /// The stage that was just completed.
+    pub stage: AnalysisStage,
+    /// The updated analysis.
+    pub anal: Analysis,
+}
+
+struct Job {
+    index: usize,
+    stage: AnalysisStage,
+    anal: Analysis,
+    tx: Sender<AnalysisResult>,
+}
+
+struct JobQueue {
+    focus: usize,
+    jobs: Vec<Job>,
+}
+
+impl JobQueue {
+    /// Smaller is more urgent. The displayed sounding is completely analyzed first, then the
+    /// basic analysis of its neighbors, and finally the plume analysis of the neighbors. Ties are
+    /// broken in favor of the future since that is usually the direction we step through time.
+    fn priority(&self, job: &Job) -> (bool, AnalysisStage, usize, bool) {
+        let distance = job.index.abs_diff(self.focus);
+        (distance != 0, job.stage, distance, job.index < self.focus)
+    }
+
+    fn pop(&mut self) -> Option<Job> {
+        let pos = self
+            .jobs
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, job)| self.priority(job))
+            .map(|(pos, _)| pos)?;
+
+        Some(self.jobs.swap_remove(pos))
+    }
+}
+
+/// Runs analysis jobs on a thread pool, prioritized by distance from the displayed sounding.
+pub struct AnalysisScheduler {
+    queue: Arc<Mutex<JobQueue>>,
+    pool: ThreadPool,
+}
+
+impl AnalysisScheduler {
+    /// Create a new scheduler with a thread pool sized to the number of CPUs.
+    pub fn new() -> Self {
+        AnalysisScheduler {
+            queue: Arc::new(Mutex::new(JobQueue {
+                focus: 0,
+                jobs: vec![],
+            })),
+            pool: ThreadPool::default(),
+        }
+    }
+
+    /// Set the index of the sounding that is currently displayed so it is analyzed next.
+    pub fn set_focus(&self, index: usize) {
+        self.queue.lock().unwrap().focus = index;
+    }
+
+    /// Drop all the jobs that have not started yet, e.g. when a new set of files is loaded.
+    pub fn clear(&self) {
+        self.queue.lock().unwrap().jobs.clear();
+    }
+
+    /// Queue up a stage of analysis, the result is sent back on `tx` when it is done.
+    pub fn submit(
+        &self,
+        index: usize,
+        stage: AnalysisStage,
+        anal: Analysis,
+        tx: Sender<AnalysisResult>,
+    ) {
+        self.queue.lock().unwrap().jobs.push(Job {
+            index,
+            stage,
+            anal,
+            tx,
+        });
+
+        // Every submitted job gets a task in the pool, but the task takes whatever job is most
+        // urgent when it starts, which is not necessarily the one that was just submitted.
+        let queue = Arc::clone(&self.queue);
+        self.pool.execute(move || {
+            let job = queue.lock().unwrap().pop();
+
+            if let Some(Job {
+                index,
+                stage,
+                mut anal,
+                tx,
+            }) = job
+            {
+                stage.run(&mut anal);
+
+                // If the receiver is gone a new load replaced this one, so just drop the result.
+                let _ = tx.send(AnalysisResult { index, stage, anal });
+            }
+        });
+    }
+}
@@ -1,7 +1,12 @@
 //! Keep configuration data in this module.
 
 use crate::{
-    coords::{GwHCoords, GwPCoords, PPCoords, SDCoords, SPCoords, TPCoords, WPCoords, XYCoords},
+    analysis::AnalysisSettings,
+    coords::{
+        convert_pressure_to_y, GwHCoords, GwPCoords, PPCoords, SDCoords, SPCoords, SkewTGeometry,
+        TPCoords, WPCoords, XYCoords,
+    },
+    errors::SondeError,
     gui::{
         profiles::{CloudContext, RHOmegaContext, WindSpeedContext},
         FirePlumeContext, FirePlumeEnergyContext, HodoContext, SkewTContext,
@@ -9,7 +14,10 @@ use crate::{
 };
 
 use lazy_static::lazy_static;
-use metfor::{Celsius, GigaWatts, HectoPascal, Kelvin, Knots, Meters, PaPS, Quantity, WindSpdDir};
+use metfor::{
+    Celsius, Fahrenheit, Feet, GigaWatts, HectoPascal, Inches, Kelvin, Knots, Meters, MetersPSec,
+    Mm, PaPS, Quantity, WindSpdDir,
+};
 use serde_derive::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -23,10 +31,45 @@ pub enum ParcelType {
     Convective,
 }
 
+/// Where a custom parcel starts from.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CustomParcelSource {
+    /// A given temperature and dew point at a pressure level.
+    Pressure {
+        pressure: HectoPascal,
+        temperature: Celsius,
+        dew_point: Celsius,
+    },
+    /// A given temperature and dew point at a height above ground level.
+    Height {
+        height: Meters,
+        temperature: Celsius,
+        dew_point: Celsius,
+    },
+    /// The average of the layer from the surface up through this depth.
+    MixedLayer { depth: HectoPascal },
+    /// The sounding's own values at a level, usually picked by clicking on the skew-t.
+    Level { pressure: HectoPascal },
+}
+
+/// A parcel defined by the user, lifted and listed along with the built in parcel types.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CustomParcel {
+    /// Name shown in the parcel tables and on the skew-t.
+    pub name: String,
+    pub source: CustomParcelSource,
+    /// Show the parcel profile on the skew-t.
+    pub show: bool,
+    /// Parcel profile color.
+    pub rgba: Rgba,
+}
+
 /// Types of helicity to use when drawing hodograph overlays.
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum HelicityType {
-    SurfaceTo3km,
+    /// From the surface up to the configured helicity depth.
+    #[serde(alias = "SurfaceTo3km")]
+    SurfaceLayer,
     Effective,
 }
 
@@ -37,6 +80,381 @@ pub enum StormMotionType {
     LeftMover,
 }
 
+/// Vertical coordinate for the time-height cross section.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum TimeHeightAxis {
+    Pressure,
+    Height,
+}
+
+/// How to draw the members of an ensemble on the skew-t.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EnsembleStyle {
+    /// Every member as a ghost profile.
+    Spaghetti,
+    /// A shaded band from the minimum to the maximum with the median dashed.
+    Envelope,
+}
+
+/// Which levels get a wind barb on the skew-t.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindBarbLevels {
+    /// Levels in the data, skipping any that would overlap the barb below.
+    Thinned,
+    /// Every level in the data.
+    EveryLevel,
+    /// Interpolated at a fixed pressure interval.
+    PressureInterval,
+    /// Interpolated at a fixed height interval above ground level.
+    HeightInterval,
+    /// Interpolated at the mandatory levels.
+    Mandatory,
+}
+
+/// Where heights are measured from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HeightReference {
+    /// Above ground level.
+    Agl,
+    /// Above mean sea level.
+    Msl,
+}
+
+/// Units for temperatures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TemperatureUnit {
+    Celsius,
+    Fahrenheit,
+}
+
+impl TemperatureUnit {
+    /// The temperature in these units.
+    pub fn value(self, t: Celsius) -> f64 {
+        match self {
+            TemperatureUnit::Celsius => t.unpack(),
+            TemperatureUnit::Fahrenheit => Fahrenheit::from(t).unpack(),
+        }
+    }
+
+    /// Convert a temperature in these units back to Celsius.
+    pub fn to_celsius(self, val: f64) -> Celsius {
+        match self {
+            TemperatureUnit::Celsius => Celsius(val),
+            TemperatureUnit::Fahrenheit => Celsius::from(Fahrenheit(val)),
+        }
+    }
+
+    /// A temperature difference, given in Celsius degrees, in these units.
+    pub fn difference(self, dt: f64) -> f64 {
+        match self {
+            TemperatureUnit::Celsius => dt,
+            TemperatureUnit::Fahrenheit => dt * 1.8,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TemperatureUnit::Celsius => "\u{00B0}C",
+            TemperatureUnit::Fahrenheit => "\u{00B0}F",
+        }
+    }
+}
+
+/// Units for wind speeds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindSpeedUnit {
+    Knots,
+    MetersPerSecond,
+    MilesPerHour,
+    KilometersPerHour,
+}
+
+impl WindSpeedUnit {
+    /// The speed in these units.
+    pub fn value(self, speed: Knots) -> f64 {
+        match self {
+            WindSpeedUnit::Knots => speed.unpack(),
+            WindSpeedUnit::MetersPerSecond => MetersPSec::from(speed).unpack(),
+            WindSpeedUnit::MilesPerHour => speed.unpack() * 1.150_779,
+            WindSpeedUnit::KilometersPerHour => speed.unpack() * 1.852,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WindSpeedUnit::Knots => "KT",
+            WindSpeedUnit::MetersPerSecond => "m/s",
+            WindSpeedUnit::MilesPerHour => "mph",
+            WindSpeedUnit::KilometersPerHour => "km/h",
+        }
+    }
+}
+
+/// Units for heights.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HeightUnit {
+    Meters,
+    Feet,
+}
+
+impl HeightUnit {
+    /// The height in these units.
+    pub fn value(self, h: Meters) -> f64 {
+        match self {
+            HeightUnit::Meters => h.unpack(),
+            HeightUnit::Feet => Feet::from(h).unpack(),
+        }
+    }
+
+    /// Convert a height in these units back to meters.
+    pub fn to_meters(self, val: f64) -> Meters {
+        match self {
+            HeightUnit::Meters => Meters(val),
+            HeightUnit::Feet => Meters::from(Feet(val)),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HeightUnit::Meters => "m",
+            HeightUnit::Feet => "ft",
+        }
+    }
+
+    /// Label for thousands of these units.
+    pub fn thousands_label(self) -> &'static str {
+        match self {
+            HeightUnit::Meters => "km",
+            HeightUnit::Feet => "kft",
+        }
+    }
+}
+
+/// Units for precipitation and precipitable water.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PrecipUnit {
+    Millimeters,
+    Inches,
+}
+
+impl PrecipUnit {
+    /// The depth in these units.
+    pub fn value(self, depth: Mm) -> f64 {
+        match self {
+            PrecipUnit::Millimeters => depth.unpack(),
+            PrecipUnit::Inches => Inches::from(depth).unpack(),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PrecipUnit::Millimeters => "mm",
+            PrecipUnit::Inches => "in",
+        }
+    }
+
+    /// Number of decimal places worth showing.
+    pub fn precision(self) -> usize {
+        match self {
+            PrecipUnit::Millimeters => 1,
+            PrecipUnit::Inches => 2,
+        }
+    }
+}
+
+/// Units for pressure.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PressureUnit {
+    HectoPascals,
+    Kilopascals,
+    InchesOfMercury,
+}
+
+impl PressureUnit {
+    /// The pressure in these units.
+    pub fn value(self, p: HectoPascal) -> f64 {
+        match self {
+            PressureUnit::HectoPascals => p.unpack(),
+            PressureUnit::Kilopascals => p.unpack() / 10.0,
+            PressureUnit::InchesOfMercury => p.unpack() * 0.029_53,
+        }
+    }
+
+    /// Convert a pressure in these units back to hectopascals.
+    pub fn to_hpa(self, val: f64) -> HectoPascal {
+        match self {
+            PressureUnit::HectoPascals => HectoPascal(val),
+            PressureUnit::Kilopascals => HectoPascal(val * 10.0),
+            PressureUnit::InchesOfMercury => HectoPascal(val / 0.029_53),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PressureUnit::HectoPascals => "hPa",
+            PressureUnit::Kilopascals => "kPa",
+            PressureUnit::InchesOfMercury => "inHg",
+        }
+    }
+
+    /// Number of decimal places worth showing.
+    pub fn precision(self) -> usize {
+        match self {
+            PressureUnit::HectoPascals => 0,
+            PressureUnit::Kilopascals => 1,
+            PressureUnit::InchesOfMercury => 2,
+        }
+    }
+}
+
+/// Thermodynamic diagram drawn in the main chart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThermoDiagram {
+    /// Temperature against log pressure with the isotherms leaning to the right.
+    SkewT,
+    /// Temperature against log pressure with upright isotherms.
+    Emagram,
+    /// Temperature against pressure to the R/cp power, which makes the dry adiabats straight.
+    Stuve,
+    /// Temperature against log potential temperature, turned so the isobars are nearly level.
+    Tephigram,
+}
+
+/// Values from the analysis that can be plotted on the meteogram.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MeteogramIndex {
+    SurfaceCape,
+    MixedLayerCape,
+    MostUnstableCape,
+    MixedLayerCin,
+    Dcape,
+    PrecipitableWater,
+    Hdw,
+    Pft,
+    #[serde(alias = "SrHelicity3km")]
+    SrHelicitySurface,
+    SrHelicityEffective,
+    Precip1Hour,
+}
+
+impl MeteogramIndex {
+    /// Every index in the order they are stacked on the meteogram.
+    pub const ALL: [MeteogramIndex; 11] = [
+        MeteogramIndex::SurfaceCape,
+        MeteogramIndex::MixedLayerCape,
+        MeteogramIndex::MostUnstableCape,
+        MeteogramIndex::MixedLayerCin,
+        MeteogramIndex::Dcape,
+        MeteogramIndex::PrecipitableWater,
+        MeteogramIndex::Hdw,
+        MeteogramIndex::Pft,
+        MeteogramIndex::SrHelicitySurface,
+        MeteogramIndex::SrHelicityEffective,
+        MeteogramIndex::Precip1Hour,
+    ];
+}
+
+/// How far the up and down keys move the active sample.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SampleStep {
+    /// To the next level in the data.
+    DataLevel,
+    /// By a fixed pressure step.
+    Pressure,
+}
+
+/// Commands that can be bound to a key in the keymap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyCommand {
+    NextTime,
+    PreviousTime,
+    FirstTime,
+    LastTime,
+    SampleUp,
+    SampleDown,
+    UnpinSample,
+    NextParcel,
+    PreviousParcel,
+    ToggleActiveReadout,
+    ToggleParcelProfile,
+    ToggleDownburst,
+    ToggleInflowLayer,
+    ToggleWindProfile,
+    ResetZoom,
+}
+
+/// Keyboard shortcuts. Each is the name GDK gives the key, like "Right", "Home", or "p", and an
+/// empty name leaves the command without a key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyMap {
+    pub next_time: String,
+    pub previous_time: String,
+    pub first_time: String,
+    pub last_time: String,
+    pub sample_up: String,
+    pub sample_down: String,
+    pub unpin_sample: String,
+    pub next_parcel: String,
+    pub previous_parcel: String,
+    pub toggle_active_readout: String,
+    pub toggle_parcel_profile: String,
+    pub toggle_downburst: String,
+    pub toggle_inflow_layer: String,
+    pub toggle_wind_profile: String,
+    pub reset_zoom: String,
+}
+
+impl KeyMap {
+    /// Find the command bound to the key with this name.
+    pub fn command(&self, key_name: &str) -> Option<KeyCommand> {
+        use KeyCommand::*;
+
+        [
+            (&self.next_time, NextTime),
+            (&self.previous_time, PreviousTime),
+            (&self.first_time, FirstTime),
+            (&self.last_time, LastTime),
+            (&self.sample_up, SampleUp),
+            (&self.sample_down, SampleDown),
+            (&self.unpin_sample, UnpinSample),
+            (&self.next_parcel, NextParcel),
+            (&self.previous_parcel, PreviousParcel),
+            (&self.toggle_active_readout, ToggleActiveReadout),
+            (&self.toggle_parcel_profile, ToggleParcelProfile),
+            (&self.toggle_downburst, ToggleDownburst),
+            (&self.toggle_inflow_layer, ToggleInflowLayer),
+            (&self.toggle_wind_profile, ToggleWindProfile),
+            (&self.reset_zoom, ResetZoom),
+        ]
+        .into_iter()
+        .find(|(name, _)| !name.is_empty() && name.as_str() == key_name)
+        .map(|(_, command)| command)
+    }
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        KeyMap {
+            next_time: "Right".to_owned(),
+            previous_time: "Left".to_owned(),
+            first_time: "Home".to_owned(),
+            last_time: "End".to_owned(),
+            sample_up: "Up".to_owned(),
+            sample_down: "Down".to_owned(),
+            unpin_sample: "Delete".to_owned(),
+            next_parcel: "p".to_owned(),
+            previous_parcel: "P".to_owned(),
+            toggle_active_readout: "a".to_owned(),
+            toggle_parcel_profile: "c".to_owned(),
+            toggle_downburst: "d".to_owned(),
+            toggle_inflow_layer: "i".to_owned(),
+            toggle_wind_profile: "w".to_owned(),
+            reset_zoom: "0".to_owned(),
+        }
+    }
+}
+
 /// Type used for colors in Gtk
 pub type Rgba = (f64, f64, f64, f64);
 pub const GREEN: Rgba = (0.0, 0.8, 0.0, 1.0);
@@ -84,6 +502,14 @@ pub struct Config {
     pub wind_barb_line_width: f64,
     /// Show the wind profile
     pub show_wind_profile: bool,
+    /// Which levels to plot wind barbs at.
+    pub wind_barb_levels: WindBarbLevels,
+    /// Spacing between wind barbs when they are plotted at a pressure interval.
+    pub wind_barb_pressure_interval: HectoPascal,
+    /// Spacing between wind barbs when they are plotted at a height interval.
+    pub wind_barb_height_interval: Meters,
+    /// Color the wind barbs by their speed instead of using the wind color.
+    pub color_wind_barbs_by_speed: bool,
 
     //
     // Temperature profile
@@ -115,6 +541,20 @@ pub struct Config {
     /// Show the dew point profile
     pub show_dew_point: bool,
 
+    //
+    // Ghost profiles
+    //
+    /// Overlay the sounding from the previous time step.
+    pub show_previous_ghost: bool,
+    /// Overlay the sounding from the next time step.
+    pub show_next_ghost: bool,
+    /// Color used for ghost profiles, usually faded.
+    pub ghost_rgba: Rgba,
+    /// How to draw comparison soundings with the same valid time as the displayed one.
+    pub ensemble_style: EnsembleStyle,
+    /// Transparency of the ensemble envelope fill.
+    pub ensemble_envelope_alpha: f64,
+
     //
     // Skew-T overlays
     //
@@ -132,6 +572,8 @@ pub struct Config {
     pub parcel_positive_rgba: Rgba,
     /// Negative parcel area color.
     pub parcel_negative_rgba: Rgba,
+    /// Parcels defined by the user.
+    pub custom_parcels: Vec<CustomParcel>,
     /// Show the inversion mix downs
     pub show_inversion_mix_down: bool,
     /// Inversion mix downs color
@@ -224,6 +666,24 @@ pub struct Config {
     pub label_padding: f64,
     /// Label color
     pub label_rgba: Rgba,
+    /// Label heights in the displayed sounding along the right side of the skew-t and profiles.
+    pub show_height_axis: bool,
+    /// Whether the height axis is above ground level or mean sea level.
+    pub height_axis_reference: HeightReference,
+
+    //
+    // Units
+    //
+    /// Units for temperatures in the text areas, readouts and labels.
+    pub temperature_units: TemperatureUnit,
+    /// Units for wind speeds.
+    pub wind_speed_units: WindSpeedUnit,
+    /// Units for heights, including the height axis.
+    pub height_units: HeightUnit,
+    /// Units for precipitation and precipitable water.
+    pub precip_units: PrecipUnit,
+    /// Units for pressure.
+    pub pressure_units: PressureUnit,
 
     //
     // Background
@@ -254,6 +714,23 @@ pub struct Config {
     /// Show or hide the moist adiabats
     pub show_iso_theta_e: bool,
 
+    //
+    // Skew-T geometry
+    //
+    /// Which thermodynamic diagram to draw, all of them use the ranges below.
+    pub thermo_diagram: ThermoDiagram,
+    /// Maximum pressure plotted on the skew-t, the bottom edge.
+    pub skew_t_max_p: HectoPascal,
+    /// Minimum pressure plotted on the skew-t, the top edge.
+    pub skew_t_min_p: HectoPascal,
+    /// Coldest temperature plotted on the bottom edge of the skew-t.
+    pub skew_t_min_t: Celsius,
+    /// Warmest temperature plotted on the bottom edge of the skew-t.
+    pub skew_t_max_t: Celsius,
+    /// How far the isotherms lean, 1.0 is 45 degrees on a square plot and 0.0 is an emagram. Only
+    /// used by the skew-t.
+    pub skew_t_skew: f64,
+
     //
     // Active readout
     //
@@ -268,6 +745,10 @@ pub struct Config {
     pub active_readout_line_width: f64,
     /// Active readout line color
     pub active_readout_line_rgba: Rgba,
+    /// Pinned sample line color
+    pub pinned_sample_rgba: Rgba,
+    /// Color of the layer picked with the layer measuring tool
+    pub layer_measure_rgba: Rgba,
 
     /// Show sample parcel profile
     pub show_sample_parcel_profile: bool,
@@ -279,6 +760,11 @@ pub struct Config {
     /// Sample mix down profile color
     pub sample_mix_down_rgba: Rgba,
 
+    /// Move the sample a data level or a pressure step at a time with the keyboard.
+    pub sample_step: SampleStep,
+    /// Pressure step for moving the sample with the keyboard.
+    pub sample_step_pressure: HectoPascal,
+
     //
     // Hodograph
     //
@@ -327,13 +813,123 @@ pub struct Config {
     /// PFT cloud parcel line color
     pub pft_cloud_parcel_color: Rgba,
 
+    //
+    // Time-height cross section.
+    //
+    /// Use pressure or height above ground level for the vertical axis.
+    pub time_height_axis: TimeHeightAxis,
+    /// Shade the relative humidity.
+    pub time_height_show_rh: bool,
+    /// Contour the vertical velocity.
+    pub time_height_show_omega: bool,
+    /// Contour the temperature.
+    pub time_height_show_temperature: bool,
+    /// Plot wind barbs.
+    pub time_height_show_wind: bool,
+
+    //
+    // Meteogram
+    //
+    /// Values to plot on the meteogram, each gets its own panel.
+    pub meteogram_indexes: Vec<MeteogramIndex>,
+    /// Keys from the provider analysis to plot on the meteogram below the indexes.
+    pub meteogram_provider_fields: Vec<String>,
+    /// Show the strip comparing precipitation type algorithms on the bottom of the meteogram.
+    pub meteogram_show_precip_type: bool,
+    /// Color of the meteogram lines.
+    pub meteogram_rgba: Rgba,
+
+    //
+    // Analysis settings.
+    //
+    /// Moisture ratio used for the PFT.
+    pub pft_moisture_ratio: f64,
+    /// Moisture ratio for the lower bound of the Briggs plume heating analysis.
+    pub briggs_low_moisture_ratio: f64,
+    /// Moisture ratio for the upper bound of the Briggs plume heating analysis.
+    pub briggs_high_moisture_ratio: f64,
+    /// Depth of the layer above ground level used for the mean wind.
+    pub mean_wind_depth: Meters,
+    /// Depth of the layer above ground level used for storm relative helicity.
+    pub srh_depth: Meters,
+    /// Depth of the layer above the surface used for the mixed layer parcel.
+    pub mixed_layer_depth: HectoPascal,
+
+    //
+    // Keyboard
+    //
+    /// Keys bound to commands on the plots and text views.
+    pub keymap: KeyMap,
+
     //
     // Misc configuration.
     //
     pub bar_graph_line_width: f64,
 }
 
-impl Config {}
+impl Config {
+    /// Gather the settings that go into the analysis.
+    pub fn analysis_settings(&self) -> AnalysisSettings {
+        AnalysisSettings {
+            pft_moisture_ratio: self.pft_moisture_ratio,
+            briggs_low_moisture_ratio: self.briggs_low_moisture_ratio,
+            briggs_high_moisture_ratio: self.briggs_high_moisture_ratio,
+            mean_wind_depth: self.mean_wind_depth,
+            srh_depth: self.srh_depth,
+            mixed_layer_depth: self.mixed_layer_depth,
+        }
+    }
+
+    /// Gather the settings for the domain of the skew-t, or an error if the pressure or
+    /// temperature range is empty, upside down, or can't be transformed.
+    pub fn skew_t_geometry(&self) -> Result<SkewTGeometry, SondeError> {
+        let diagram = self.thermo_diagram;
+
+        let (min_p, max_p) = (self.skew_t_min_p.unpack(), self.skew_t_max_p.unpack());
+        let (min_t, max_t) = (self.skew_t_min_t.unpack(), self.skew_t_max_t.unpack());
+
+        // The pressure transforms take logarithms and powers of the pressure, so it must be
+        // positive.
+        if ![min_p, max_p, min_t, max_t, self.skew_t_skew]
+            .iter()
+            .all(|val| val.is_finite())
+            || min_p <= 0.0
+            || min_p >= max_p
+            || min_t >= max_t
+        {
+            return Err(SondeError::InvalidSkewTGeometry);
+        }
+
+        // An emagram is a skew-t without the skew, and the Stuve diagram has upright isotherms too.
+        let skew = match diagram {
+            ThermoDiagram::SkewT => self.skew_t_skew,
+            _ => 0.0,
+        };
+
+        Ok(SkewTGeometry {
+            diagram,
+            max_p: self.skew_t_max_p,
+            min_p: self.skew_t_min_p,
+            min_t: self.skew_t_min_t,
+            max_t: self.skew_t_max_t,
+            skew,
+        })
+    }
+
+    /// The color for a wind barb with this speed.
+    pub fn wind_barb_rgba(&self, speed: Knots) -> Rgba {
+        if !self.color_wind_barbs_by_speed {
+            return self.wind_rgba;
+        }
+
+        WIND_BARB_SPEED_COLORS
+            .iter()
+            .rev()
+            .find(|(threshold, _)| speed >= *threshold)
+            .map(|&(_, rgba)| rgba)
+            .unwrap_or(self.wind_rgba)
+    }
+}
 
 impl Default for Config {
     fn default() -> Self {
@@ -360,6 +956,10 @@ impl Default for Config {
             wind_rgba: (0.0, 0.0, 0.0, 1.0),
             wind_barb_line_width: 1.0,
             show_wind_profile: true,
+            wind_barb_levels: WindBarbLevels::Thinned,
+            wind_barb_pressure_interval: HectoPascal(25.0),
+            wind_barb_height_interval: Meters(500.0),
+            color_wind_barbs_by_speed: false,
 
             //
             // Temperature profile
@@ -382,6 +982,15 @@ impl Default for Config {
             dew_point_line_width: 2.0,
             show_dew_point: true,
 
+            //
+            // Ghost profiles
+            //
+            show_previous_ghost: false,
+            show_next_ghost: false,
+            ghost_rgba: (0.5, 0.5, 0.5, 0.6),
+            ensemble_style: EnsembleStyle::Spaghetti,
+            ensemble_envelope_alpha: 0.25,
+
             //
             // Skew-T overlays
             //
@@ -392,6 +1001,7 @@ impl Default for Config {
             fill_parcel_areas: true,
             parcel_positive_rgba: (0.80, 0.0, 0.0, 0.5),
             parcel_negative_rgba: (0.0, 0.0, 0.80, 0.5),
+            custom_parcels: vec![],
             show_inversion_mix_down: true,
             inversion_mix_down_rgba: (0.560_784_313_725, 0.349_019_607_843, 0.007_843_137_254, 1.0),
             show_downburst: true,
@@ -446,6 +1056,17 @@ impl Default for Config {
             edge_padding: 5.0,
             label_padding: 3.0,
             label_rgba: (0.862_745_098, 0.388_235_294, 0.156_862_745, 1.0),
+            show_height_axis: false,
+            height_axis_reference: HeightReference::Agl,
+
+            //
+            // Units
+            //
+            temperature_units: TemperatureUnit::Celsius,
+            wind_speed_units: WindSpeedUnit::Knots,
+            height_units: HeightUnit::Meters,
+            precip_units: PrecipUnit::Millimeters,
+            pressure_units: PressureUnit::HectoPascals,
 
             //
             // Background
@@ -465,6 +1086,16 @@ impl Default for Config {
             iso_theta_e_rgba: (0.333_333_333, 0.662_745_098, 0.278_431_373, 1.0),
             show_iso_theta_e: true,
 
+            //
+            // Skew-T geometry
+            //
+            thermo_diagram: ThermoDiagram::SkewT,
+            skew_t_max_p: HectoPascal(1050.0),
+            skew_t_min_p: HectoPascal(99.0),
+            skew_t_min_t: Celsius(-40.5),
+            skew_t_max_t: Celsius(55.5),
+            skew_t_skew: 1.0,
+
             //
             // Active readout
             //
@@ -473,10 +1104,14 @@ impl Default for Config {
             show_active_readout_line: true,
             active_readout_line_width: 3.0,
             active_readout_line_rgba: (1.0, 0.0, 0.0, 1.0),
+            pinned_sample_rgba: (1.0, 0.5, 0.0, 1.0),
+            layer_measure_rgba: (0.0, 0.6, 0.6, 1.0),
             show_sample_parcel_profile: true,
             sample_parcel_profile_color: (1.0, 0.0, 0.0, 1.0),
             show_sample_mix_down: true,
             sample_mix_down_rgba: (0.560_784_313_725, 0.349_019_607_843, 0.007_843_137_254, 1.0),
+            sample_step: SampleStep::DataLevel,
+            sample_step_pressure: HectoPascal(10.0),
 
             //
             // Hodograph
@@ -526,6 +1161,43 @@ impl Default for Config {
                 1.0,
             ),
 
+            //
+            // Time-height cross section.
+            //
+            time_height_axis: TimeHeightAxis::Pressure,
+            time_height_show_rh: true,
+            time_height_show_omega: true,
+            time_height_show_temperature: true,
+            time_height_show_wind: true,
+
+            //
+            // Meteogram
+            //
+            meteogram_indexes: vec![
+                MeteogramIndex::MixedLayerCape,
+                MeteogramIndex::MostUnstableCape,
+                MeteogramIndex::Dcape,
+                MeteogramIndex::Hdw,
+            ],
+            meteogram_provider_fields: vec![],
+            meteogram_show_precip_type: true,
+            meteogram_rgba: (0.0, 0.0, 0.0, 1.0),
+
+            //
+            // Analysis settings.
+            //
+            pft_moisture_ratio: 15.0,
+            briggs_low_moisture_ratio: 8.0,
+            briggs_high_moisture_ratio: 15.0,
+            mean_wind_depth: Meters(6000.0),
+            srh_depth: Meters(3000.0),
+            mixed_layer_depth: HectoPascal(100.0),
+
+            //
+            // Keyboard
+            //
+            keymap: KeyMap::default(),
+
             //
             // Misc configuration.
             //
@@ -538,18 +1210,10 @@ impl Default for Config {
 *                         Constant, compile time configuration items.
 **************************************************************************************************/
 //
-// Constants for defining a standard x-y coordinate system
+// Constants for defining a standard x-y coordinate system, the skew-t domain is in the
+// configuration.
 //
 
-/// Maximum pressure plotted on skew-t (bottom edge)
-pub const MAXP: HectoPascal = HectoPascal(1050.0); // hPa
-/// Minimum pressure plotted on skew-t (top edge)
-pub const MINP: HectoPascal = HectoPascal(99.0); // hPa
-/// Coldest temperature plotted at max pressure, on the bottom edge.
-pub const MINT: Celsius = Celsius(-40.5); // C - at MAXP
-/// Warmest temperature plotted at max pressure, on the bottom edge.
-pub const MAXT: Celsius = Celsius(55.5); // C - at MAXP
-
 /// Maximum absolute vertical velocity in Pa/s
 pub const MAX_ABS_W: PaPS = PaPS(15.0);
 
@@ -571,17 +1235,21 @@ pub const MIN_FIRE_PLUME_HEIGHT: Meters = Meters(-1_000.0);
 pub const MAX_FIRE_PLUME_PCT: f64 = 110.0;
 /// Minimum cape for fire plume plot
 pub const MIN_FIRE_PLUME_PCT: f64 = -10.0;
+/// Maximum height above ground level on the time-height cross section.
+pub const MAX_TIME_HEIGHT_HEIGHT: Meters = Meters(12_000.0);
 
 //
 // Limits on the top pressure level for some background lines.
 //
 
-/// Highest elevation pressure level to draw isentrops up to
-pub const ISENTROPS_TOP_P: HectoPascal = MINP;
 /// Moist adiabat highest elevation pressure to draw up to
 pub const THETA_E_TOP_P: HectoPascal = HectoPascal(200.0);
 /// Number of points to use per isentrop line when drawing.
 pub const POINTS_PER_ISENTROP: u32 = 40;
+/// Number of points to use per isobar when drawing, they are only curved on a tephigram.
+pub const POINTS_PER_ISOBAR: u32 = 40;
+/// Number of points to use per iso mixing ratio line when drawing.
+pub const POINTS_PER_ISO_MIXING_RATIO: u32 = 10;
 /// Hightest elevation pressure level to draw iso mixing ratio up to
 pub const ISO_MIXING_RATIO_TOP_P: HectoPascal = HectoPascal(400.0);
 
@@ -624,8 +1292,8 @@ pub const ISOTHERMS: [Celsius; 31] = [
     Celsius(60.0),
 ];
 
-/// Isobars to plot on the chart background.
-pub const ISOBARS: [HectoPascal; 9] = [
+/// Isobars to plot on the chart background, the ones outside the skew-t domain are skipped.
+pub const ISOBARS: [HectoPascal; 14] = [
     HectoPascal(1050.0),
     HectoPascal(1000.0),
     HectoPascal(925.0),
@@ -635,6 +1303,46 @@ pub const ISOBARS: [HectoPascal; 9] = [
     HectoPascal(300.0),
     HectoPascal(200.0),
     HectoPascal(100.0),
+    HectoPascal(70.0),
+    HectoPascal(50.0),
+    HectoPascal(30.0),
+    HectoPascal(20.0),
+    HectoPascal(10.0),
+];
+
+/// Levels used for wind barbs when only the mandatory levels are plotted.
+pub const MANDATORY_LEVELS: [HectoPascal; 11] = [
+    HectoPascal(1000.0),
+    HectoPascal(925.0),
+    HectoPascal(850.0),
+    HectoPascal(700.0),
+    HectoPascal(500.0),
+    HectoPascal(400.0),
+    HectoPascal(300.0),
+    HectoPascal(250.0),
+    HectoPascal(200.0),
+    HectoPascal(150.0),
+    HectoPascal(100.0),
+];
+
+/// Wind barb colors when coloring by speed, each is used from its speed up to the next one.
+pub const WIND_BARB_SPEED_COLORS: [(Knots, Rgba); 6] = [
+    (Knots(0.0), (0.5, 0.5, 0.5, 1.0)),
+    (Knots(10.0), (0.0, 0.6, 0.0, 1.0)),
+    (Knots(25.0), (0.0, 0.0, 0.9, 1.0)),
+    (Knots(50.0), (0.9, 0.6, 0.0, 1.0)),
+    (Knots(75.0), (0.9, 0.0, 0.0, 1.0)),
+    (Knots(100.0), (0.7, 0.0, 0.7, 1.0)),
+];
+
+/// Colors given to new custom parcels in turn, they can be changed afterwards.
+pub const CUSTOM_PARCEL_COLORS: [Rgba; 6] = [
+    (0.8, 0.4, 0.0, 1.0),
+    (0.5, 0.0, 0.7, 1.0),
+    (0.0, 0.5, 0.5, 1.0),
+    (0.7, 0.0, 0.3, 1.0),
+    (0.3, 0.5, 0.0, 1.0),
+    (0.0, 0.3, 0.7, 1.0),
 ];
 
 /// Isentrops to plot on the chart background.
@@ -812,118 +1520,149 @@ Values below this line are automatically calculated based on the configuration v
 should not be altered.
 ------------------------------------------------------------------------------------------------ */
 
-lazy_static! {
-
-    /// Compute points for background isotherms only once
-    pub static ref ISOTHERM_PNTS: Vec<[XYCoords; 2]> = {
-        ISOTHERMS
-        .iter()
-        .map(|t| {
-            [
-                TPCoords{temperature:*t, pressure:MAXP},
-                TPCoords{temperature:*t, pressure:MINP}
-            ]
-        })
-        .map(|tp| {
-            [
-                SkewTContext::convert_tp_to_xy(tp[0]),
-                SkewTContext::convert_tp_to_xy(tp[1])
-            ]
-        })
-        .collect()
-    };
+/// Background lines that depend on the skew-t geometry, regenerated when it changes.
+pub struct SkewTBackground {
+    pub isotherm_pnts: Vec<[XYCoords; 2]>,
+    pub isobar_pnts: Vec<Vec<XYCoords>>,
+    /// Level isobars for the profiles beside the skew-t, which share its vertical coordinate.
+    pub profile_isobar_pnts: Vec<[XYCoords; 2]>,
+    pub isentrop_pnts: Vec<Vec<XYCoords>>,
+    pub iso_mixing_ratio_pnts: Vec<Vec<XYCoords>>,
+    pub iso_theta_e_pnts: Vec<Vec<XYCoords>>,
+    pub iso_omega_pnts: Vec<[XYCoords; 2]>,
+    pub cloud_percent_pnts: Vec<[XYCoords; 2]>,
+    pub profile_speed_pnts: Vec<[XYCoords; 2]>,
+}
 
-    /// Compute points for background isobars only once
-    pub static ref ISOBAR_PNTS: Vec<[XYCoords; 2]> = {
-        ISOBARS
-        .iter()
-        .map(|p| {
-            [
-                TPCoords{temperature:Celsius(-150.0), pressure:*p},
-                TPCoords{temperature:Celsius(60.0), pressure:*p}
-            ]
-        })
-        .map(|tp| {
-            [
-                SkewTContext::convert_tp_to_xy(tp[0]),
-                SkewTContext::convert_tp_to_xy(tp[1])
-            ]
-        })
-        .collect()
-    };
+impl SkewTBackground {
+    /// Generate the background lines for a skew-t geometry.
+    pub fn new(geometry: SkewTGeometry) -> Self {
+        let SkewTGeometry { max_p, min_p, .. } = geometry;
 
-    /// Compute points for background isentrops only once
-    pub static ref ISENTROP_PNTS: Vec<Vec<XYCoords>> = {
-        ISENTROPS
-        .iter()
-        .map(|theta| generate_isentrop(*theta))
-        .collect()
-    };
+        let isotherm_pnts = ISOTHERMS
+            .iter()
+            .map(|t| SkewTContext::isotherm_xy(*t, geometry))
+            .collect();
+
+        let isobars = ISOBARS.iter().filter(|&&p| p >= min_p && p <= max_p);
+
+        let isobar_pnts = isobars
+            .clone()
+            .map(|&p| {
+                // Cold enough to reach the left edge of a tephigram near the top.
+                let (cold_t, warm_t) = (Celsius(-200.0), Celsius(60.0));
+                let dt = (warm_t - cold_t).unpack() / f64::from(POINTS_PER_ISOBAR);
+
+                (0..=POINTS_PER_ISOBAR)
+                    .map(|i| TPCoords {
+                        temperature: Celsius(cold_t.unpack() + dt * f64::from(i)),
+                        pressure: p,
+                    })
+                    .map(|tp| SkewTContext::convert_tp_to_xy(tp, geometry))
+                    .collect()
+            })
+            .collect();
 
-    /// Compute points for background mixing ratio only once
-    pub static ref ISO_MIXING_RATIO_PNTS: Vec<[XYCoords; 2]> = {
-        use metfor::*;
+        let profile_isobar_pnts = isobars
+            .map(|&p| {
+                let y = convert_pressure_to_y(p, geometry);
+                [XYCoords { x: 0.0, y }, XYCoords { x: 1.0, y }]
+            })
+            .collect();
 
-        ISO_MIXING_RATIO
-        .iter()
-        .map(|mw| {
-            [
-                TPCoords{
-                    temperature: dew_point_from_p_and_mw(MAXP, *mw/1000.0)
-                        .expect("dp from mw fail"),
-                    pressure: MAXP
-                },
-                TPCoords{
-                    temperature: dew_point_from_p_and_mw(ISO_MIXING_RATIO_TOP_P, *mw/1000.0)
-                        .expect("dp from mw fail"),
-                    pressure: ISO_MIXING_RATIO_TOP_P,
-                },
-            ]
-        })
-        .map(|tp| {
-            [
-                SkewTContext::convert_tp_to_xy(tp[0]),
-                SkewTContext::convert_tp_to_xy(tp[1])
-            ]
-        })
-        .collect()
-    };
+        let isentrop_pnts = ISENTROPS
+            .iter()
+            .map(|theta| generate_isentrop(*theta, geometry))
+            .collect();
 
-    /// Compute points for background theta-e
-    pub static ref ISO_THETA_E_PNTS: Vec<Vec<XYCoords>> = {
-        use metfor::equiv_pot_temperature;
+        let iso_mixing_ratio_pnts = ISO_MIXING_RATIO
+            .iter()
+            .map(|mw| generate_iso_mixing_ratio(*mw, geometry))
+            .collect();
+
+        let iso_theta_e_pnts = {
+            use metfor::equiv_pot_temperature;
+
+            ISO_THETA_E_C
+                .iter()
+                .map(|theta_c| {
+                    equiv_pot_temperature(*theta_c, *theta_c, HectoPascal(1000.0))
+                        .expect("theta_e isopleth failed")
+                })
+                .map(|theta_e| generate_theta_e_isopleth(theta_e, geometry))
+                .collect()
+        };
+
+        let iso_omega_pnts = ISO_OMEGA
+            .iter()
+            .map(|w| [WPCoords { w: *w, p: min_p }, WPCoords { w: *w, p: max_p }])
+            .map(|tp| {
+                [
+                    RHOmegaContext::convert_wp_to_xy(tp[0], geometry),
+                    RHOmegaContext::convert_wp_to_xy(tp[1], geometry),
+                ]
+            })
+            .collect();
 
-        ISO_THETA_E_C
-        .iter()
-        .map(|theta_c| equiv_pot_temperature(*theta_c, *theta_c, HectoPascal(1000.0)).expect("theta_e isopleth failed"))
-        .map(generate_theta_e_isopleth)
-        .collect()
-    };
+        let cloud_percent_pnts = PERCENTS
+            .iter()
+            .map(|p| {
+                [
+                    PPCoords {
+                        pcnt: *p / 100.0,
+                        press: min_p,
+                    },
+                    PPCoords {
+                        pcnt: *p / 100.0,
+                        press: max_p,
+                    },
+                ]
+            })
+            .map(|pp| {
+                [
+                    CloudContext::convert_pp_to_xy(pp[0], geometry),
+                    CloudContext::convert_pp_to_xy(pp[1], geometry),
+                ]
+            })
+            .collect();
 
-    /// Compute points for background omega
-    pub static ref ISO_OMEGA_PNTS: Vec<[XYCoords; 2]> = {
-        ISO_OMEGA
+        let profile_speed_pnts = PROFILE_SPEEDS
             .iter()
-            .map(|w| {
+            .map(|speed| {
                 [
-                WPCoords {
-                    w: *w,
-                    p: MINP,
-                },
-                WPCoords {
-                    w: *w,
-                    p: MAXP,
-                },
-            ]
+                    SPCoords {
+                        spd: *speed,
+                        press: min_p,
+                    },
+                    SPCoords {
+                        spd: *speed,
+                        press: max_p,
+                    },
+                ]
             })
-        .map(|tp| {
-            [
-                RHOmegaContext::convert_wp_to_xy(tp[0]),
-                RHOmegaContext::convert_wp_to_xy(tp[1])
-            ]
-        })
-        .collect()
-    };
+            .map(|sp| {
+                [
+                    WindSpeedContext::convert_sp_to_xy(sp[0], geometry),
+                    WindSpeedContext::convert_sp_to_xy(sp[1], geometry),
+                ]
+            })
+            .collect();
+
+        SkewTBackground {
+            isotherm_pnts,
+            isobar_pnts,
+            profile_isobar_pnts,
+            isentrop_pnts,
+            iso_mixing_ratio_pnts,
+            iso_theta_e_pnts,
+            iso_omega_pnts,
+            cloud_percent_pnts,
+            profile_speed_pnts,
+        }
+    }
+}
+
+lazy_static! {
 
     /// Compute points for background speed
     pub static ref ISO_SPEED_PNTS: Vec<Vec<XYCoords>> = {
@@ -941,56 +1680,6 @@ lazy_static! {
         .collect()
     };
 
-    /// Compute points for background cloud coverage
-    pub static ref CLOUD_PERCENT_PNTS: Vec<[XYCoords; 2]> = {
-        PERCENTS
-            .iter()
-            .map(|p| {
-                [
-                PPCoords {
-                    pcnt: *p / 100.0,
-                    press: MINP,
-                },
-                PPCoords {
-                    pcnt: *p / 100.0,
-                    press: MAXP,
-                },
-            ]
-            })
-        .map(|pp| {
-            [
-                CloudContext::convert_pp_to_xy(pp[0]),
-                CloudContext::convert_pp_to_xy(pp[1])
-            ]
-        })
-            .collect()
-    };
-
-    /// Compute points for background cloud coverage
-    pub static ref PROFILE_SPEED_PNTS: Vec<[XYCoords; 2]> = {
-        PROFILE_SPEEDS
-            .iter()
-            .map(|speed| {
-                [
-                SPCoords {
-                    spd: *speed,
-                    press: MINP,
-                },
-                SPCoords {
-                    spd: *speed,
-                    press: MAXP,
-                },
-            ]
-            })
-        .map(|sp| {
-            [
-                WindSpeedContext::convert_sp_to_xy(sp[0]),
-                WindSpeedContext::convert_sp_to_xy(sp[1])
-            ]
-        })
-            .collect()
-    };
-
     /// Compute points for background fire power in fire plume charts
     pub static ref FIRE_PLUME_GW_PNTS: Vec<[XYCoords; 2]> = {
        FIRE_PLUME_GW
@@ -1068,39 +1757,86 @@ lazy_static! {
 
 }
 
+/// Generate a list of Temperature, Pressure points along a line of constant mixing ratio, given
+/// in g/kg.
+pub fn generate_iso_mixing_ratio(mw: f64, geometry: SkewTGeometry) -> Vec<XYCoords> {
+    generate_iso_mixing_ratio_to(mw, ISO_MIXING_RATIO_TOP_P, geometry)
+}
+
+/// Generate a list of Temperature, Pressure points along an isopleth of mixing ratio from the
+/// bottom of the skew-t up to `top_p`, or the top of the skew-t if it is higher.
+pub fn generate_iso_mixing_ratio_to(
+    mw: f64,
+    top_p: HectoPascal,
+    geometry: SkewTGeometry,
+) -> Vec<XYCoords> {
+    use metfor::dew_point_from_p_and_mw;
+
+    let SkewTGeometry { max_p, min_p, .. } = geometry;
+    let top_p = if top_p > min_p { top_p } else { min_p };
+
+    let dp = (top_p - max_p).unpack() / f64::from(POINTS_PER_ISO_MIXING_RATIO);
+
+    (0..=POINTS_PER_ISO_MIXING_RATIO)
+        .map(|i| HectoPascal(max_p.unpack() + dp * f64::from(i)))
+        .filter_map(|p| {
+            dew_point_from_p_and_mw(p, mw / 1000.0).map(|temperature| TPCoords {
+                temperature,
+                pressure: p,
+            })
+        })
+        .map(|tp| SkewTContext::convert_tp_to_xy(tp, geometry))
+        .collect()
+}
+
 /// Generate a list of Temperature, Pressure points along an isentrope.
-fn generate_isentrop(theta: Kelvin) -> Vec<XYCoords> {
+pub fn generate_isentrop(theta: Kelvin, geometry: SkewTGeometry) -> Vec<XYCoords> {
     use metfor::temperature_from_pot_temp;
     use std::f64;
 
+    let SkewTGeometry { max_p, min_p, .. } = geometry;
+
     let mut result = vec![];
 
-    let mut p = MAXP;
-    while p >= ISENTROPS_TOP_P {
+    let mut p = max_p;
+    while p >= min_p {
         let t: Celsius = temperature_from_pot_temp(theta, p).into();
-        result.push(SkewTContext::convert_tp_to_xy(TPCoords {
-            temperature: t,
-            pressure: p,
-        }));
-        p += HectoPascal((ISENTROPS_TOP_P - MAXP).unpack() / f64::from(POINTS_PER_ISENTROP));
+        result.push(SkewTContext::convert_tp_to_xy(
+            TPCoords {
+                temperature: t,
+                pressure: p,
+            },
+            geometry,
+        ));
+        p += HectoPascal((min_p - max_p).unpack() / f64::from(POINTS_PER_ISENTROP));
     }
-    let t: Celsius = temperature_from_pot_temp(theta, ISENTROPS_TOP_P).into();
+    let t: Celsius = temperature_from_pot_temp(theta, min_p).into();
 
-    result.push(SkewTContext::convert_tp_to_xy(TPCoords {
-        temperature: t,
-        pressure: ISENTROPS_TOP_P,
-    }));
+    result.push(SkewTContext::convert_tp_to_xy(
+        TPCoords {
+            temperature: t,
+            pressure: min_p,
+        },
+        geometry,
+    ));
 
     result
 }
 
 /// Generate an isopleth for equivalent potential temperatures.
-fn generate_theta_e_isopleth(theta_e_k: Kelvin) -> Vec<XYCoords> {
+pub fn generate_theta_e_isopleth(theta_e_k: Kelvin, geometry: SkewTGeometry) -> Vec<XYCoords> {
+    let SkewTGeometry { max_p, min_p, .. } = geometry;
+    let top_p = if THETA_E_TOP_P > min_p {
+        THETA_E_TOP_P
+    } else {
+        min_p
+    };
+
     let mut v = vec![];
-    let mut p = THETA_E_TOP_P;
-    let dp = HectoPascal((MAXP - MINP).unpack() / f64::from(POINTS_PER_ISENTROP));
+    let mut p = top_p;
+    let dp = HectoPascal((max_p - min_p).unpack() / f64::from(POINTS_PER_ISENTROP));
 
-    while p < MAXP + dp * 1.0001 {
+    while p < max_p + dp * 1.0001 {
         match metfor::find_root(
             &|t| {
                 Some(
@@ -1112,10 +1848,13 @@ fn generate_theta_e_isopleth(theta_e_k: Kelvin) -> Vec<XYCoords> {
             Celsius(50.0).unpack(),
         )
         .map(|t| {
-            v.push(SkewTContext::convert_tp_to_xy(TPCoords {
-                temperature: Celsius(t),
-                pressure: p,
-            }));
+            v.push(SkewTContext::convert_tp_to_xy(
+                TPCoords {
+                    temperature: Celsius(t),
+                    pressure: p,
+                },
+                geometry,
+            ));
         }) {
             Some(_) => p += dp,
             None => {
@@ -1130,8 +1869,8 @@ fn generate_theta_e_isopleth(theta_e_k: Kelvin) -> Vec<XYCoords> {
                                 .unpack(),
                         )
                     },
-                    THETA_E_TOP_P.unpack(),
-                    MAXP.unpack(),
+                    top_p.unpack(),
+                    max_p.unpack(),
                 )
                 .map(HectoPascal)
                 .unwrap_or_else(|| p + HectoPascal(1.0))
@@ -0,0 +1,112 @@
+//! Parcels defined by the user.
+//!
+//! The parcels themselves are kept in the configuration so they are saved between sessions, this
+//! only keeps track of whether clicking on the skew-t adds a parcel lifted from the clicked level.
+use super::{
+    config::{CustomParcel, CustomParcelSource, CUSTOM_PARCEL_COLORS},
+    AppContext, AppContextPointer,
+};
+use metfor::{HectoPascal, Quantity};
+
+#[derive(Default)]
+pub struct CustomParcelTool {
+    enabled: bool,
+}
+
+impl AppContext {
+    /// Does clicking on the skew-t add a custom parcel instead of panning?
+    pub fn is_adding_custom_parcels(&self) -> bool {
+        self.custom_parcel_tool.borrow().enabled
+    }
+
+    /// Turn adding custom parcels by clicking on the skew-t on or off.
+    pub fn toggle_adding_custom_parcels(&self) {
+        let mut tool = self.custom_parcel_tool.borrow_mut();
+        tool.enabled = !tool.enabled;
+    }
+
+    /// Add a custom parcel, giving it the next color in the palette.
+    pub fn add_custom_parcel(acp: &AppContextPointer, name: String, source: CustomParcelSource) {
+        {
+            let mut config = acp.config.borrow_mut();
+            let rgba =
+                CUSTOM_PARCEL_COLORS[config.custom_parcels.len() % CUSTOM_PARCEL_COLORS.len()];
+            config.custom_parcels.push(CustomParcel {
+                name,
+                source,
+                show: true,
+                rgba,
+            });
+        }
+
+        Self::update_custom_parcels(acp);
+    }
+
+    /// Add a custom parcel lifted from the sounding's values at a level.
+    pub fn add_custom_parcel_at(acp: &AppContextPointer, pressure: HectoPascal) {
+        let name = {
+            let p_units = acp.config.borrow().pressure_units;
+            format!(
+                "{:.*} {}",
+                p_units.precision(),
+                p_units.value(pressure),
+                p_units.label()
+            )
+        };
+
+        // Round to a tenth of a hectopascal so the saved configuration stays tidy.
+        let pressure = HectoPascal((pressure.unpack() * 10.0).round() / 10.0);
+
+        Self::add_custom_parcel(acp, name, CustomParcelSource::Level { pressure });
+    }
+
+    /// Remove a custom parcel.
+    pub fn remove_custom_parcel(acp: &AppContextPointer, index: usize) {
+        {
+            let mut config = acp.config.borrow_mut();
+            if index >= config.custom_parcels.len() {
+                return;
+            }
+            config.custom_parcels.remove(index);
+        }
+
+        Self::update_custom_parcels(acp);
+    }
+
+    /// Lift the custom parcels from everything loaded in the background and redraw everything
+    /// showing them after they were changed.
+    pub fn update_custom_parcels(acp: &AppContextPointer) {
+        let sources: Vec<CustomParcelSource> = acp
+            .config
+            .borrow()
+            .custom_parcels
+            .iter()
+            .map(|pcl| pcl.source)
+            .collect();
+
+        let changed = {
+            let (list, comparison_list) = (acp.list.borrow(), acp.comparison_list.borrow());
+            let stations = acp.stations.borrow();
+
+            let mut changed = false;
+            for anal in list
+                .iter()
+                .chain(comparison_list.iter())
+                .chain(stations.unselected())
+            {
+                changed |= anal.borrow_mut().update_custom_parcels_mut(&sources);
+            }
+
+            changed
+        };
+
+        if changed {
+            Self::fill_in_analysis(acp);
+        }
+
+        acp.mark_data_dirty();
+        crate::gui::draw_all(acp);
+        crate::gui::update_text_views(acp);
+        crate::gui::update_custom_parcel_list(acp);
+    }
+}
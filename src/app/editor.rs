@@ -0,0 +1,403 @@
+//! Interactive editing of the displayed sounding to build "what-if" scenarios.
+//!
+//! Every edit replaces the displayed entry in the list with a new analysis of the modified
+//! sounding, and the entry it replaced is pushed onto an undo history. The history only covers
+//! the sounding it was started on, it is reset when an edit is started on a different one.
+//!
+//! While a point is dragged the edited sounding keeps the values computed before the edit, and
+//! the basic analysis is redone in the background so the indexes follow the drag.
+use super::{
+    analysis_scheduler::{AnalysisResult, AnalysisStage},
+    AppContext, AppContextPointer,
+};
+use crate::analysis::Analysis;
+use crossbeam_channel::TryRecvError;
+use gtk::glib;
+use itertools::izip;
+use metfor::{Celsius, Knots, WindSpdDir};
+use optional::some;
+use sounding_analysis::Sounding;
+use std::{cell::RefCell, rc::Rc};
+
+/// A point in the sounding that is being dragged, the index is the level in the sounding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditTarget {
+    Temperature(usize),
+    DewPoint(usize),
+    Wind(usize),
+}
+
+#[derive(Default)]
+pub struct SoundingEditor {
+    enabled: bool,
+    dragging: Option<EditTarget>,
+    // Index in the list of the sounding the history belongs to.
+    index: usize,
+    // Previous versions of the sounding, the first one is the original.
+    history: Vec<Rc<RefCell<Analysis>>>,
+    // A background analysis of the sounding being dragged is running.
+    analyzing: bool,
+}
+
+impl SoundingEditor {
+    /// Forget the edit history, e.g. when the list is replaced.
+    pub fn reset(&mut self) {
+        self.dragging = None;
+        self.history.clear();
+    }
+}
+
+impl AppContext {
+    /// Are the skew-t and hodograph in editing mode?
+    pub fn is_editing(&self) -> bool {
+        self.editor.borrow().enabled
+    }
+
+    /// Turn editing mode on or off.
+    pub fn toggle_editing(&self) {
+        {
+            let mut editor = self.editor.borrow_mut();
+            editor.enabled = !editor.enabled;
+            editor.dragging = None;
+        }
+
+        self.mark_data_dirty();
+        crate::gui::draw_all(self);
+    }
+
+    /// The point currently being dragged, if any.
+    pub fn edit_target(&self) -> Option<EditTarget> {
+        self.editor.borrow().dragging
+    }
+
+    /// Start dragging a point in the displayed sounding.
+    pub fn start_edit(&self, target: EditTarget) {
+        if self.push_edit_history() {
+            self.editor.borrow_mut().dragging = Some(target);
+        }
+    }
+
+    /// Move the temperature or dew point being dragged to a new value.
+    ///
+    /// The dew point is never allowed to be warmer than the temperature.
+    pub fn drag_temperature_edit(acp: &AppContextPointer, value: Celsius) {
+        let target = match acp.edit_target() {
+            Some(target @ EditTarget::Temperature(_)) | Some(target @ EditTarget::DewPoint(_)) => {
+                target
+            }
+            _ => return,
+        };
+
+        acp.modify_displayed_sounding(|snd| {
+            let mut temperature = snd.temperature_profile().to_vec();
+            let mut dew_point = snd.dew_point_profile().to_vec();
+
+            match target {
+                EditTarget::Temperature(i) => {
+                    let t = match dew_point.get(i).and_then(|dp| dp.into_option()) {
+                        Some(dp) if value < dp => dp,
+                        _ => value,
+                    };
+                    *temperature.get_mut(i)? = some(t);
+                }
+                EditTarget::DewPoint(i) => {
+                    let dp = match temperature.get(i).and_then(|t| t.into_option()) {
+                        Some(t) if value > t => t,
+                        _ => value,
+                    };
+                    *dew_point.get_mut(i)? = some(dp);
+                }
+                EditTarget::Wind(_) => return None,
+            }
+
+            Some(
+                snd.clone()
+                    .with_temperature_profile(temperature)
+                    .with_dew_point_profile(dew_point),
+            )
+        });
+
+        Self::analyze_live_edit(acp);
+    }
+
+    /// Move the wind being dragged to a new value.
+    pub fn drag_wind_edit(acp: &AppContextPointer, value: WindSpdDir<Knots>) {
+        let i = match acp.edit_target() {
+            Some(EditTarget::Wind(i)) => i,
+            _ => return,
+        };
+
+        acp.modify_displayed_sounding(|snd| {
+            let mut wind = snd.wind_profile().to_vec();
+            *wind.get_mut(i)? = some(value);
+
+            Some(snd.clone().with_wind_profile(wind))
+        });
+
+        Self::analyze_live_edit(acp);
+    }
+
+    /// Replace the surface temperature and dew point, e.g. with a forecast afternoon maximum, and
+    /// mix the boundary layer dry adiabatically from the new surface values.
+    pub fn modify_surface(acp: &AppContextPointer, temperature: Celsius, dew_point: Celsius) {
+        if acp.push_edit_history() {
+            acp.modify_displayed_sounding(|snd| mix_from_surface(snd, temperature, dew_point));
+            Self::finish_edit(acp);
+        }
+    }
+
+    /// Stop dragging and analyze the edited sounding in the background.
+    pub fn finish_edit(acp: &AppContextPointer) {
+        {
+            let mut editor = acp.editor.borrow_mut();
+            editor.dragging = None;
+
+            // If nothing was actually changed, there is nothing to undo.
+            if let (Some(last), Some(displayed)) =
+                (editor.history.last(), acp.get_sounding_for_display())
+            {
+                if Rc::ptr_eq(last, &displayed) {
+                    editor.history.pop();
+                    return;
+                }
+            }
+        }
+
+        Self::fill_in_displayed_analysis(acp);
+    }
+
+    /// Go back to the version of the sounding before the last edit.
+    pub fn undo_edit(&self) {
+        let previous = {
+            let mut editor = self.editor.borrow_mut();
+            if editor.index != self.currently_displayed_index.get() {
+                return;
+            }
+            editor.history.pop()
+        };
+
+        if let Some(previous) = previous {
+            self.replace_displayed(previous);
+        }
+    }
+
+    /// Throw away all the edits of the displayed sounding.
+    pub fn revert_edits(&self) {
+        let original = {
+            let mut editor = self.editor.borrow_mut();
+            if editor.index != self.currently_displayed_index.get() || editor.history.is_empty() {
+                return;
+            }
+            let original = editor.history.swap_remove(0);
+            editor.history.clear();
+            original
+        };
+
+        self.replace_displayed(original);
+    }
+
+    /// Keep the edited sounding as a new entry in the list right after the original, and put the
+    /// original back in its place.
+    pub fn save_edits_as_new(&self) {
+        let original = {
+            let mut editor = self.editor.borrow_mut();
+            if editor.index != self.currently_displayed_index.get() || editor.history.is_empty() {
+                return;
+            }
+            let original = editor.history.swap_remove(0);
+            editor.history.clear();
+            original
+        };
+
+        let index = self.currently_displayed_index.get();
+        {
+            let mut list = self.list.borrow_mut();
+            let edited = std::mem::replace(&mut list[index], original);
+            list.insert(index + 1, edited);
+        }
+
+        self.set_currently_displayed(index + 1);
+    }
+
+    // Redo the basic analysis of the sounding being dragged at the top priority. Only one job runs
+    // at a time, if the sounding was dragged further while it ran its values are shown with the
+    // newest version of the sounding and that version is analyzed next. The full analysis is done
+    // by `finish_edit` when the drag is over.
+    fn analyze_live_edit(acp: &AppContextPointer) {
+        if acp.editor.borrow().analyzing {
+            return;
+        }
+
+        let displayed = match acp.get_sounding_for_display() {
+            Some(anal) if anal.borrow().is_stale() => anal,
+            _ => return,
+        };
+
+        let index = acp.currently_displayed_index.get();
+        let load_id = acp.load_calls.get();
+        let (tx, rx) = crossbeam_channel::bounded(1);
+
+        acp.editor.borrow_mut().analyzing = true;
+        acp.analysis_scheduler
+            .submit(index, AnalysisStage::Basic, displayed.borrow().clone(), tx);
+
+        let acp = Rc::clone(acp);
+        glib::idle_add_local(move || {
+            let anal = match rx.try_recv() {
+                Ok(AnalysisResult { anal, .. }) => anal,
+                Err(TryRecvError::Empty) => return glib::ControlFlow::Continue,
+                Err(TryRecvError::Disconnected) => {
+                    acp.editor.borrow_mut().analyzing = false;
+                    return glib::ControlFlow::Break;
+                }
+            };
+            acp.editor.borrow_mut().analyzing = false;
+
+            if load_id != acp.load_calls.get() || index != acp.currently_displayed_index.get() {
+                return glib::ControlFlow::Break;
+            }
+
+            // Results from the analysis started by `finish_edit` are never replaced.
+            let current = match acp.get_sounding_for_display() {
+                Some(anal) if anal.borrow().is_stale() => anal,
+                _ => return glib::ControlFlow::Break,
+            };
+
+            let edited_again = !Rc::ptr_eq(&current, &displayed);
+            let anal = if edited_again {
+                let snd = current.borrow().sounding().clone();
+                anal.with_modified_sounding(snd)
+            } else {
+                anal
+            };
+            *current.borrow_mut() = anal;
+
+            acp.mark_data_dirty();
+            acp.update_all_gui();
+
+            if edited_again && acp.edit_target().is_some() {
+                Self::analyze_live_edit(&acp);
+            }
+
+            glib::ControlFlow::Break
+        });
+    }
+
+    // Save the displayed sounding in the history before changing it.
+    fn push_edit_history(&self) -> bool {
+        let displayed = match self.get_sounding_for_display() {
+            Some(anal) => anal,
+            None => return false,
+        };
+
+        let mut editor = self.editor.borrow_mut();
+        let index = self.currently_displayed_index.get();
+        if editor.index != index {
+            editor.history.clear();
+            editor.index = index;
+        }
+
+        editor.history.push(displayed);
+        true
+    }
+
+    // Apply a modification to a copy of the displayed sounding and display the result.
+    fn modify_displayed_sounding<F>(&self, modify: F)
+    where
+        F: FnOnce(&Sounding) -> Option<Sounding>,
+    {
+        let displayed = match self.get_sounding_for_display() {
+            Some(anal) => anal,
+            None => return,
+        };
+
+        let original_description = self.editor.borrow().history.first().and_then(|anal| {
+            anal.borrow()
+                .sounding()
+                .source_description()
+                .map(str::to_owned)
+        });
+
+        let edited = {
+            let anal = displayed.borrow();
+            let snd = match modify(anal.sounding()) {
+                Some(snd) => snd,
+                None => return,
+            };
+
+            let description = original_description.map(|desc| format!("{} (edited)", desc));
+            anal.with_modified_sounding(snd.with_source_description(description))
+        };
+
+        self.replace_displayed(Rc::new(RefCell::new(edited)));
+    }
+
+    fn replace_displayed(&self, anal: Rc<RefCell<Analysis>>) {
+        let index = self.currently_displayed_index.get();
+
+        match self.list.borrow_mut().get_mut(index) {
+            Some(entry) => *entry = anal,
+            None => return,
+        }
+
+        self.update_sample();
+        self.mark_data_dirty();
+        self.update_all_gui();
+    }
+}
+
+/// Build a sounding with a well mixed layer from the surface up to where the dry adiabat through
+/// the new surface temperature meets the original profile. The mixing ratio of the new surface
+/// dew point is used throughout the mixed layer.
+fn mix_from_surface(snd: &Sounding, temperature: Celsius, dew_point: Celsius) -> Option<Sounding> {
+    let dew_point = if dew_point > temperature {
+        temperature
+    } else {
+        dew_point
+    };
+
+    let sfc_p = snd
+        .bottom_up()
+        .filter_map(|row| row.pressure.into_option())
+        .next()?;
+    let theta = metfor::potential_temperature(sfc_p, temperature);
+    let mw = metfor::mixing_ratio(dew_point, sfc_p)?;
+
+    let mut temperature_profile = snd.temperature_profile().to_vec();
+    let mut dew_point_profile = snd.dew_point_profile().to_vec();
+
+    for (p, t, dp) in izip!(
+        snd.pressure_profile(),
+        &mut temperature_profile,
+        &mut dew_point_profile
+    ) {
+        let p = match p.into_option() {
+            Some(p) if p <= sfc_p => p,
+            _ => continue,
+        };
+
+        let mixed_t = Celsius::from(metfor::temperature_from_pot_temp(theta, p));
+
+        // The top of the mixed layer is where the original profile is warmer than the adiabat.
+        if p < sfc_p && t.into_option().is_some_and(|t| t >= mixed_t) {
+            break;
+        }
+
+        *t = some(mixed_t);
+        if let Some(mixed_dp) = metfor::dew_point_from_p_and_mw(p, mw) {
+            *dp = some(if mixed_dp > mixed_t {
+                mixed_t
+            } else {
+                mixed_dp
+            });
+        }
+    }
+
+    Some(
+        snd.clone()
+            .with_temperature_profile(temperature_profile)
+            .with_dew_point_profile(dew_point_profile)
+            .with_sfc_temperature(some(temperature))
+            .with_sfc_dew_point(some(dew_point)),
+    )
+}
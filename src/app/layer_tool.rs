@@ -0,0 +1,77 @@
+//! Measuring a layer by dragging between two levels on the skew-t.
+//!
+//! The levels are kept as pressures, so the measurement follows the displayed sounding as the
+//! time changes until it is cleared or a new layer is dragged out.
+use super::AppContext;
+use metfor::HectoPascal;
+
+#[derive(Default)]
+pub struct LayerTool {
+    enabled: bool,
+    dragging: bool,
+    // Level the drag started at and the level it is at now, or ended at.
+    start: Option<HectoPascal>,
+    end: Option<HectoPascal>,
+}
+
+impl AppContext {
+    /// Does dragging on the skew-t measure a layer instead of panning?
+    pub fn is_measuring_layer(&self) -> bool {
+        self.layer_tool.borrow().enabled
+    }
+
+    /// Turn the layer measuring tool on or off, turning it off clears the measurement.
+    pub fn toggle_measuring_layer(&self) {
+        {
+            let mut tool = self.layer_tool.borrow_mut();
+            *tool = LayerTool {
+                enabled: !tool.enabled,
+                ..LayerTool::default()
+            };
+        }
+
+        self.mark_overlay_dirty();
+        crate::gui::draw_all(self);
+    }
+
+    /// Is a layer being dragged out right now?
+    pub fn is_dragging_layer(&self) -> bool {
+        self.layer_tool.borrow().dragging
+    }
+
+    /// Start a new layer at a level, replacing the last one.
+    pub fn start_layer(&self, pressure: HectoPascal) {
+        {
+            let mut tool = self.layer_tool.borrow_mut();
+            tool.dragging = true;
+            tool.start = Some(pressure);
+            tool.end = None;
+        }
+
+        self.mark_overlay_dirty();
+        crate::gui::draw_all(self);
+    }
+
+    /// Move the other end of the layer being dragged out.
+    pub fn drag_layer(&self, pressure: HectoPascal) {
+        if !self.is_dragging_layer() {
+            return;
+        }
+
+        self.layer_tool.borrow_mut().end = Some(pressure);
+
+        self.mark_overlay_dirty();
+        crate::gui::draw_all(self);
+    }
+
+    /// Stop dragging, the layer stays measured until the next one is started.
+    pub fn finish_layer(&self) {
+        self.layer_tool.borrow_mut().dragging = false;
+    }
+
+    /// The two levels bounding the measured layer, in the order they were picked.
+    pub fn measured_layer(&self) -> Option<(HectoPascal, HectoPascal)> {
+        let tool = self.layer_tool.borrow();
+        tool.start.zip(tool.end)
+    }
+}
@@ -1,8 +1,9 @@
 use crate::{
-    analysis::Analysis,
-    app::{AppContext, AppContextPointer},
+    analysis::{cache, Analysis, AnalysisSettings},
+    app::{AppContext, AppContextPointer, StationKey},
     errors::SondeError,
 };
+use itertools::Itertools;
 use sonde_bufr::load_309052_sounding;
 use sounding_bufkit::BufkitFile;
 use std::{
@@ -13,7 +14,11 @@ use std::{
 };
 
 pub fn load_multiple(paths: &[PathBuf], ac: &AppContextPointer) -> Result<(), Box<dyn Error>> {
-    let datas: Result<Vec<_>, _> = paths.iter().map(|pb| load_file(pb)).collect();
+    let settings = ac.config.borrow().analysis_settings();
+    let datas: Result<Vec<_>, _> = paths
+        .iter()
+        .map(|pb| load_file_with_cache(pb, settings))
+        .collect();
     let mut datas: Vec<_> = datas?.into_iter().flatten().collect();
 
     // Sort by valid time ascending, then by lead time ascending
@@ -38,26 +43,50 @@ pub fn load_multiple(paths: &[PathBuf], ac: &AppContextPointer) -> Result<(), Bo
         }
     });
 
-    // Scan through the list and only pass the first value with a given valid time.
-    let datas = datas
-        .into_iter()
-        .scan(None, |prev, anal| {
-            let valid_time = anal.sounding().valid_time();
-            if *prev == valid_time {
-                Some(None)
-            } else {
-                *prev = valid_time;
-                Some(Some(anal))
-            }
-        })
-        // Get rid of the None and only let the
-        .flatten();
+    // Only pass the first value with a given valid time for each station.
+    let datas = datas.into_iter().unique_by(|anal| {
+        (
+            StationKey::of(anal.sounding()),
+            anal.sounding().valid_time(),
+        )
+    });
 
     AppContext::load_data(Rc::clone(ac), datas);
 
     Ok(())
 }
 
+/// Load soundings from another run or model to compare against the ones already loaded.
+pub fn load_comparison(paths: &[PathBuf], ac: &AppContextPointer) -> Result<(), Box<dyn Error>> {
+    let datas: Result<Vec<_>, _> = paths.iter().map(|pb| load_file(pb)).collect();
+    let datas = datas?.into_iter().flatten();
+
+    AppContext::load_comparison_data(ac, datas);
+
+    Ok(())
+}
+
+/// Load a file and fill in anything we already computed the last time it was opened.
+fn load_file_with_cache(
+    path: &Path,
+    settings: AnalysisSettings,
+) -> Result<Vec<Analysis>, Box<dyn Error>> {
+    let mut data: Vec<_> = load_file(path)?
+        .into_iter()
+        .map(|anal| anal.with_settings(settings))
+        .collect();
+
+    if let Ok(key) = cache::file_key(path) {
+        cache::apply_cached(key, &mut data);
+        data = data
+            .into_iter()
+            .map(|anal| anal.with_source_key(key))
+            .collect();
+    }
+
+    Ok(data)
+}
+
 // Make `pub` so I can use it in benches too.
 pub fn load_file(path: &Path) -> Result<Vec<Analysis>, Box<dyn Error>> {
     let extension: Option<String> = path
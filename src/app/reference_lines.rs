@@ -0,0 +1,46 @@
+//! Reference lines through points clicked on the skew-t.
+//!
+//! Each point gets the dry adiabat, moist adiabat, and saturation mixing ratio line through it,
+//! and they stay on the chart until they are cleared.
+use super::AppContext;
+use crate::coords::TPCoords;
+
+#[derive(Default)]
+pub struct ReferenceLines {
+    enabled: bool,
+    points: Vec<TPCoords>,
+}
+
+impl AppContext {
+    /// Does clicking on the skew-t add reference lines instead of panning?
+    pub fn is_placing_reference_lines(&self) -> bool {
+        self.reference_lines.borrow().enabled
+    }
+
+    /// Turn placing reference lines on or off, the lines already placed stay.
+    pub fn toggle_placing_reference_lines(&self) {
+        let mut lines = self.reference_lines.borrow_mut();
+        lines.enabled = !lines.enabled;
+    }
+
+    /// Add reference lines through a point.
+    pub fn add_reference_lines(&self, point: TPCoords) {
+        self.reference_lines.borrow_mut().points.push(point);
+
+        self.mark_data_dirty();
+        crate::gui::draw_all(self);
+    }
+
+    /// Remove all the reference lines.
+    pub fn clear_reference_lines(&self) {
+        self.reference_lines.borrow_mut().points.clear();
+
+        self.mark_data_dirty();
+        crate::gui::draw_all(self);
+    }
+
+    /// The points the reference lines go through.
+    pub fn reference_points(&self) -> Vec<TPCoords> {
+        self.reference_lines.borrow().points.clone()
+    }
+}
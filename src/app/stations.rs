@@ -0,0 +1,156 @@
+//! Group the loaded soundings by station so files for several stations can be open at once.
+//!
+//! Only the soundings for the selected station are in the list, so everything that works with the
+//! list, like time navigation, the series plots, and the analysis, sees one station at a time. The
+//! soundings for the other stations wait here until they are selected.
+use super::{AppContext, AppContextPointer};
+use crate::analysis::Analysis;
+use itertools::Itertools;
+use sounding_analysis::Sounding;
+use std::{cell::RefCell, rc::Rc};
+
+/// Identifies a station by its number and location.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StationKey {
+    num: Option<i32>,
+    // Latitude and longitude in hundredths of a degree, so small differences between files don't
+    // split a station in two.
+    location: Option<(i64, i64)>,
+}
+
+impl StationKey {
+    /// Get the key for the station a sounding is for.
+    pub fn of(snd: &Sounding) -> Self {
+        let info = snd.station_info();
+
+        StationKey {
+            num: info.station_num().into_option(),
+            location: info
+                .location()
+                .map(|(lat, lon)| ((lat * 100.0).round() as i64, (lon * 100.0).round() as i64)),
+        }
+    }
+
+    /// Name to show in the station selector.
+    pub fn label(&self) -> String {
+        let location = self
+            .location
+            .map(|(lat, lon)| format!("{:.2}, {:.2}", lat as f64 / 100.0, lon as f64 / 100.0));
+
+        match (self.num, location) {
+            (Some(num), Some(location)) => format!("{} ({})", num, location),
+            (Some(num), None) => num.to_string(),
+            (None, Some(location)) => location,
+            (None, None) => "Unknown station".to_owned(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Stations {
+    // Every loaded station, in the order they were first seen.
+    keys: Vec<StationKey>,
+    // The soundings for each station sorted by valid time, except the selected one's are in the
+    // list instead.
+    lists: Vec<Vec<Rc<RefCell<Analysis>>>>,
+    selected: usize,
+}
+
+impl Stations {
+    /// Replace all the stations with the ones these soundings are for, select the first one, and
+    /// return its soundings.
+    pub fn replace<I>(&mut self, anals: I) -> Vec<Rc<RefCell<Analysis>>>
+    where
+        I: Iterator<Item = Rc<RefCell<Analysis>>>,
+    {
+        let mut keys: Vec<StationKey> = vec![];
+        let mut lists: Vec<Vec<_>> = vec![];
+
+        for anal in anals {
+            let key = StationKey::of(anal.borrow().sounding());
+            match keys.iter().position(|&k| k == key) {
+                Some(i) => lists[i].push(anal),
+                None => {
+                    keys.push(key);
+                    lists.push(vec![anal]);
+                }
+            }
+        }
+
+        for list in lists.iter_mut() {
+            list.sort_by_key(|anal| anal.borrow().sounding().valid_time());
+        }
+
+        self.keys = keys;
+        self.selected = 0;
+        self.lists = lists;
+
+        self.lists
+            .first_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// The soundings for every station except the selected one.
+    pub fn unselected(&self) -> impl Iterator<Item = &Rc<RefCell<Analysis>>> {
+        self.lists.iter().flatten()
+    }
+}
+
+impl AppContext {
+    /// Names of the loaded stations, in the order they are in the station selector.
+    pub fn station_labels(&self) -> Vec<String> {
+        self.stations
+            .borrow()
+            .keys
+            .iter()
+            .map(StationKey::label)
+            .collect()
+    }
+
+    /// Index of the station the list holds the soundings for.
+    pub fn selected_station(&self) -> usize {
+        self.stations.borrow().selected
+    }
+
+    /// Swap in the soundings for another station, and display the one closest to the valid time
+    /// of the sounding displayed now.
+    pub fn select_station(acp: &AppContextPointer, index: usize) {
+        let new_index = {
+            let mut stations = acp.stations.borrow_mut();
+            if index == stations.selected || index >= stations.lists.len() {
+                return;
+            }
+
+            let valid_time = acp
+                .get_sounding_for_display()
+                .and_then(|anal| anal.borrow().sounding().valid_time());
+
+            let previous = stations.selected;
+            let list = std::mem::take(&mut stations.lists[index]);
+            stations.lists[previous] = acp.list.replace(list);
+            stations.selected = index;
+
+            valid_time
+                .and_then(|target| {
+                    acp.list.borrow().iter().position_min_by_key(|anal| {
+                        anal.borrow()
+                            .sounding()
+                            .valid_time()
+                            .map(|vt| (vt - target).num_seconds().abs())
+                            .unwrap_or(i64::MAX)
+                    })
+                })
+                .unwrap_or(0)
+        };
+
+        acp.editor.borrow_mut().reset();
+
+        acp.set_currently_displayed(new_index);
+        acp.mark_background_dirty();
+
+        // Anything not yet analyzed in the new list is filled in, and the analysis of the old
+        // list picks up where it left off when it is selected again.
+        Self::fill_in_analysis(acp);
+    }
+}
@@ -1,8 +1,8 @@
 //! Coordinate systems and geometry definitions. Some conversions are dependent on the application
 //! state, and so those functions are a part of the `AppContext`.
 
-use crate::app::config;
-use metfor::{Celsius, GigaWatts, HectoPascal, Knots, Meters, PaPS, WindSpdDir};
+use crate::app::config::ThermoDiagram;
+use metfor::{Celsius, GigaWatts, HectoPascal, Knots, Meters, PaPS, Quantity, WindSpdDir};
 
 /// Common operations on rectangles
 pub trait Rect {
@@ -314,31 +314,102 @@ impl Rect for DeviceRect {
     }
 }
 
+/***************************************************************************************************
+ *                   Skew-T domain
+ * ************************************************************************************************/
+/// The part of the atmosphere covered by the skew-t, and the pressure profiles that share its
+/// vertical coordinate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SkewTGeometry {
+    /// Which thermodynamic diagram is drawn.
+    pub diagram: ThermoDiagram,
+    /// Maximum pressure plotted, on the bottom edge.
+    pub max_p: HectoPascal,
+    /// Minimum pressure plotted, on the top edge.
+    pub min_p: HectoPascal,
+    /// Coldest temperature plotted at the maximum pressure, on the bottom edge.
+    pub min_t: Celsius,
+    /// Warmest temperature plotted at the maximum pressure, on the bottom edge.
+    pub max_t: Celsius,
+    /// How far the isotherms lean to the right, 1.0 puts them at 45 degrees when the plot is
+    /// square.
+    pub skew: f64,
+}
+
+impl Default for SkewTGeometry {
+    fn default() -> Self {
+        SkewTGeometry {
+            diagram: ThermoDiagram::SkewT,
+            max_p: HectoPascal(1050.0),
+            min_p: HectoPascal(99.0),
+            min_t: Celsius(-40.5),
+            max_t: Celsius(55.5),
+            skew: 1.0,
+        }
+    }
+}
+
 /***************************************************************************************************
  *                   Converting Pressure to the y coordinate
  * ************************************************************************************************/
+/// Poisson constant for dry air, R/cp, the exponent for the Stuve diagram's pressure axis.
+pub const KAPPA: f64 = 0.2857;
+
 /// Given a pressure value, convert it to a y-value from X-Y coordinates.
 ///
 /// Overwhelmingly the veritical coordinate system is based on pressure, so this is a very common
-/// operation to do, and you want it to always be done them same.
-pub fn convert_pressure_to_y(pressure: HectoPascal) -> f64 {
-    (config::MAXP / pressure).log10() / (config::MAXP / config::MINP).log10()
+/// operation to do, and you want it to always be done them same. The y-value follows the log of
+/// the pressure, except on a Stuve diagram where it follows the pressure to the R/cp power. Isobars
+/// on a tephigram bend a little, so this is where they cross the 0C isotherm.
+pub fn convert_pressure_to_y(pressure: HectoPascal, geometry: SkewTGeometry) -> f64 {
+    let SkewTGeometry {
+        diagram,
+        max_p,
+        min_p,
+        ..
+    } = geometry;
+
+    match diagram {
+        ThermoDiagram::Stuve => {
+            let (bottom, top) = (max_p.unpack().powf(KAPPA), min_p.unpack().powf(KAPPA));
+            (bottom - pressure.unpack().powf(KAPPA)) / (bottom - top)
+        }
+        _ => (max_p / pressure).log10() / (max_p / min_p).log10(),
+    }
 }
 
 /// Provide an inverse function as well.
-pub fn convert_y_to_pressure(y: f64) -> HectoPascal {
-    config::MAXP * (config::MINP / config::MAXP).powf(y)
+pub fn convert_y_to_pressure(y: f64, geometry: SkewTGeometry) -> HectoPascal {
+    let SkewTGeometry {
+        diagram,
+        max_p,
+        min_p,
+        ..
+    } = geometry;
+
+    match diagram {
+        ThermoDiagram::Stuve => {
+            let (bottom, top) = (max_p.unpack().powf(KAPPA), min_p.unpack().powf(KAPPA));
+            HectoPascal((bottom - y * (bottom - top)).powf(1.0 / KAPPA))
+        }
+        _ => max_p * (min_p / max_p).powf(y),
+    }
 }
 
 #[test]
 fn test_pressure_to_y_and_back() {
-    use metfor::Quantity;
-
-    for i in 100..=1000 {
-        let p = HectoPascal(i as f64);
-        let y = convert_pressure_to_y(p);
-        let pback = convert_y_to_pressure(y);
-        println!("p = {:?} y = {:?}  pback = {:?}", p, y, pback);
-        assert!((p - pback).abs() < HectoPascal(1.0e-6));
+    for diagram in [ThermoDiagram::SkewT, ThermoDiagram::Stuve] {
+        let geometry = SkewTGeometry {
+            diagram,
+            ..SkewTGeometry::default()
+        };
+
+        for i in 100..=1000 {
+            let p = HectoPascal(i as f64);
+            let y = convert_pressure_to_y(p, geometry);
+            let pback = convert_y_to_pressure(y, geometry);
+            println!("p = {:?} y = {:?}  pback = {:?}", p, y, pback);
+            assert!((p - pback).abs() < HectoPascal(1.0e-6));
+        }
     }
 }
@@ -7,6 +7,7 @@ pub enum SondeError {
     CairoError(gtk::cairo::Error),
     GLibBoolError(gtk::glib::error::BoolError),
     NoMatchingFileType,
+    InvalidSkewTGeometry,
 }
 
 impl Display for SondeError {
@@ -17,6 +18,10 @@ impl Display for SondeError {
             CairoError(err) => write!(f, "Error with cairo = {:?}.", err),
             GLibBoolError(err) => write!(f, "Error with glib = {:?}.", err),
             NoMatchingFileType => write!(f, "Unable to find a way to load this file."),
+            InvalidSkewTGeometry => write!(
+                f,
+                "The skew-t range is empty, upside down, not finite, or has a pressure <= 0."
+            ),
         }
     }
 }
@@ -1,7 +1,11 @@
 //! Module for the GUI components of the application.
 
 use crate::{
-    app::{config::Rgba, sample::Sample, AppContext, AppContextPointer},
+    app::{
+        config::{KeyCommand, ParcelType, Rgba},
+        sample::Sample,
+        AppContext, AppContextPointer,
+    },
     coords::{
         convert_pressure_to_y, DeviceCoords, DeviceRect, Rect, ScreenCoords, ScreenRect, XYCoords,
     },
@@ -9,6 +13,7 @@ use crate::{
 };
 use gtk::{
     cairo::{Context, FontExtents, FontFace, FontSlant, FontWeight, Matrix, Operator},
+    gio::{SimpleAction, SimpleActionGroup},
     glib::Propagation,
     prelude::*,
     DrawingArea, EventControllerMotion,
@@ -18,24 +23,34 @@ use sounding_analysis::{
     self, freezing_levels, warm_temperature_layer_aloft, warm_wet_bulb_layer_aloft,
     wet_bulb_zero_levels, DataRow, Layer,
 };
+use std::rc::Rc;
 
 mod control_area;
+mod ensemble;
 mod fire_plume;
 mod hodograph;
 mod indexes_area;
 mod main_window;
+mod metadata;
+mod meteogram;
+mod pinned;
 mod plot_context;
 pub mod profiles;
 mod provider_data;
 mod sounding;
 mod text_area;
+mod time_height;
 mod utility;
 
+pub use self::control_area::{update_control_area_units, update_custom_parcel_list};
 pub use self::fire_plume::{FirePlumeContext, FirePlumeEnergyContext};
 pub use self::hodograph::HodoContext;
+pub use self::main_window::update_station_selector;
+pub use self::meteogram::MeteogramContext;
 pub use self::plot_context::{PlotContext, PlotContextExt};
 pub use self::sounding::SkewTContext;
 pub use self::text_area::update_text_highlight;
+pub use self::time_height::TimeHeightContext;
 
 use self::utility::{plot_curve_from_points, DrawingArgs};
 
@@ -47,22 +62,29 @@ pub fn initialize(app: &AppContextPointer) -> Result<(), SondeError> {
     hodograph::HodoContext::set_up_drawing_area(app)?;
     fire_plume::FirePlumeContext::set_up_drawing_area(app)?;
     fire_plume::FirePlumeEnergyContext::set_up_drawing_area(app)?;
+    time_height::TimeHeightContext::set_up_drawing_area(app)?;
+    meteogram::MeteogramContext::set_up_drawing_area(app)?;
     control_area::set_up_control_area(app)?;
     text_area::set_up_text_area(app)?;
     profiles::initialize_profiles(app)?;
     indexes_area::set_up_indexes_area(app)?;
     provider_data::set_up_provider_text_area(app)?;
+    ensemble::set_up_ensemble_text_area(app)?;
+    pinned::set_up_pinned_text_area(app)?;
+    metadata::set_up_metadata_text_area(app)?;
     main_window::set_up_main_window(app)?;
 
     Ok(())
 }
 
 pub fn draw_all(app: &AppContext) {
-    const DRAWING_AREAS: [&str; 4] = [
+    const DRAWING_AREAS: [&str; 6] = [
         "skew_t",
         "hodograph_area",
         "fire_plume_height_area",
         "fire_plume_energy_area",
+        "time_height_area",
+        "meteogram_area",
     ];
 
     for &da in &DRAWING_AREAS {
@@ -75,10 +97,119 @@ pub fn draw_all(app: &AppContext) {
 }
 
 pub fn update_text_views(app: &AppContext) {
+    self::text_area::update_text_header(app);
     self::text_area::update_text_area(app);
     self::text_area::update_text_highlight(app);
     self::indexes_area::update_indexes_area(app);
     self::provider_data::update_text_area(app);
+    self::ensemble::update_text_area(app);
+    self::pinned::update_text_area(app);
+    self::metadata::update_text_area(app);
+    self::metadata::update_window_title(app);
+}
+
+/// Add the actions for editing the displayed sounding to a context menu action group.
+fn add_edit_actions(group: &SimpleActionGroup, acp: &AppContextPointer) {
+    let ac = Rc::clone(acp);
+    let action = SimpleAction::new("edit_sounding", None);
+    action.connect_activate(move |_action, _variant| ac.toggle_editing());
+    group.add_action(&action);
+
+    let ac = Rc::clone(acp);
+    let action = SimpleAction::new("undo_edit", None);
+    action.connect_activate(move |_action, _variant| ac.undo_edit());
+    group.add_action(&action);
+
+    let ac = Rc::clone(acp);
+    let action = SimpleAction::new("revert_edits", None);
+    action.connect_activate(move |_action, _variant| ac.revert_edits());
+    group.add_action(&action);
+
+    let ac = Rc::clone(acp);
+    let action = SimpleAction::new("save_edits", None);
+    action.connect_activate(move |_action, _variant| ac.save_edits_as_new());
+    group.add_action(&action);
+}
+
+/// Run the command the keymap binds to a key, if there is one.
+pub fn key_press(keyval: gtk::gdk::Key, ac: &AppContextPointer) -> Propagation {
+    use gtk::gdk::Key;
+
+    // The keypad keys do the same as the ones on the main keyboard.
+    let keyval = match keyval {
+        Key::KP_Right => Key::Right,
+        Key::KP_Left => Key::Left,
+        Key::KP_Up => Key::Up,
+        Key::KP_Down => Key::Down,
+        Key::KP_Home => Key::Home,
+        Key::KP_End => Key::End,
+        Key::KP_0 => Key::_0,
+        keyval => keyval,
+    };
+
+    let command = keyval
+        .name()
+        .and_then(|name| ac.config.borrow().keymap.command(&name));
+
+    let command = match command {
+        Some(command) => command,
+        None => return Propagation::Proceed,
+    };
+
+    match command {
+        KeyCommand::NextTime => ac.display_next(),
+        KeyCommand::PreviousTime => ac.display_previous(),
+        KeyCommand::FirstTime => ac.display_first(),
+        KeyCommand::LastTime => ac.display_last(),
+        KeyCommand::SampleUp => ac.step_sample_up(),
+        KeyCommand::SampleDown => ac.step_sample_down(),
+        KeyCommand::UnpinSample => ac.unpin_sample(),
+        KeyCommand::NextParcel => cycle_parcel_type(ac, true),
+        KeyCommand::PreviousParcel => cycle_parcel_type(ac, false),
+        KeyCommand::ToggleActiveReadout => activate_action(ac, "skew-t.show_active_readout", None),
+        KeyCommand::ToggleParcelProfile => activate_action(ac, "skew-t.show_parcel_profile", None),
+        KeyCommand::ToggleDownburst => activate_action(ac, "skew-t.show_downburst", None),
+        KeyCommand::ToggleInflowLayer => activate_action(ac, "skew-t.show_inflow_layer", None),
+        KeyCommand::ToggleWindProfile => activate_action(ac, "skew-t.show_wind_profile", None),
+        KeyCommand::ResetZoom => ac.reset_zoom(),
+    }
+
+    Propagation::Stop
+}
+
+/// Switch to the next or previous parcel type through the skew-t menu action, so the menu stays
+/// in sync.
+fn cycle_parcel_type(ac: &AppContext, forward: bool) {
+    const PARCELS: [&str; 5] = ["surface", "mixed", "unstable", "effective", "convective"];
+
+    let current = match ac.config.borrow().parcel_type {
+        ParcelType::Surface => 0,
+        ParcelType::MixedLayer => 1,
+        ParcelType::MostUnstable => 2,
+        ParcelType::Effective => 3,
+        ParcelType::Convective => 4,
+    };
+
+    let next = if forward {
+        (current + 1) % PARCELS.len()
+    } else {
+        (current + PARCELS.len() - 1) % PARCELS.len()
+    };
+
+    activate_action(
+        ac,
+        "skew-t.parcel_type_action",
+        Some(&PARCELS[next].to_variant()),
+    );
+}
+
+/// Activate an action in one of the groups on the main window.
+fn activate_action(ac: &AppContext, name: &str, parameter: Option<&gtk::glib::Variant>) {
+    if let Ok(window) = ac.fetch_widget::<gtk::Window>("main_window") {
+        if let Err(err) = window.activate_action(name, parameter) {
+            eprintln!("Unable to activate {}: {}", name, err);
+        }
+    }
 }
 
 trait Drawable: PlotContext + PlotContextExt {
@@ -520,7 +651,7 @@ trait Drawable: PlotContext + PlotContextExt {
         };
 
         if config.show_active_readout_line {
-            self.draw_sample_line(args, sample_p);
+            self.draw_sample_line(args, sample_p, config.active_readout_line_rgba);
         }
 
         if config.show_active_readout_text {
@@ -538,16 +669,28 @@ trait Drawable: PlotContext + PlotContextExt {
         }
     }
 
+    /// Override for plots that don't use pressure as a vertical coordinate, or to mark the pinned
+    /// levels some other way. Like the Hodograph.
+    fn draw_pinned_samples(&self, args: DrawingArgs<'_, '_>) {
+        if !self.has_data() {
+            return;
+        }
+
+        let rgba = args.ac.config.borrow().pinned_sample_rgba;
+        for pressure in args.ac.pinned_samples() {
+            self.draw_sample_line(args, pressure, rgba);
+        }
+    }
+
     /// Not recommended to override.
-    fn draw_sample_line(&self, args: DrawingArgs<'_, '_>, sample_p: HectoPascal) {
+    fn draw_sample_line(&self, args: DrawingArgs<'_, '_>, sample_p: HectoPascal, rgba: Rgba) {
         let (ac, cr) = (args.ac, args.cr);
         let config = ac.config.borrow();
 
         let bb = self.get_plot_area();
         let (left, right) = (bb.lower_left.x, bb.upper_right.x);
-        let y = convert_pressure_to_y(sample_p);
+        let y = convert_pressure_to_y(sample_p, ac.skew_t.geometry());
 
-        let rgba = config.active_readout_line_rgba;
         cr.set_source_rgba(rgba.0, rgba.1, rgba.2, rgba.3);
         cr.set_line_width(
             cr.device_to_user_distance(config.active_readout_line_width, 0.0)
@@ -568,8 +711,8 @@ trait Drawable: PlotContext + PlotContextExt {
         strings: &[(String, Rgba)],
         sample_p: HectoPascal,
     ) -> ScreenRect {
-        let cr = args.cr;
-        let config = args.ac.config.borrow();
+        let (ac, cr) = (args.ac, args.cr);
+        let config = ac.config.borrow();
 
         let mut width: f64 = 0.0;
         let mut height: f64 = 0.0;
@@ -604,7 +747,7 @@ trait Drawable: PlotContext + PlotContextExt {
             self.convert_device_to_screen(DeviceCoords { col: 5.0, row: 5.0 });
         let ScreenCoords { y: top, .. } = self.convert_xy_to_screen(XYCoords {
             x: 0.0,
-            y: convert_pressure_to_y(sample_p),
+            y: convert_pressure_to_y(sample_p, ac.skew_t.geometry()),
         });
         let mut bottom = top - height;
 
@@ -899,17 +1042,7 @@ trait Drawable: PlotContext + PlotContextExt {
     }
 
     fn key_press_event(keyval: gtk::gdk::Key, ac: &AppContextPointer) -> Propagation {
-        use gtk::gdk::Key;
-
-        if keyval == Key::KP_Right || keyval == Key::Right {
-            ac.display_next();
-            Propagation::Stop
-        } else if keyval == Key::KP_Left || keyval == Key::Left {
-            ac.display_previous();
-            Propagation::Stop
-        } else {
-            Propagation::Proceed
-        }
+        key_press(keyval, ac)
     }
 
     fn size_allocate_event(&self, da: &DrawingArea) {
@@ -1003,6 +1136,7 @@ trait Drawable: PlotContext + PlotContextExt {
 
             self.clip(&tmp_cr);
 
+            self.draw_pinned_samples(tmp_args);
             self.draw_active_readout(tmp_args);
 
             self.clear_overlay_dirty();
@@ -1175,7 +1309,8 @@ trait SlaveProfileDrawable: Drawable {
     }
 
     fn draw_layers(&self, args: DrawingArgs<'_, '_>, layers: &[Layer], color_rgba: Rgba) {
-        let cr = args.cr;
+        let (ac, cr) = (args.ac, args.cr);
+        let geometry = ac.skew_t.geometry();
 
         let bb = self.get_plot_area();
         let (left, right) = (bb.lower_left.x, bb.upper_right.x);
@@ -1204,7 +1339,7 @@ trait SlaveProfileDrawable: Drawable {
 
             // Convert points to screen coords
             for coord in &mut coords {
-                coord.1 = convert_pressure_to_y(HectoPascal(coord.1));
+                coord.1 = convert_pressure_to_y(HectoPascal(coord.1), geometry);
 
                 let screen_coords = self.convert_xy_to_screen(XYCoords {
                     x: coord.0,
@@ -1235,7 +1370,8 @@ trait SlaveProfileDrawable: Drawable {
         color_rgba: Rgba,
         line_width: f64,
     ) {
-        let cr = args.cr;
+        let (ac, cr) = (args.ac, args.cr);
+        let geometry = ac.skew_t.geometry();
 
         let bb = self.get_plot_area();
         let (left, right) = (bb.lower_left.x, bb.upper_right.x);
@@ -1253,7 +1389,7 @@ trait SlaveProfileDrawable: Drawable {
 
             // Convert points to screen coords
             for coord in &mut coords {
-                coord.1 = convert_pressure_to_y(HectoPascal(coord.1));
+                coord.1 = convert_pressure_to_y(HectoPascal(coord.1), geometry);
 
                 let screen_coords = self.convert_xy_to_screen(XYCoords {
                     x: coord.0,
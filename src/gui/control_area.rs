@@ -134,11 +134,70 @@ macro_rules! build_config_color {
     };
 }
 
+macro_rules! build_config_spin {
+    ($v_box:ident, $label:expr, $acp_in:expr, $var:ident, $unit:ident, $min:expr, $max:expr,
+     $step:expr, $digits:expr, $on_change:path) => {{
+        let hbox = gtk::Box::new(gtk::Orientation::Horizontal, BOX_SPACING);
+        let spin = gtk::SpinButton::with_range($min, $max, $step);
+        spin.set_digits($digits);
+        spin.set_halign(gtk::Align::End);
+        spin.set_hexpand(true);
+        spin.set_value($acp_in.config.borrow().$var.unpack());
+
+        // Create spin button callback
+        let acp = Rc::clone(&$acp_in);
+        spin.connect_value_changed(move |button| {
+            acp.config.borrow_mut().$var = $unit(button.value());
+            $on_change(&acp);
+        });
+
+        // Layout
+        hbox.append(&gtk::Label::new(Some($label)));
+        hbox.append(&spin);
+        $v_box.append(&hbox);
+
+        spin
+    }};
+    ($v_box:ident, $label:expr, $acp_in:expr, $var:ident, $min:expr, $max:expr, $step:expr,
+     $digits:expr, $on_change:path) => {{
+        let hbox = gtk::Box::new(gtk::Orientation::Horizontal, BOX_SPACING);
+        let spin = gtk::SpinButton::with_range($min, $max, $step);
+        spin.set_digits($digits);
+        spin.set_halign(gtk::Align::End);
+        spin.set_hexpand(true);
+        spin.set_value($acp_in.config.borrow().$var);
+
+        // Create spin button callback
+        let acp = Rc::clone(&$acp_in);
+        spin.connect_value_changed(move |button| {
+            acp.config.borrow_mut().$var = button.value();
+            $on_change(&acp);
+        });
+
+        // Layout
+        hbox.append(&gtk::Label::new(Some($label)));
+        hbox.append(&spin);
+        $v_box.append(&hbox);
+
+        spin
+    }};
+}
+
 mod active_readout;
+mod analysis_options;
 mod background_options;
+mod custom_parcels;
 mod data_options;
 mod overlay_options;
 
+pub use custom_parcels::update_custom_parcel_list;
+
+/// Rebuild the inputs that show values in the display units after the units change.
+pub fn update_control_area_units(acp: &AppContextPointer) {
+    background_options::update_geometry_units(acp);
+    custom_parcels::update_custom_parcel_units(acp);
+}
+
 const BOX_SPACING: i32 = 5;
 
 pub fn set_up_control_area(acp: &AppContextPointer) -> Result<(), SondeError> {
@@ -163,5 +222,9 @@ pub fn set_up_control_area(acp: &AppContextPointer) -> Result<(), SondeError> {
     control_area.append_page(&overlay_options, None::<&gtk::Widget>);
     control_area.set_tab_label_text(&overlay_options, "Overlays");
 
+    let analysis_options = analysis_options::make_analysis_frame(acp);
+    control_area.append_page(&analysis_options, None::<&gtk::Widget>);
+    control_area.set_tab_label_text(&analysis_options, "Analysis");
+
     Ok(())
 }
@@ -17,6 +17,8 @@ pub fn make_active_readout_frame(ac: &AppContextPointer) -> ScrolledWindow {
 
     // Active readout
     build_config_color!(sample_box, "Sampling marker", ac, active_readout_line_rgba);
+    build_config_color!(sample_box, "Pinned sample marker", ac, pinned_sample_rgba);
+    build_config_color!(sample_box, "Measured layer", ac, layer_measure_rgba);
     build_config_color!(
         sample_box,
         "Sample profile",
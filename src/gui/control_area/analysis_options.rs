@@ -0,0 +1,114 @@
+use crate::{
+    app::{AppContext, AppContextPointer},
+    gui::control_area::BOX_SPACING,
+};
+use gtk::{self, prelude::*, Frame, ScrolledWindow};
+use metfor::{HectoPascal, Meters, Quantity};
+use std::rc::Rc;
+
+pub fn make_analysis_frame(ac: &AppContextPointer) -> ScrolledWindow {
+    let f = Frame::new(None);
+    f.set_hexpand(true);
+    f.set_vexpand(true);
+
+    // Layout vertically
+    let v_box = gtk::Box::new(gtk::Orientation::Vertical, BOX_SPACING);
+    v_box.set_baseline_position(gtk::BaselinePosition::Top);
+
+    let parcel_frame = gtk::Frame::new(Some("Parcels"));
+    let parcel_box = gtk::Box::new(gtk::Orientation::Vertical, BOX_SPACING);
+    parcel_frame.set_child(Some(&parcel_box));
+
+    build_config_spin!(
+        parcel_box,
+        "Mixed layer depth (hPa)",
+        ac,
+        mixed_layer_depth,
+        HectoPascal,
+        10.0,
+        300.0,
+        10.0,
+        0,
+        AppContext::update_analysis_settings
+    );
+
+    let custom_parcel_frame = super::custom_parcels::make_custom_parcel_frame(ac);
+
+    let kinematic_frame = gtk::Frame::new(Some("Kinematics"));
+    let kinematic_box = gtk::Box::new(gtk::Orientation::Vertical, BOX_SPACING);
+    kinematic_frame.set_child(Some(&kinematic_box));
+
+    build_config_spin!(
+        kinematic_box,
+        "Mean wind layer depth (m AGL)",
+        ac,
+        mean_wind_depth,
+        Meters,
+        500.0,
+        12000.0,
+        500.0,
+        0,
+        AppContext::update_analysis_settings
+    );
+    build_config_spin!(
+        kinematic_box,
+        "Storm relative helicity depth (m AGL)",
+        ac,
+        srh_depth,
+        Meters,
+        500.0,
+        6000.0,
+        250.0,
+        0,
+        AppContext::update_analysis_settings
+    );
+
+    let fire_frame = gtk::Frame::new(Some("Fire"));
+    let fire_box = gtk::Box::new(gtk::Orientation::Vertical, BOX_SPACING);
+    fire_frame.set_child(Some(&fire_box));
+
+    build_config_spin!(
+        fire_box,
+        "PFT moisture ratio",
+        ac,
+        pft_moisture_ratio,
+        1.0,
+        30.0,
+        0.5,
+        1,
+        AppContext::update_analysis_settings
+    );
+    build_config_spin!(
+        fire_box,
+        "Plume moisture ratio (low)",
+        ac,
+        briggs_low_moisture_ratio,
+        1.0,
+        30.0,
+        0.5,
+        1,
+        AppContext::update_analysis_settings
+    );
+    build_config_spin!(
+        fire_box,
+        "Plume moisture ratio (high)",
+        ac,
+        briggs_high_moisture_ratio,
+        1.0,
+        30.0,
+        0.5,
+        1,
+        AppContext::update_analysis_settings
+    );
+
+    // Layout boxes in the frame
+    f.set_child(Some(&v_box));
+    v_box.append(&parcel_frame);
+    v_box.append(&custom_parcel_frame);
+    v_box.append(&kinematic_frame);
+    v_box.append(&fire_frame);
+    let sw = ScrolledWindow::new();
+    sw.set_child(Some(&f));
+
+    sw
+}
@@ -1,7 +1,13 @@
-use crate::{app::AppContextPointer, gui::control_area::BOX_SPACING};
-use gtk::{self, gdk::RGBA, prelude::*, ColorButton, Frame, ScrolledWindow};
+use crate::{
+    app::{config::Config, AppContext, AppContextPointer},
+    gui::control_area::BOX_SPACING,
+};
+use gtk::{self, gdk::RGBA, prelude::*, ColorButton, Frame, ScrolledWindow, SpinButton};
+use metfor::{Celsius, HectoPascal, Quantity};
 use std::rc::Rc;
 
+const GEOMETRY_ID: &str = "skew_t_geometry_box";
+
 pub fn make_background_frame(acp: &AppContextPointer) -> ScrolledWindow {
     let f = Frame::new(None);
     f.set_hexpand(true);
@@ -20,11 +26,15 @@ pub fn make_background_frame(acp: &AppContextPointer) -> ScrolledWindow {
     // Third set is for font
     let font_frame = build_font_frame(acp);
 
+    // Fourth set is the skew-T geometry
+    let geometry_frame = build_geometry_frame(acp);
+
     // Layout boxes in the frame
     f.set_child(Some(&v_box));
     v_box.append(&lines_frame);
     v_box.append(&fills_frame);
     v_box.append(&font_frame);
+    v_box.append(&geometry_frame);
     let sw = ScrolledWindow::new();
     sw.set_child(Some(&f));
 
@@ -167,3 +177,144 @@ fn build_font_frame(acp: &AppContextPointer) -> gtk::Frame {
 
     font_frame
 }
+
+fn build_geometry_frame(acp: &AppContextPointer) -> gtk::Frame {
+    let geometry_frame = gtk::Frame::new(Some("Skew-T"));
+    let geometry_box = gtk::Box::new(gtk::Orientation::Vertical, BOX_SPACING);
+    geometry_frame.set_child(Some(&geometry_box));
+
+    // Rebuilt when the units change.
+    acp.expose_widget(GEOMETRY_ID, &geometry_box);
+    fill_geometry_box(acp, &geometry_box);
+
+    geometry_frame
+}
+
+/// Rebuild the skew-t geometry inputs in the current temperature units.
+pub fn update_geometry_units(acp: &AppContextPointer) {
+    let geometry_box: gtk::Box = match acp.fetch_widget(GEOMETRY_ID) {
+        Ok(geometry_box) => geometry_box,
+        Err(_) => return,
+    };
+
+    while let Some(child) = geometry_box.first_child() {
+        geometry_box.remove(&child);
+    }
+
+    fill_geometry_box(acp, &geometry_box);
+}
+
+fn fill_geometry_box(acp: &AppContextPointer, geometry_box: &gtk::Box) {
+    let max_p = build_config_spin!(
+        geometry_box,
+        "Bottom pressure (hPa)",
+        acp,
+        skew_t_max_p,
+        HectoPascal,
+        500.0,
+        1100.0,
+        10.0,
+        0,
+        AppContext::update_skew_t_geometry
+    );
+    let min_p = build_config_spin!(
+        geometry_box,
+        "Top pressure (hPa)",
+        acp,
+        skew_t_min_p,
+        HectoPascal,
+        10.0,
+        500.0,
+        10.0,
+        0,
+        AppContext::update_skew_t_geometry
+    );
+    keep_spins_ordered(&min_p, &max_p);
+
+    let min_t = build_temperature_spin(
+        geometry_box,
+        "Left temperature",
+        acp,
+        (Celsius(-100.0), Celsius(20.0)),
+        |config| config.skew_t_min_t,
+        |config, t| config.skew_t_min_t = t,
+    );
+    let max_t = build_temperature_spin(
+        geometry_box,
+        "Right temperature",
+        acp,
+        (Celsius(0.0), Celsius(80.0)),
+        |config| config.skew_t_max_t,
+        |config, t| config.skew_t_max_t = t,
+    );
+    keep_spins_ordered(&min_t, &max_t);
+
+    build_config_spin!(
+        geometry_box,
+        "Skew",
+        acp,
+        skew_t_skew,
+        0.0,
+        2.0,
+        0.1,
+        1,
+        AppContext::update_skew_t_geometry
+    );
+}
+
+/// Like `build_config_spin!`, but the temperature is shown and entered in the display units.
+fn build_temperature_spin(
+    v_box: &gtk::Box,
+    label: &str,
+    acp: &AppContextPointer,
+    (min, max): (Celsius, Celsius),
+    get: fn(&Config) -> Celsius,
+    set: fn(&mut Config, Celsius),
+) -> SpinButton {
+    let t_units = acp.config.borrow().temperature_units;
+
+    let hbox = gtk::Box::new(gtk::Orientation::Horizontal, BOX_SPACING);
+    let spin = SpinButton::with_range(t_units.value(min), t_units.value(max), 1.0);
+    spin.set_digits(1);
+    spin.set_halign(gtk::Align::End);
+    spin.set_hexpand(true);
+    spin.set_value(t_units.value(get(&acp.config.borrow())));
+
+    let ac = Rc::clone(acp);
+    spin.connect_value_changed(move |button| {
+        let t = t_units.to_celsius(button.value());
+        set(&mut ac.config.borrow_mut(), t);
+        ac.update_skew_t_geometry();
+    });
+
+    let label = format!("{} ({})", label, t_units.label());
+    hbox.append(&gtk::Label::new(Some(&label)));
+    hbox.append(&spin);
+    v_box.append(&hbox);
+
+    spin
+}
+
+/// Limit the ranges of a pair of spin buttons so the value of `low` always stays at least one step
+/// below the value of `high`, and the range between them can't be empty or upside down.
+fn keep_spins_ordered(low: &gtk::SpinButton, high: &gtk::SpinButton) {
+    let (low_min, low_max) = low.range();
+    let (high_min, high_max) = high.range();
+    let (step, _) = low.increments();
+
+    let limit_low = {
+        let low = low.clone();
+        move |high: &gtk::SpinButton| low.set_range(low_min, low_max.min(high.value() - step))
+    };
+    let limit_high = {
+        let high = high.clone();
+        move |low: &gtk::SpinButton| high.set_range(high_min.max(low.value() + step), high_max)
+    };
+
+    // Fix up values that were already out of order before watching for changes.
+    limit_low(high);
+    limit_high(low);
+
+    high.connect_value_changed(limit_low);
+    low.connect_value_changed(limit_high);
+}
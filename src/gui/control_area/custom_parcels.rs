@@ -0,0 +1,330 @@
+use crate::{
+    app::{
+        config::{Config, CustomParcelSource},
+        AppContext, AppContextPointer,
+    },
+    gui::control_area::BOX_SPACING,
+};
+use gtk::{self, gdk::RGBA, prelude::*, Frame, SpinButton};
+use metfor::{Celsius, HectoPascal, Meters};
+use std::rc::Rc;
+
+const LIST_ID: &str = "custom_parcel_list";
+const INPUTS_ID: &str = "custom_parcel_inputs";
+
+// Positions of the kinds of parcels in the drop down.
+const PRESSURE: u32 = 0;
+const HEIGHT: u32 = 1;
+const MIXED_LAYER: u32 = 2;
+
+pub fn make_custom_parcel_frame(ac: &AppContextPointer) -> Frame {
+    let frame = Frame::new(Some("Custom Parcels"));
+    let v_box = gtk::Box::new(gtk::Orientation::Vertical, BOX_SPACING);
+    frame.set_child(Some(&v_box));
+
+    // The parcels already defined, rebuilt whenever they change.
+    let list = gtk::Box::new(gtk::Orientation::Vertical, BOX_SPACING);
+    v_box.append(&list);
+    ac.expose_widget(LIST_ID, &list);
+    update_custom_parcel_list(ac);
+
+    // Inputs for a new parcel, rebuilt when the units change.
+    v_box.append(&gtk::Separator::new(gtk::Orientation::Horizontal));
+
+    let inputs = gtk::Box::new(gtk::Orientation::Vertical, BOX_SPACING);
+    v_box.append(&inputs);
+    ac.expose_widget(INPUTS_ID, &inputs);
+    fill_custom_parcel_inputs(ac, &inputs);
+
+    frame
+}
+
+/// Rebuild the inputs for a new parcel and the list of parcels in the current units.
+pub fn update_custom_parcel_units(acp: &AppContextPointer) {
+    if let Ok(inputs) = acp.fetch_widget::<gtk::Box>(INPUTS_ID) {
+        while let Some(child) = inputs.first_child() {
+            inputs.remove(&child);
+        }
+
+        fill_custom_parcel_inputs(acp, &inputs);
+    }
+
+    update_custom_parcel_list(acp);
+}
+
+fn fill_custom_parcel_inputs(ac: &AppContextPointer, v_box: &gtk::Box) {
+    let (p_units, h_units, t_units) = {
+        let config = ac.config.borrow();
+        (
+            config.pressure_units,
+            config.height_units,
+            config.temperature_units,
+        )
+    };
+
+    let hbox = gtk::Box::new(gtk::Orientation::Horizontal, BOX_SPACING);
+    let name = gtk::Entry::new();
+    name.set_placeholder_text(Some("Name"));
+    name.set_hexpand(true);
+    let kind = gtk::DropDown::from_strings(&["Pressure level", "Height AGL", "Mixed layer"]);
+    hbox.append(&name);
+    hbox.append(&kind);
+    v_box.append(&hbox);
+
+    let p = |hpa: f64| p_units.value(HectoPascal(hpa));
+    let h = |m: f64| h_units.value(Meters(m));
+    let t = |c: f64| t_units.value(Celsius(c));
+    let p_digits = p_units.precision() as u32;
+
+    let label = format!("Pressure ({})", p_units.label());
+    let pressure = make_spin(
+        v_box,
+        &label,
+        p(100.0),
+        p(1100.0),
+        p(10.0),
+        p_digits,
+        p(850.0),
+    );
+    let label = format!("Height ({} AGL)", h_units.label());
+    let height = make_spin(v_box, &label, h(0.0), h(10000.0), h(100.0), 0, h(500.0));
+    let label = format!("Mixed layer depth ({})", p_units.label());
+    let depth = make_spin(
+        v_box,
+        &label,
+        p(10.0),
+        p(300.0),
+        p(10.0),
+        p_digits,
+        p(100.0),
+    );
+    let label = format!("Temperature ({})", t_units.label());
+    let temperature = make_spin(v_box, &label, t(-60.0), t(50.0), 0.5, 1, t(20.0));
+    let label = format!("Dew point ({})", t_units.label());
+    let dew_point = make_spin(v_box, &label, t(-60.0), t(50.0), 0.5, 1, t(10.0));
+
+    // Only the inputs used by the selected kind of parcel can be changed.
+    let update_sensitivity = {
+        let (pressure, height, depth) = (pressure.clone(), height.clone(), depth.clone());
+        let (temperature, dew_point) = (temperature.clone(), dew_point.clone());
+
+        move |selected: u32| {
+            pressure.set_sensitive(selected == PRESSURE);
+            height.set_sensitive(selected == HEIGHT);
+            depth.set_sensitive(selected == MIXED_LAYER);
+            temperature.set_sensitive(selected != MIXED_LAYER);
+            dew_point.set_sensitive(selected != MIXED_LAYER);
+        }
+    };
+    update_sensitivity(kind.selected());
+    kind.connect_selected_notify(move |kind| update_sensitivity(kind.selected()));
+
+    let add = gtk::Button::with_label("Add parcel");
+    add.set_halign(gtk::Align::End);
+    let acp = Rc::clone(ac);
+    add.connect_clicked(move |_button| {
+        let t = t_units.to_celsius(temperature.value());
+        // A dew point above the temperature isn't physical, cap it at saturation.
+        let dp = t_units.to_celsius(dew_point.value());
+        let dp = if dp > t { t } else { dp };
+
+        let source = match kind.selected() {
+            HEIGHT => CustomParcelSource::Height {
+                height: h_units.to_meters(height.value()),
+                temperature: t,
+                dew_point: dp,
+            },
+            MIXED_LAYER => CustomParcelSource::MixedLayer {
+                depth: p_units.to_hpa(depth.value()),
+            },
+            _ => CustomParcelSource::Pressure {
+                pressure: p_units.to_hpa(pressure.value()),
+                temperature: t,
+                dew_point: dp,
+            },
+        };
+
+        let parcel_name = match name.text().trim() {
+            "" => default_name(source, &acp.config.borrow()),
+            text => text.to_owned(),
+        };
+        name.set_text("");
+
+        AppContext::add_custom_parcel(&acp, parcel_name, source);
+    });
+    v_box.append(&add);
+}
+
+/// Rebuild the list of custom parcels after they were added, removed, or loaded.
+pub fn update_custom_parcel_list(acp: &AppContextPointer) {
+    let list: gtk::Box = match acp.fetch_widget(LIST_ID) {
+        Ok(list) => list,
+        Err(_) => return,
+    };
+
+    while let Some(child) = list.first_child() {
+        list.remove(&child);
+    }
+
+    let config = acp.config.borrow();
+    let parcels = config.custom_parcels.clone();
+    if parcels.is_empty() {
+        list.append(&gtk::Label::new(Some(
+            "No custom parcels, add one below or from the skew-t menu",
+        )));
+        return;
+    }
+
+    for (i, parcel) in parcels.iter().enumerate() {
+        let hbox = gtk::Box::new(gtk::Orientation::Horizontal, BOX_SPACING);
+
+        let check = gtk::CheckButton::with_label(&parcel.name);
+        check.set_active(parcel.show);
+        check.set_hexpand(true);
+        check.set_tooltip_text(Some(&describe(parcel.source, &config)));
+
+        let color = gtk::ColorButton::new();
+        color.set_use_alpha(true);
+        let rgba = parcel.rgba;
+        color.set_rgba(&RGBA::new(
+            rgba.0 as f32,
+            rgba.1 as f32,
+            rgba.2 as f32,
+            rgba.3 as f32,
+        ));
+
+        let remove = gtk::Button::from_icon_name("list-remove-symbolic");
+        remove.set_tooltip_text(Some("Remove parcel"));
+
+        let ac = Rc::clone(acp);
+        check.connect_toggled(move |button| {
+            if let Some(pcl) = ac.config.borrow_mut().custom_parcels.get_mut(i) {
+                pcl.show = button.is_active();
+            }
+            ac.mark_data_dirty();
+            crate::gui::draw_all(&ac);
+        });
+
+        let ac = Rc::clone(acp);
+        color.connect_color_set(move |button| {
+            let rgba = button.rgba();
+
+            if let Some(pcl) = ac.config.borrow_mut().custom_parcels.get_mut(i) {
+                pcl.rgba = (
+                    rgba.red() as f64,
+                    rgba.green() as f64,
+                    rgba.blue() as f64,
+                    rgba.alpha() as f64,
+                );
+            }
+            ac.mark_data_dirty();
+            crate::gui::draw_all(&ac);
+            crate::gui::indexes_area::update_indexes_area(&ac);
+        });
+
+        let ac = Rc::clone(acp);
+        remove.connect_clicked(move |_button| AppContext::remove_custom_parcel(&ac, i));
+
+        hbox.append(&check);
+        hbox.append(&color);
+        hbox.append(&remove);
+        list.append(&hbox);
+    }
+}
+
+fn make_spin(
+    v_box: &gtk::Box,
+    label: &str,
+    min: f64,
+    max: f64,
+    step: f64,
+    digits: u32,
+    value: f64,
+) -> SpinButton {
+    let hbox = gtk::Box::new(gtk::Orientation::Horizontal, BOX_SPACING);
+    let spin = SpinButton::with_range(min, max, step);
+    spin.set_digits(digits);
+    spin.set_halign(gtk::Align::End);
+    spin.set_hexpand(true);
+    spin.set_value(value);
+
+    hbox.append(&gtk::Label::new(Some(label)));
+    hbox.append(&spin);
+    v_box.append(&hbox);
+
+    spin
+}
+
+/// Short name for a parcel the user didn't name.
+fn default_name(source: CustomParcelSource, config: &Config) -> String {
+    let (p_units, h_units) = (config.pressure_units, config.height_units);
+    let pressure = |p| {
+        format!(
+            "{:.*} {}",
+            p_units.precision(),
+            p_units.value(p),
+            p_units.label()
+        )
+    };
+
+    match source {
+        CustomParcelSource::Pressure { pressure: p, .. }
+        | CustomParcelSource::Level { pressure: p } => pressure(p),
+        CustomParcelSource::Height { height, .. } => {
+            format!("{:.0} {} AGL", h_units.value(height), h_units.label())
+        }
+        CustomParcelSource::MixedLayer { depth } => format!("{} ML", pressure(depth)),
+    }
+}
+
+/// Where a parcel starts, for the tooltip in the list.
+fn describe(source: CustomParcelSource, config: &Config) -> String {
+    let (p_units, h_units, t_units) = (
+        config.pressure_units,
+        config.height_units,
+        config.temperature_units,
+    );
+    let pressure = |p| {
+        format!(
+            "{:.*} {}",
+            p_units.precision(),
+            p_units.value(p),
+            p_units.label()
+        )
+    };
+    let temperatures = |t, dp| {
+        format!(
+            "T {:.1}{} and Td {:.1}{}",
+            t_units.value(t),
+            t_units.label(),
+            t_units.value(dp),
+            t_units.label()
+        )
+    };
+
+    match source {
+        CustomParcelSource::Pressure {
+            pressure: p,
+            temperature,
+            dew_point,
+        } => format!(
+            "From {} with {}",
+            pressure(p),
+            temperatures(temperature, dew_point)
+        ),
+        CustomParcelSource::Height {
+            height,
+            temperature,
+            dew_point,
+        } => format!(
+            "From {:.0} {} AGL with {}",
+            h_units.value(height),
+            h_units.label(),
+            temperatures(temperature, dew_point)
+        ),
+        CustomParcelSource::MixedLayer { depth } => {
+            format!("Mixed layer {} deep", pressure(depth))
+        }
+        CustomParcelSource::Level { pressure: p } => format!("Sounding values at {}", pressure(p)),
+    }
+}
@@ -1,5 +1,9 @@
-use crate::{app::AppContextPointer, gui::control_area::BOX_SPACING};
+use crate::{
+    app::{AppContext, AppContextPointer},
+    gui::control_area::BOX_SPACING,
+};
 use gtk::{self, gdk::RGBA, prelude::*, Frame, ScrolledWindow};
+use metfor::{HectoPascal, Meters, Quantity};
 use std::rc::Rc;
 
 pub fn make_data_option_frame(ac: &AppContextPointer) -> ScrolledWindow {
@@ -20,6 +24,30 @@ pub fn make_data_option_frame(ac: &AppContextPointer) -> ScrolledWindow {
     build_config_color!(skewt_box, "Wet Bulb", ac, wet_bulb_rgba);
     build_config_color!(skewt_box, "Dew Point", ac, dew_point_rgba);
     build_config_color!(skewt_box, "Wind", ac, wind_rgba);
+    build_config_spin!(
+        skewt_box,
+        "Wind barb pressure interval (hPa)",
+        ac,
+        wind_barb_pressure_interval,
+        HectoPascal,
+        5.0,
+        200.0,
+        5.0,
+        0,
+        redraw_data
+    );
+    build_config_spin!(
+        skewt_box,
+        "Wind barb height interval (m AGL)",
+        ac,
+        wind_barb_height_interval,
+        Meters,
+        100.0,
+        5000.0,
+        100.0,
+        0,
+        redraw_data
+    );
 
     /* NOTE: wind line color in the hodograph is the same as the wind color for the skew-t set above.
     let hodo_frame = gtk::Frame::new(Some("Hodograph"));
@@ -54,3 +82,8 @@ pub fn make_data_option_frame(ac: &AppContextPointer) -> ScrolledWindow {
 
     sw
 }
+
+fn redraw_data(ac: &AppContext) {
+    ac.mark_data_dirty();
+    crate::gui::draw_all(ac);
+}
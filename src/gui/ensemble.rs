@@ -0,0 +1,145 @@
+use crate::{
+    analysis::{ensemble, Analysis},
+    app::{config::MeteogramIndex, AppContext, AppContextPointer},
+    errors::SondeError,
+};
+use gtk::{glib::translate::IntoGlib, prelude::*, EventControllerKey, TextTag, TextView};
+use sounding_analysis::PrecipType;
+use std::{fmt::Write, rc::Rc};
+
+const TEXT_AREA_ID: &str = "ensemble_text";
+
+/// Percentiles shown in the index distribution table.
+const PERCENTILES: [(&str, f64); 5] = [
+    ("Min", 0.0),
+    ("10%", 10.0),
+    ("50%", 50.0),
+    ("90%", 90.0),
+    ("Max", 100.0),
+];
+
+macro_rules! make_default_tag {
+    ($tb:ident, $acp:ident) => {
+        let tag_table = $tb.tag_table();
+        let config = $acp.config.borrow();
+        let font = &config.font_name;
+        let font_size = config.text_area_font_size_points;
+
+        let tag = TextTag::builder()
+            .name("default")
+            .family(font)
+            .size_points(font_size)
+            .weight(gtk::pango::Weight::Bold.into_glib())
+            .build();
+
+        let success = tag_table.add(&tag);
+        debug_assert!(success, "Failed to add tag to text tag table");
+    };
+}
+
+macro_rules! set_text {
+    ($tb:ident, $txt:expr) => {
+        $tb.set_text($txt);
+        let start = $tb.start_iter();
+        let end = $tb.end_iter();
+        $tb.apply_tag_by_name("default", &start, &end);
+    };
+}
+
+pub fn set_up_ensemble_text_area(acp: &AppContextPointer) -> Result<(), SondeError> {
+    let text_area: TextView = acp.fetch_widget(TEXT_AREA_ID)?;
+
+    let key_press = EventControllerKey::new();
+    let ac = Rc::clone(acp);
+    key_press.connect_key_pressed(move |_key_press, key, _code, _key_modifier| {
+        crate::gui::key_press(key, &ac)
+    });
+    text_area.add_controller(key_press);
+
+    let tb = text_area.buffer();
+    make_default_tag!(tb, acp);
+    set_text!(tb, "No ensemble members loaded");
+
+    Ok(())
+}
+
+pub fn update_text_area(ac: &AppContext) {
+    let text_area: TextView = if let Ok(ta) = ac.fetch_widget(TEXT_AREA_ID) {
+        ta
+    } else {
+        return;
+    };
+    let tb = text_area.buffer();
+
+    let members = ac.ensemble_members();
+    if members.is_empty() {
+        set_text!(tb, "No ensemble members loaded");
+        return;
+    }
+
+    let members: Vec<_> = members.iter().map(|anal| anal.borrow()).collect();
+    let config = ac.config.borrow();
+    let mut text = String::with_capacity(4096);
+
+    let valid_time = members[0]
+        .sounding()
+        .valid_time()
+        .map(|vt| vt.format("%Y-%m-%d %H:%MZ").to_string())
+        .unwrap_or_else(|| "unknown".to_owned());
+    writeln!(text, "{} members valid {}\n", members.len(), valid_time).unwrap();
+
+    // Distribution of each index across the members.
+    write!(text, "{:-22}", "Index").unwrap();
+    for (label, _) in PERCENTILES {
+        write!(text, " {:>8}", label).unwrap();
+    }
+    writeln!(text, "  {:>2}", "N").unwrap();
+
+    for index in MeteogramIndex::ALL {
+        let mut vals: Vec<f64> = members
+            .iter()
+            .filter_map(|anal| index.value(anal, &config))
+            .collect();
+        vals.sort_by(|a, b| a.total_cmp(b));
+
+        let name = if index.units(&config).is_empty() {
+            index.name().to_owned()
+        } else {
+            format!("{} ({})", index.name(), index.units(&config))
+        };
+        let precision = index.precision(&config);
+        write!(text, "{:-22}", name).unwrap();
+
+        for (_, pct) in PERCENTILES {
+            match ensemble::percentile(&vals, pct) {
+                Some(val) => write!(text, " {:>8.*}", precision, val).unwrap(),
+                None => write!(text, " {:>8}", "-").unwrap(),
+            }
+        }
+        writeln!(text, "  {:>2}", vals.len()).unwrap();
+    }
+
+    // How many members vote for each precipitation type, by algorithm.
+    let algorithms: [(&str, fn(&Analysis) -> Option<PrecipType>); 3] = [
+        ("Model", Analysis::provider_precip_type),
+        ("Bourgouin", Analysis::bourgouin_precip_type),
+        ("NSSL", Analysis::nssl_precip_type),
+    ];
+
+    writeln!(text, "\nPrecipitation Type Votes").unwrap();
+    for (name, precip_type) in algorithms {
+        let votes =
+            ensemble::precip_type_votes(members.iter().filter_map(|anal| precip_type(anal)));
+
+        write!(text, "{:-10}:", name).unwrap();
+        if votes.is_empty() {
+            write!(text, " -").unwrap();
+        }
+        for (precip_type, count) in votes {
+            write!(text, " {:?} ({})", precip_type, count).unwrap();
+        }
+        writeln!(text).unwrap();
+    }
+
+    set_text!(tb, &text);
+}
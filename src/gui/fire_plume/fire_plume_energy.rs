@@ -354,6 +354,10 @@ impl Drawable for FirePlumeEnergyContext {
         Corner::UpperRight
     }
 
+    fn draw_pinned_samples(&self, _args: DrawingArgs<'_, '_>) {
+        // The plume is sampled by fire power, there are no levels to mark.
+    }
+
     fn draw_active_sample(&self, args: DrawingArgs<'_, '_>) {
         if !self.has_data() {
             return;
@@ -235,8 +235,9 @@ impl Drawable for FirePlumeContext {
             check_overlap_then_add(cr, ac, &mut labels, &screen_edges, pair);
         }
 
+        let units = ac.config.borrow().height_units;
         for &h in config::FIRE_PLUME_HEIGHTS.iter().skip(1) {
-            let label = format!("{:.0}km", h.unpack() / 1_000.0);
+            let label = format!("{:.0}{}", units.value(h) / 1_000.0, units.thousands_label());
 
             let extents = cr.text_extents(&label).unwrap();
 
@@ -273,14 +274,15 @@ impl Drawable for FirePlumeContext {
 
     fn build_legend_strings(ac: &AppContext) -> Vec<(String, Rgba)> {
         let config = ac.config.borrow();
+        let units = config.height_units.thousands_label();
 
         vec![
             (
-                "Level of Max Int. Buoyancy (km)".to_owned(),
+                format!("Level of Max Int. Buoyancy ({})", units),
                 config.fire_plume_lmib_color,
             ),
             (
-                "Lifting Condensation Level (km)".to_owned(),
+                format!("Lifting Condensation Level ({})", units),
                 config.fire_plume_lcl_color,
             ),
             (
@@ -382,6 +384,10 @@ impl Drawable for FirePlumeContext {
         Corner::LowerRight
     }
 
+    fn draw_pinned_samples(&self, _args: DrawingArgs<'_, '_>) {
+        // The plume is sampled by fire power, there are no levels to mark.
+    }
+
     fn draw_active_sample(&self, args: DrawingArgs<'_, '_>) {
         if !self.has_data() {
             return;
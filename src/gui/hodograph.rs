@@ -2,13 +2,16 @@ use crate::{
     app::{
         config::{self, HelicityType, Rgba, StormMotionType},
         sample::Sample,
-        AppContext, AppContextPointer, ZoomableDrawingAreas,
+        AppContext, AppContextPointer, EditTarget, ZoomableDrawingAreas,
     },
-    coords::{SDCoords, ScreenCoords, ScreenRect, XYCoords},
+    coords::{DeviceCoords, SDCoords, ScreenCoords, ScreenRect, XYCoords},
     errors::SondeError,
     gui::{
         plot_context::{GenericContext, HasGenericContext, PlotContext, PlotContextExt},
-        utility::{check_overlap_then_add, draw_filled_polygon, plot_curve_from_points},
+        utility::{
+            check_overlap_then_add, draw_filled_polygon, plot_curve_from_points,
+            plot_dashed_curve_from_points,
+        },
         Drawable, DrawingArgs, MasterDrawable,
     },
 };
@@ -20,8 +23,8 @@ use gtk::{
     EventControllerScrollFlags, GestureClick, Window,
 };
 use itertools::izip;
-use metfor::{Knots, Meters, Quantity, WindSpdDir, WindUV};
-use sounding_analysis::DataRow;
+use metfor::{Knots, Quantity, WindSpdDir, WindUV};
+use sounding_analysis::{DataRow, Sounding};
 use std::{iter::once, rc::Rc};
 
 pub struct HodoContext {
@@ -44,10 +47,49 @@ impl HodoContext {
         XYCoords { x, y }
     }
 
+    pub fn convert_xy_to_sd(coords: XYCoords) -> SDCoords {
+        let u = Knots((coords.x - 0.5) * 2.0 * config::MAX_SPEED.unpack());
+        let v = Knots((coords.y - 0.5) * 2.0 * config::MAX_SPEED.unpack());
+
+        SDCoords {
+            spd_dir: WindSpdDir::from(WindUV { u, v }),
+        }
+    }
+
     pub fn convert_sd_to_screen(&self, coords: SDCoords) -> ScreenCoords {
         let xy = HodoContext::convert_sd_to_xy(coords);
         self.convert_xy_to_screen(xy)
     }
+
+    /// Find the wind closest to the cursor, if there is one close enough to grab for editing.
+    fn find_edit_target(&self, position: DeviceCoords, ac: &AppContext) -> Option<EditTarget> {
+        const EDIT_HIT_RADIUS: f64 = 8.0;
+
+        let anal = ac.get_sounding_for_display()?;
+        let anal = anal.borrow();
+        let snd = anal.sounding();
+        let min_hodo_pressure = ac.config.borrow().min_hodo_pressure;
+
+        let cursor = self.convert_device_to_screen(position);
+        let max_dist = EDIT_HIT_RADIUS / self.scale_factor();
+
+        izip!(0.., snd.pressure_profile(), snd.wind_profile())
+            .filter_map(|(i, p, wind)| {
+                p.into_option()
+                    .filter(|p| *p >= min_hodo_pressure)
+                    .and(wind.into_option())
+                    .map(|spd_dir| (i, self.convert_sd_to_screen(SDCoords { spd_dir })))
+            })
+            .map(|(i, pnt)| {
+                (
+                    i,
+                    ((pnt.x - cursor.x).powi(2) + (pnt.y - cursor.y).powi(2)).sqrt(),
+                )
+            })
+            .filter(|&(_, dist)| dist <= max_dist)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| EditTarget::Wind(i))
+    }
 }
 
 impl HasGenericContext for HodoContext {
@@ -235,9 +277,10 @@ impl Drawable for HodoContext {
         let screen_edges = self.calculate_plot_edges(cr, ac);
 
         if config.show_iso_speed {
+            let units = config.wind_speed_units;
             for &s in &config::ISO_SPEED {
                 for direction in &[240.0] {
-                    let label = format!("{:.0}", s.unpack());
+                    let label = format!("{:.0}", units.value(s));
 
                     let extents = cr.text_extents(&label).unwrap();
 
@@ -288,6 +331,7 @@ impl Drawable for HodoContext {
     fn draw_data(&self, args: DrawingArgs<'_, '_>) {
         draw_data(args);
         draw_data_overlays(args);
+        draw_edit_handles(args);
     }
 
     /***********************************************************************************************
@@ -323,6 +367,35 @@ impl Drawable for HodoContext {
         Self::draw_point(coords, rgba, args);
     }
 
+    fn draw_pinned_samples(&self, args: DrawingArgs<'_, '_>) {
+        if !self.has_data() {
+            return;
+        }
+
+        let (ac, config) = (args.ac, args.ac.config.borrow());
+
+        let anal = match ac.get_sounding_for_display() {
+            Some(anal) => anal,
+            None => return,
+        };
+        let anal = anal.borrow();
+
+        for pressure in ac
+            .pinned_samples()
+            .into_iter()
+            .filter(|&pr| pr > config.min_hodo_pressure)
+        {
+            let wind = sounding_analysis::linear_interpolate_sounding(anal.sounding(), pressure)
+                .ok()
+                .and_then(|row| row.wind.into_option());
+
+            if let Some(spd_dir) = wind {
+                let coords = ac.hodo.convert_sd_to_screen(SDCoords { spd_dir });
+                Self::draw_point(coords, config.pinned_sample_rgba, args);
+            }
+        }
+    }
+
     /***********************************************************************************************
      * Events
      **********************************************************************************************/
@@ -342,6 +415,64 @@ impl Drawable for HodoContext {
     fn enter_event(&self, ac: &AppContextPointer) {
         ac.set_last_focus(ZoomableDrawingAreas::Hodo);
     }
+
+    fn left_button_press_event(&self, position: (f64, f64), ac: &AppContextPointer) {
+        let position = DeviceCoords::from(position);
+        self.set_last_cursor_position(Some(position));
+
+        // While editing, grabbing a point drags it instead of panning the view.
+        match self
+            .find_edit_target(position, ac)
+            .filter(|_| ac.is_editing())
+        {
+            Some(target) => ac.start_edit(target),
+            None => self.set_left_button_pressed(true),
+        }
+    }
+
+    fn left_button_release_event(&self, _position: (f64, f64), ac: &AppContextPointer) {
+        if ac.edit_target().is_some() {
+            AppContext::finish_edit(ac);
+        }
+
+        self.set_last_cursor_position(None);
+        self.set_left_button_pressed(false);
+    }
+
+    fn mouse_motion_event(
+        &self,
+        controller: &EventControllerMotion,
+        new_position: (f64, f64),
+        ac: &AppContextPointer,
+    ) {
+        let da: DrawingArea = controller.widget().unwrap().downcast().unwrap();
+        da.grab_focus();
+
+        let position = DeviceCoords::from(new_position);
+
+        if let Some(EditTarget::Wind(_)) = ac.edit_target() {
+            let SDCoords { spd_dir } = Self::convert_xy_to_sd(self.convert_device_to_xy(position));
+            AppContext::drag_wind_edit(ac, spd_dir);
+        } else if self.get_left_button_pressed() {
+            if let Some(last_position) = self.get_last_cursor_position() {
+                let old_position = self.convert_device_to_xy(last_position);
+
+                let position = self.convert_device_to_xy(position);
+                let delta = (position.x - old_position.x, position.y - old_position.y);
+                let mut translate = self.get_translate();
+                translate.x -= delta.0;
+                translate.y -= delta.1;
+                self.set_translate(translate);
+                self.bound_view();
+                self.mark_background_dirty();
+
+                crate::gui::draw_all(ac);
+                crate::gui::text_area::update_text_highlight(ac);
+            }
+        }
+
+        self.set_last_cursor_position(Some(position));
+    }
 }
 
 impl MasterDrawable for HodoContext {}
@@ -358,7 +489,7 @@ fn build_hodograph_area_context_menu(acp: &AppContextPointer) -> Result<(), Sond
 
     // Configure the layer to use for helicity calculations
     let current_helicity_layer = match config.helicity_layer {
-        HelicityType::SurfaceTo3km => "sfc_to_3km",
+        HelicityType::SurfaceLayer => "sfc_layer",
         HelicityType::Effective => "effective",
     };
 
@@ -381,7 +512,7 @@ fn build_hodograph_area_context_menu(acp: &AppContextPointer) -> Result<(), Sond
         action.set_state(var);
 
         let layer = match val {
-            "sfc_to_3km" => HelicityType::SurfaceTo3km,
+            "sfc_layer" => HelicityType::SurfaceLayer,
             "effective" => HelicityType::Effective,
             _ => unreachable!(),
         };
@@ -442,6 +573,8 @@ fn build_hodograph_area_context_menu(acp: &AppContextPointer) -> Result<(), Sond
     });
     hodo_group.add_action(&helicity_type_action);
 
+    crate::gui::add_edit_actions(&hodo_group, acp);
+
     Ok(())
 }
 
@@ -452,34 +585,72 @@ fn draw_data(args: DrawingArgs<'_, '_>) {
     let (ac, cr) = (args.ac, args.cr);
     let config = ac.config.borrow();
 
+    // Ghosts go underneath the displayed sounding.
+    for ghost in ac.ghost_soundings() {
+        let ghost = ghost.borrow();
+        plot_dashed_curve_from_points(
+            cr,
+            config.velocity_line_width,
+            config.ghost_rgba,
+            hodograph_points(ghost.sounding(), ac),
+        );
+    }
+
     if let Some(anal) = ac.get_sounding_for_display() {
         let anal = anal.borrow();
-        let sndg = anal.sounding();
-        let pres_data = sndg.pressure_profile();
-        let wind_data = sndg.wind_profile();
-
-        let profile_data = izip!(pres_data, wind_data).filter_map(|(p, wind)| {
-            if let (Some(p), Some(spd_dir)) = (p.into_option(), wind.into_option()) {
-                if p >= config.min_hodo_pressure {
-                    let sd_coords = SDCoords { spd_dir };
-                    Some(ac.hodo.convert_sd_to_screen(sd_coords))
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        });
 
         plot_curve_from_points(
             cr,
             config.velocity_line_width,
             config.wind_rgba,
-            profile_data,
+            hodograph_points(anal.sounding(), ac),
         );
     }
 }
 
+fn hodograph_points<'a>(
+    sndg: &'a Sounding,
+    ac: &'a AppContext,
+) -> impl Iterator<Item = ScreenCoords> + 'a {
+    let min_p = ac.config.borrow().min_hodo_pressure;
+
+    izip!(sndg.pressure_profile(), sndg.wind_profile()).filter_map(move |(p, wind)| {
+        if let (Some(p), Some(spd_dir)) = (p.into_option(), wind.into_option()) {
+            if p >= min_p {
+                let sd_coords = SDCoords { spd_dir };
+                Some(ac.hodo.convert_sd_to_screen(sd_coords))
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    })
+}
+
+fn draw_edit_handles(args: DrawingArgs<'_, '_>) {
+    let ac = args.ac;
+
+    if !ac.is_editing() {
+        return;
+    }
+
+    if let Some(anal) = ac.get_sounding_for_display() {
+        let anal = anal.borrow();
+        let sndg = anal.sounding();
+        let config = ac.config.borrow();
+
+        for (p, wind) in izip!(sndg.pressure_profile(), sndg.wind_profile()) {
+            if let (Some(p), Some(spd_dir)) = (p.into_option(), wind.into_option()) {
+                if p >= config.min_hodo_pressure {
+                    let coords = ac.hodo.convert_sd_to_screen(SDCoords { spd_dir });
+                    HodoContext::draw_point(coords, config.wind_rgba, args);
+                }
+            }
+        }
+    }
+}
+
 fn draw_data_overlays(args: DrawingArgs<'_, '_>) {
     draw_helicity_fill(args);
     draw_storm_motion_and_mean_wind(args);
@@ -512,8 +683,8 @@ fn draw_helicity_fill(args: DrawingArgs<'_, '_>) {
 
         let pnts = {
             let layer = match config.helicity_layer {
-                HelicityType::SurfaceTo3km => {
-                    sounding_analysis::layer_agl(anal.sounding(), Meters(3000.0)).ok()
+                HelicityType::SurfaceLayer => {
+                    sounding_analysis::layer_agl(anal.sounding(), config.srh_depth).ok()
                 }
                 HelicityType::Effective => anal.effective_inflow_layer(),
             };
@@ -1,33 +1,27 @@
 use crate::{
     analysis::Analysis,
-    app::{AppContext, AppContextPointer},
+    app::{config::Config, AppContext, AppContextPointer},
     errors::SondeError,
 };
 use gtk::{
-    glib::{translate::IntoGlib, Propagation},
-    prelude::*,
-    EventControllerKey, TextBuffer, TextTag, TextView,
+    glib::translate::IntoGlib, prelude::*, EventControllerKey, TextBuffer, TextTag, TextView,
 };
-use metfor::{Fahrenheit, Feet, Inches, Knots, Quantity};
+use metfor::{Celsius, HectoPascal, Knots, Meters, MetersPSec, Quantity};
+use sounding_analysis::ParcelAscentAnalysis;
 use std::{fmt::Write, rc::Rc};
 
 const TEXT_AREA_ID: &str = "indexes_text_area";
+/// Width of the parcel names in the parcel tables.
+const PARCEL_LABEL_WIDTH: usize = 14;
 const HEADER_LINE: &str = "----------------------------------------------------\n";
 
 pub fn set_up_indexes_area(acp: &AppContextPointer) -> Result<(), SondeError> {
-    use gtk::gdk::Key;
-
     let text_area: TextView = acp.fetch_widget(TEXT_AREA_ID)?;
 
     let key_press = EventControllerKey::new();
     let ac = Rc::clone(acp);
     key_press.connect_key_pressed(move |_key_press, key, _code, _key_modifier| {
-        if key == Key::KP_Right || key == Key::Right {
-            ac.display_next();
-        } else if key == Key::KP_Left || key == Key::Left {
-            ac.display_previous();
-        }
-        Propagation::Stop
+        crate::gui::key_press(key, &ac)
     });
     text_area.add_controller(key_press);
 
@@ -54,9 +48,11 @@ pub fn update_indexes_area(ac: &AppContext) {
     let anal = &anal.borrow();
     let text = &mut String::with_capacity(4096);
 
-    push_profile_indexes(text, anal);
-    push_parcel_indexes(text, anal);
-    push_fire_indexes(text, anal);
+    let config = ac.config.borrow();
+    push_profile_indexes(text, anal, &config);
+    let custom_parcel_rows = push_parcel_indexes(text, anal, &config);
+    push_fire_indexes(text, anal, &config);
+    drop(config);
 
     // Get the scroll position before setting the text
     let old_adj = text_area.vadjustment().map(|adj| adj.value());
@@ -64,6 +60,7 @@ pub fn update_indexes_area(ac: &AppContext) {
     set_text(&text_buffer, text);
 
     highlight_parcel(&text_buffer, ac);
+    color_custom_parcels(&text_buffer, ac, &custom_parcel_rows);
 
     // I don't totally understand this, but after quite a lot of experimentation this works
     // well at keeping the scroll of the text view in the same area as you step through
@@ -132,19 +129,13 @@ macro_rules! push_prof {
             });
         $buf.push('\n');
     };
-    ($anal: expr, $buf:ident, $name:expr, $selector:tt, $format:expr, temp, $empty_val:expr) => {
+    ($anal: expr, $buf:ident, $name:expr, $selector:tt, $format:expr, temp($units:expr), $empty_val:expr) => {
         $buf.push_str($name);
         $anal
             .$selector()
             .into_option()
             .and_then(|val| {
-                write!(
-                    $buf,
-                    $format,
-                    val.unpack(),
-                    Fahrenheit::from(val).unpack().round()
-                )
-                .unwrap();
+                write!($buf, $format, $units.value(val), $units.label()).unwrap();
                 Some(())
             })
             .or_else(|| {
@@ -153,23 +144,19 @@ macro_rules! push_prof {
             });
         $buf.push('\n');
     };
-    ($anal: expr, $buf:ident, $name:expr, $selector:tt, $format:expr, mm, $empty_val:expr) => {
+    ($anal: expr, $buf:ident, $name:expr, $selector:tt, $format:expr, mm($units:expr), $empty_val:expr) => {
         $buf.push_str($name);
         $anal
             .$selector()
             .into_option()
             .and_then(|val| {
-                let val_inches = Inches::from(val);
-                if val_inches < Inches(0.01) && val_inches > Inches(0.0) {
-                    write!($buf, "         T").unwrap();
+                let precision = $units.precision();
+                let val = $units.value(val);
+                let smallest = 10.0f64.powi(-(precision as i32));
+                if val < smallest / 2.0 && val > 0.0 {
+                    write!($buf, "      T").unwrap();
                 } else {
-                    write!(
-                        $buf,
-                        $format,
-                        (val.unpack() * 10.0).round() / 10.0,
-                        (val_inches.unpack() * 100.0).round() / 100.0,
-                    )
-                    .unwrap();
+                    write!($buf, $format, precision, val, $units.label()).unwrap();
                 }
                 Some(())
             })
@@ -179,15 +166,21 @@ macro_rules! push_prof {
             });
         $buf.push('\n');
     };
-    ($anal: expr, $buf:ident, $name:expr, $selector:tt, $format:expr, cape, $empty_val:expr) => {
+    ($anal: expr, $buf:ident, $name:expr, $selector:tt, $format:expr, cape($units:expr), $empty_val:expr) => {
         $buf.push_str($name);
         $anal
             .$selector()
             .into_option()
             .and_then(|val| {
-                let mps = (val.unpack() * 2.0).sqrt();
-                let mph = mps * 2.23694;
-                write!($buf, $format, val.unpack(), mps.round(), mph.round()).unwrap();
+                let speed = Knots::from(MetersPSec((val.unpack() * 2.0).sqrt()));
+                write!(
+                    $buf,
+                    $format,
+                    val.unpack(),
+                    $units.value(speed),
+                    $units.label()
+                )
+                .unwrap();
                 Some(())
             })
             .or_else(|| {
@@ -200,29 +193,35 @@ macro_rules! push_prof {
 
 #[inline]
 #[rustfmt::skip]
-fn push_profile_indexes(buffer: &mut String, anal: &Analysis){
+fn push_profile_indexes(buffer: &mut String, anal: &Analysis, config: &Config){
     let empty_val = "    -    ";
+    let (t_units, w_units, p_units) = (config.temperature_units, config.wind_speed_units, config.precip_units);
 
     buffer.push('\n');
 
     buffer.push_str("Index                Value\n");
     buffer.push_str(HEADER_LINE);
-    push_prof!(anal, buffer, "1-hour Precip       ", provider_1hr_precip, "{:>7.1} mm ({:>4.2} in)",                           mm,   empty_val);
-    push_prof!(anal, buffer, "DCAPE               ", dcape,               "{:>5.0} J/kg ({:>3.0} m/s {:>3.0} m/h)",            cape, empty_val);
-    push_prof!(anal, buffer, "PWAT                ", pwat,                "{:>7.0} mm ({:>4.2} in)",                           mm,   empty_val);
-    push_prof!(anal, buffer, "Downrush T          ", downrush_t,          "{:>8.0}\u{00b0}C ({:>3.0}\u{00b0}F)",               temp, empty_val);
-    push_prof!(anal, buffer, "Convective T        ", convective_t,        "{:>8.0}\u{00b0}C ({:>3.0}\u{00b0}F)              ", temp, empty_val);
-    push_prof!(anal, buffer, "3km SR Helicity (RM)", sr_helicity_3k_rm,   "{:>4.0} m\u{00b2}/s\u{00b2}",                             empty_val);
-    push_prof!(anal, buffer, "3km SR Helicity (LM)", sr_helicity_3k_lm,   "{:>4.0} m\u{00b2}/s\u{00b2}",                             empty_val);
+    push_prof!(anal, buffer, "1-hour Precip       ", provider_1hr_precip, "{:>7.*} {}",                 mm(p_units),   empty_val);
+    push_prof!(anal, buffer, "DCAPE               ", dcape,               "{:>5.0} J/kg ({:>3.0} {})",  cape(w_units), empty_val);
+    push_prof!(anal, buffer, "PWAT                ", pwat,                "{:>7.*} {}",                 mm(p_units),   empty_val);
+    push_prof!(anal, buffer, "Downrush T          ", downrush_t,          "{:>8.0}{}",                  temp(t_units), empty_val);
+    push_prof!(anal, buffer, "Convective T        ", convective_t,        "{:>8.0}{}",                  temp(t_units), empty_val);
+    push_prof!(anal, buffer, "Sfc SR Helicity (RM)", sr_helicity_sfc_rm,  "{:>4.0} m\u{00b2}/s\u{00b2}",                             empty_val);
+    push_prof!(anal, buffer, "Sfc SR Helicity (LM)", sr_helicity_sfc_lm,  "{:>4.0} m\u{00b2}/s\u{00b2}",                             empty_val);
     push_prof!(anal, buffer, "Eff SR Helicity (RM)", sr_helicity_eff_rm,  "{:>4.0} m\u{00b2}/s\u{00b2}",                             empty_val);
     push_prof!(anal, buffer, "Eff SR Helicity (LM)", sr_helicity_eff_lm,  "{:>4.0} m\u{00b2}/s\u{00b2}",                             empty_val);
 }
 
 #[inline]
 #[rustfmt::skip]
-fn push_parcel_indexes(buffer: &mut String, anal: &Analysis) {
+fn push_parcel_indexes(buffer: &mut String, anal: &Analysis, config: &Config) -> Vec<(i32, usize)> {
     buffer.push('\n');
 
+    let (p_units, h_units, t_units) = (config.pressure_units, config.height_units, config.temperature_units);
+    let pres = |p: HectoPascal| format!("{:.*}", p_units.precision(), p_units.value(p));
+    let hgt = |h: Meters| format!("{:.0}", h_units.value(h));
+    let temp = |t: Celsius| format!("{:.0}", t_units.value(t));
+
     macro_rules! push_var {
         ($buf:ident, $anal:ident, $selector:tt, $fmt:expr,$empty:expr) => {
             $anal.$selector().into_option().and_then(|val|{
@@ -232,7 +231,16 @@ fn push_parcel_indexes(buffer: &mut String, anal: &Analysis) {
                 $buf.push_str($empty);
                 Some(())
             });
-        }
+        };
+        ($buf:ident, $anal:ident, $selector:tt, $convert:ident, $fmt:expr,$empty:expr) => {
+            $anal.$selector().into_option().and_then(|val|{
+                $buf.push_str(&format!($fmt, $convert(val)));
+                Some(())
+            }).or_else(||{
+                $buf.push_str($empty);
+                Some(())
+            });
+        };
     }
 
     macro_rules! parcel_index_row {
@@ -258,12 +266,12 @@ fn push_parcel_indexes(buffer: &mut String, anal: &Analysis) {
             $buf.push_str($pcl_name);
 
             if let Some(anal) = $opt_pcl_anal {
-                push_var!($buf, anal, lcl_pressure,   " {:>5.0}", $empty);
-                push_var!($buf, anal, lcl_height_agl, " {:>6.0}", $empty);
-                push_var!($buf, anal, lfc_pressure,   " {:>5.0}", $empty);
-                push_var!($buf, anal, el_pressure,    " {:>5.0}", $empty);
-                push_var!($buf, anal, el_height_asl,  " {:>6.0}", $empty);
-                push_var!($buf, anal, el_temperature, " {:>5.0}", $empty);
+                push_var!($buf, anal, lcl_pressure,   pres, " {:>5}", $empty);
+                push_var!($buf, anal, lcl_height_agl, hgt,  " {:>6}", $empty);
+                push_var!($buf, anal, lfc_pressure,   pres, " {:>5}", $empty);
+                push_var!($buf, anal, el_pressure,    pres, " {:>5}", $empty);
+                push_var!($buf, anal, el_height_asl,  hgt,  " {:>6}", $empty);
+                push_var!($buf, anal, el_temperature, temp, " {:>5}", $empty);
             } else {
                 $buf.push_str("         -- No Parcel --              ");
             }
@@ -277,6 +285,13 @@ fn push_parcel_indexes(buffer: &mut String, anal: &Analysis) {
     let mu = anal.most_unstable_parcel_analysis();
     let con = anal.convective_parcel_analysis();
     let eff = anal.effective_parcel_analysis();
+    let custom: Vec<(String, Option<&ParcelAscentAnalysis>)> = config.custom_parcels.iter()
+        .map(|pcl| (custom_parcel_label(&pcl.name), anal.custom_parcel_analysis(pcl.source)))
+        .collect();
+
+    // Remember which line each custom parcel is on so it can be colored.
+    let mut custom_rows = vec![];
+    let line_number = |buffer: &str| buffer.matches('\n').count() as i32;
 
     let empty = "     -";
     buffer.push_str("Parcel          CAPE   CIN NCAPE  Hail\n");
@@ -287,20 +302,34 @@ fn push_parcel_indexes(buffer: &mut String, anal: &Analysis) {
     parcel_index_row!(buffer, "Most Unstable ", mu,  empty);
     parcel_index_row!(buffer, "Convective    ", con, empty);
     parcel_index_row!(buffer, "Effective     ", eff, empty);
+    for (i, (name, pcl)) in custom.iter().enumerate() {
+        let pcl = *pcl;
+        custom_rows.push((line_number(buffer), i));
+        parcel_index_row!(buffer, name, pcl, empty);
+    }
     buffer.push('\n');
     buffer.push_str("Parcel           LCL    LCL   LFC    EL     EL    EL\n");
-    buffer.push_str("                 hPa  m AGL   hPa   hPa  m ASL     C\n");
+    buffer.push_str(&format!("{:14} {:>5} {:>6} {:>5} {:>5} {:>6} {:>5}\n", "",
+            p_units.label(), format!("{} AGL", h_units.label()), p_units.label(), p_units.label(),
+            format!("{} ASL", h_units.label()), t_units.label()));
     buffer.push_str(HEADER_LINE);
     parcel_level_row!(buffer, "Surface       ", sfc, empty);
     parcel_level_row!(buffer, "Mixed Layer   ", ml,  empty);
     parcel_level_row!(buffer, "Most Unstable ", mu,  empty);
     parcel_level_row!(buffer, "Convective    ", con, empty);
     parcel_level_row!(buffer, "Effective     ", eff, empty);
+    for (i, (name, pcl)) in custom.iter().enumerate() {
+        let pcl = *pcl;
+        custom_rows.push((line_number(buffer), i));
+        parcel_level_row!(buffer, name, pcl, empty);
+    }
+
+    custom_rows
 }
 
 #[inline]
 #[rustfmt::skip]
-fn push_fire_indexes(buffer: &mut String, anal: &Analysis) {
+fn push_fire_indexes(buffer: &mut String, anal: &Analysis, config: &Config) {
     
     macro_rules! push_fire_index {
         ($buf:ident, $label:expr, $anal:ident, $selector:tt, $fmt:expr, $empty:expr) => {
@@ -331,8 +360,10 @@ fn push_fire_indexes(buffer: &mut String, anal: &Analysis) {
     buffer.push_str("PFT           ");
     if let Some(pft_anal) = anal.pft(){
             buffer.push_str(&format!("{:>10.0}GW\n", pft_anal.pft.unpack()));
-            buffer.push_str(&format!("           [Spd = {:>2.0}kts Zfc = {:5.0}ft ∆θ = {:5.2}K]\n",
-                    Knots::from(pft_anal.u_ml).unpack(), Feet::from(pft_anal.z_fc).unpack(), pft_anal.d_theta.unpack()));
+            let (w_units, h_units) = (config.wind_speed_units, config.height_units);
+            buffer.push_str(&format!("           [Spd = {:>2.0}{} Zfc = {:5.0}{} ∆θ = {:5.2}K]\n",
+                    w_units.value(Knots::from(pft_anal.u_ml)), w_units.label(),
+                    h_units.value(pft_anal.z_fc), h_units.label(), pft_anal.d_theta));
     } else {
         buffer.push_str(empty);
     }
@@ -379,3 +410,44 @@ fn highlight_parcel(tb: &TextBuffer, ac: &AppContext) {
         }
     }
 }
+
+/// Name of a custom parcel padded or cut to fit the parcel tables.
+fn custom_parcel_label(name: &str) -> String {
+    format!("{:<width$.width$}", name, width = PARCEL_LABEL_WIDTH)
+}
+
+/// Color the names of the custom parcels in the parcel tables to match their profiles, `rows` is
+/// the line each parcel's name is on paired with its position in the configuration.
+fn color_custom_parcels(tb: &TextBuffer, ac: &AppContext, rows: &[(i32, usize)]) {
+    let config = ac.config.borrow();
+    let tag_table = tb.tag_table();
+
+    for (i, parcel) in config.custom_parcels.iter().enumerate() {
+        let tag_name = format!("custom_parcel_{}", i);
+        let tag = match tag_table.lookup(&tag_name) {
+            Some(tag) => tag,
+            None => {
+                let tag = TextTag::builder().name(tag_name.as_str()).build();
+                let success = tag_table.add(&tag);
+                debug_assert!(success, "Failed to add tag to text tag table");
+                tag
+            }
+        };
+
+        let rgba = parcel.rgba;
+        tag.set_foreground_rgba(Some(&gtk::gdk::RGBA::new(
+            rgba.0 as f32,
+            rgba.1 as f32,
+            rgba.2 as f32,
+            rgba.3 as f32,
+        )));
+
+        for &(line, _) in rows.iter().filter(|&&(_, row_parcel)| row_parcel == i) {
+            if let Some(start) = tb.iter_at_line(line) {
+                let mut end = start;
+                end.forward_chars(PARCEL_LABEL_WIDTH as i32);
+                tb.apply_tag(&tag, &start, &end);
+            }
+        }
+    }
+}
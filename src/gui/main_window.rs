@@ -1,5 +1,5 @@
 use crate::{
-    app::{AppContext, AppContextPointer},
+    app::{config::Config, AppContext, AppContextPointer},
     errors::SondeError,
 };
 use gtk::{
@@ -7,14 +7,16 @@ use gtk::{
     gio::{SimpleAction, SimpleActionGroup},
     glib::Propagation,
     prelude::*,
-    Button, Notebook, Paned, Widget, Window,
+    Button, DropDown, Notebook, Paned, StringList, Widget, Window,
 };
 use std::rc::Rc;
 
 mod menu_callbacks;
 
-const TABS: [(&str, &str); 8] = [
+const TABS: [(&str, &str); 13] = [
     ("skew_t", "Skew-T"),
+    ("time_height_area", "Time-Height"),
+    ("meteogram_area", "Meteogram"),
     ("hodograph_area", "Hodograph"),
     ("fire_plume_container", "Fire Plume"),
     ("text_area_container", "Text"),
@@ -22,6 +24,9 @@ const TABS: [(&str, &str); 8] = [
     ("profiles_area_container", "Profiles"),
     ("indexes_scrolled_window", "Indexes"),
     ("provider_data_text_container", "Provider Data"),
+    ("ensemble_text_container", "Ensemble"),
+    ("pinned_text_container", "Pinned"),
+    ("metadata_text_container", "Metadata"),
 ];
 
 pub fn set_up_main_window(ac: &AppContextPointer) -> Result<(), SondeError> {
@@ -51,7 +56,8 @@ macro_rules! set_up_button {
 
 fn connect_header_bar(ac: &AppContextPointer) -> Result<(), SondeError> {
     use menu_callbacks::{
-        load_default_theme, load_theme, open_toolbar_callback, save_image_callback, save_theme,
+        load_default_theme, load_theme, open_comparison_files, open_toolbar_callback,
+        save_image_callback, save_theme,
     };
 
     let win: Window = ac.fetch_widget("main_window")?;
@@ -64,6 +70,12 @@ fn connect_header_bar(ac: &AppContextPointer) -> Result<(), SondeError> {
     set_up_button!(ac, "go-next-button", display_next);
     set_up_button!(ac, "go-last-button", display_last);
 
+    let station_selector: DropDown = ac.fetch_widget("station-selector")?;
+    let acp = ac.clone();
+    station_selector.connect_selected_notify(move |selector| {
+        AppContext::select_station(&acp, selector.selected() as usize);
+    });
+
     set_up_button!(ac, "zoom-in-button", zoom_in);
     set_up_button!(ac, "zoom-out-button", zoom_out);
 
@@ -89,6 +101,14 @@ fn connect_header_bar(ac: &AppContextPointer) -> Result<(), SondeError> {
     });
     burger_group.add_action(&save_theme_action);
 
+    let acp = ac.clone();
+    let winc = win.clone();
+    let open_comparison_action = SimpleAction::new("open_comparison", None);
+    open_comparison_action.connect_activate(move |_action, _variant| {
+        open_comparison_files(&acp, &winc);
+    });
+    burger_group.add_action(&open_comparison_action);
+
     let acp = ac.clone();
     let load_theme_action = SimpleAction::new("load_theme", None);
     load_theme_action.connect_activate(move |_action, _variant| {
@@ -96,9 +116,124 @@ fn connect_header_bar(ac: &AppContextPointer) -> Result<(), SondeError> {
     });
     burger_group.add_action(&load_theme_action);
 
+    add_units_actions(&burger_group, ac);
+
     Ok(())
 }
 
+/// Add a stateful action to the hamburger menu for each kind of unit.
+fn add_units_actions(group: &SimpleActionGroup, ac: &AppContextPointer) {
+    use crate::app::config::{
+        HeightUnit, PrecipUnit, PressureUnit, TemperatureUnit, WindSpeedUnit,
+    };
+
+    add_units_action(
+        group,
+        ac,
+        "temperature_units",
+        &[
+            ("c", TemperatureUnit::Celsius),
+            ("f", TemperatureUnit::Fahrenheit),
+        ],
+        |config| config.temperature_units,
+        |config, units| config.temperature_units = units,
+    );
+
+    add_units_action(
+        group,
+        ac,
+        "wind_speed_units",
+        &[
+            ("kt", WindSpeedUnit::Knots),
+            ("mps", WindSpeedUnit::MetersPerSecond),
+            ("mph", WindSpeedUnit::MilesPerHour),
+            ("kph", WindSpeedUnit::KilometersPerHour),
+        ],
+        |config| config.wind_speed_units,
+        |config, units| config.wind_speed_units = units,
+    );
+
+    add_units_action(
+        group,
+        ac,
+        "height_units",
+        &[("m", HeightUnit::Meters), ("ft", HeightUnit::Feet)],
+        |config| config.height_units,
+        |config, units| config.height_units = units,
+    );
+
+    add_units_action(
+        group,
+        ac,
+        "precip_units",
+        &[("mm", PrecipUnit::Millimeters), ("in", PrecipUnit::Inches)],
+        |config| config.precip_units,
+        |config, units| config.precip_units = units,
+    );
+
+    add_units_action(
+        group,
+        ac,
+        "pressure_units",
+        &[
+            ("hpa", PressureUnit::HectoPascals),
+            ("kpa", PressureUnit::Kilopascals),
+            ("inhg", PressureUnit::InchesOfMercury),
+        ],
+        |config| config.pressure_units,
+        |config, units| config.pressure_units = units,
+    );
+}
+
+fn add_units_action<T: Copy + PartialEq + 'static>(
+    group: &SimpleActionGroup,
+    ac: &AppContextPointer,
+    name: &str,
+    choices: &'static [(&'static str, T)],
+    get: fn(&Config) -> T,
+    set: fn(&mut Config, T),
+) {
+    let current = get(&ac.config.borrow());
+    let current = choices
+        .iter()
+        .find(|(_, units)| *units == current)
+        .map(|(target, _)| *target)
+        .unwrap_or(choices[0].0);
+
+    let variant = unsafe {
+        &gtk::glib::Variant::from_data_with_type_trusted(current, gtk::glib::VariantTy::STRING)
+    };
+    let action = SimpleAction::new_stateful(name, Some(gtk::glib::VariantTy::STRING), variant);
+
+    let acp = ac.clone();
+    action.connect_activate(move |action, variant| {
+        let var = variant.unwrap();
+        let val: &str = var.str().unwrap();
+        action.set_state(var);
+
+        if let Some(&(_, units)) = choices.iter().find(|(target, _)| *target == val) {
+            set(&mut acp.config.borrow_mut(), units);
+            AppContext::update_units(&acp);
+        }
+    });
+    group.add_action(&action);
+}
+
+/// Fill the station selector with the loaded stations, it is only shown if there is more than one.
+pub fn update_station_selector(ac: &AppContext) {
+    let selector: DropDown = match ac.fetch_widget("station-selector") {
+        Ok(selector) => selector,
+        Err(_) => return,
+    };
+
+    let labels = ac.station_labels();
+    let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+
+    selector.set_model(Some(&StringList::new(&labels)));
+    selector.set_selected(ac.selected_station() as u32);
+    selector.set_visible(labels.len() > 1);
+}
+
 fn configure_main_window(ac: &AppContextPointer) -> Result<(), SondeError> {
     let window: Window = ac.fetch_widget("main_window")?;
 
@@ -1,5 +1,5 @@
 use crate::{
-    app::AppContextPointer,
+    app::{AppContext, AppContextPointer},
     coords::DeviceRect,
     errors::SondeError,
     gui::{Drawable, DrawingArgs, PlotContext},
@@ -15,8 +15,63 @@ pub fn open_toolbar_callback(ac: &AppContextPointer, win: &Window) {
 }
 
 fn open_files(ac: &AppContextPointer, win: &Window) {
+    let dialog = sounding_file_dialog("Open File", ac, win);
+
+    let ac = ac.clone();
+    let win = win.clone();
+    dialog.connect_response(move |dialog, response| {
+        if response == ResponseType::Ok {
+            let paths = selected_paths(dialog);
+
+            // Remember the last opened file in the config.
+            if let Some(ref f0) = paths.get(0) {
+                ac.config.borrow_mut().last_open_file = Some(PathBuf::from(f0));
+            }
+
+            if let Err(ref err) = crate::app::load_file::load_multiple(&paths, &ac) {
+                show_error_dialog(&format!("Error loading file: {}", err), &win);
+            } else {
+                let da: Widget = ac.fetch_widget("skew_t").unwrap();
+                da.grab_focus();
+            }
+        }
+
+        match response {
+            ResponseType::DeleteEvent => {}
+            _x => dialog.close(),
+        }
+    });
+
+    dialog.show();
+}
+
+pub fn open_comparison_files(ac: &AppContextPointer, win: &Window) {
+    let dialog = sounding_file_dialog("Open Comparison File", ac, win);
+
+    let ac = ac.clone();
+    let win = win.clone();
+    dialog.connect_response(move |dialog, response| {
+        if response == ResponseType::Ok {
+            let paths = selected_paths(dialog);
+
+            if let Err(ref err) = crate::app::load_file::load_comparison(&paths, &ac) {
+                show_error_dialog(&format!("Error loading file: {}", err), &win);
+            }
+        }
+
+        match response {
+            ResponseType::DeleteEvent => {}
+            _x => dialog.close(),
+        }
+    });
+
+    dialog.show();
+}
+
+/// Build a dialog for selecting sounding files with filters for every supported file type.
+fn sounding_file_dialog(title: &str, ac: &AppContext, win: &Window) -> FileChooserDialog {
     let dialog = FileChooserDialog::new(
-        Some("Open File"),
+        Some(title),
         Some(win),
         FileChooserAction::Open,
         &[("Open", ResponseType::Ok), ("Cancel", ResponseType::Cancel)],
@@ -57,39 +112,19 @@ fn open_files(ac: &AppContextPointer, win: &Window) {
     filter.set_name(Some("All Files"));
     dialog.add_filter(&filter);
 
-    let ac = ac.clone();
-    let win = win.clone();
-    dialog.connect_response(move |dialog, response| {
-        if response == ResponseType::Ok {
-            let paths: Vec<_> = dialog
-                .files()
-                .into_iter()
-                .filter_map(|pb| pb.ok())
-                .filter_map(|pb| pb.downcast::<gio::File>().ok())
-                .filter_map(|pb| pb.path())
-                .filter(|pb| pb.is_file())
-                .collect();
-
-            // Remember the last opened file in the config.
-            if let Some(ref f0) = paths.get(0) {
-                ac.config.borrow_mut().last_open_file = Some(PathBuf::from(f0));
-            }
-
-            if let Err(ref err) = crate::app::load_file::load_multiple(&paths, &ac) {
-                show_error_dialog(&format!("Error loading file: {}", err), &win);
-            } else {
-                let da: Widget = ac.fetch_widget("skew_t").unwrap();
-                da.grab_focus();
-            }
-        }
-
-        match response {
-            ResponseType::DeleteEvent => {}
-            _x => dialog.close(),
-        }
-    });
+    dialog
+}
 
-    dialog.show();
+/// Get the paths of the files selected in a file dialog.
+fn selected_paths(dialog: &FileChooserDialog) -> Vec<PathBuf> {
+    dialog
+        .files()
+        .into_iter()
+        .filter_map(|pb| pb.ok())
+        .filter_map(|pb| pb.downcast::<gio::File>().ok())
+        .filter_map(|pb| pb.path())
+        .filter(|pb| pb.is_file())
+        .collect()
 }
 
 pub fn save_image_callback(ac: &AppContextPointer, win: &Window) {
@@ -254,7 +289,10 @@ pub fn load_theme(ac: &AppContextPointer, win: &Window) {
         if response == ResponseType::Ok {
             if let Some(filename) = dialog.file().and_then(|f| f.path()) {
                 match crate::load_config_from_file(&ac, &filename) {
-                    Ok(()) => {}
+                    Ok(()) => {
+                        AppContext::update_analysis_settings(&ac);
+                        crate::gui::update_custom_parcel_list(&ac);
+                    }
                     Err(err) => show_error_dialog(
                         &format!(
                             "Error loading theme {}: {}",
@@ -280,8 +318,12 @@ pub fn load_theme(ac: &AppContextPointer, win: &Window) {
 
 pub fn load_default_theme(ac: &AppContextPointer) {
     *ac.config.borrow_mut() = crate::app::config::Config::default();
+    ac.update_skew_t_geometry();
 
     ac.mark_background_dirty();
     ac.mark_data_dirty();
     ac.mark_data_dirty();
+
+    AppContext::update_analysis_settings(ac);
+    crate::gui::update_custom_parcel_list(ac);
 }
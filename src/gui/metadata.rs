@@ -0,0 +1,188 @@
+use crate::{
+    analysis::Analysis,
+    app::{config::Config, AppContext, AppContextPointer},
+    errors::SondeError,
+};
+use chrono::{Duration, NaiveDateTime};
+use gtk::{glib::translate::IntoGlib, prelude::*, EventControllerKey, Label, TextTag, TextView};
+use std::{fmt::Write, rc::Rc};
+
+const TEXT_AREA_ID: &str = "metadata_text";
+
+macro_rules! make_default_tag {
+    ($tb:ident, $acp:ident) => {
+        let tag_table = $tb.tag_table();
+        let config = $acp.config.borrow();
+        let font = &config.font_name;
+        let font_size = config.text_area_font_size_points;
+
+        let tag = TextTag::builder()
+            .name("default")
+            .family(font)
+            .size_points(font_size)
+            .weight(gtk::pango::Weight::Bold.into_glib())
+            .build();
+
+        let success = tag_table.add(&tag);
+        debug_assert!(success, "Failed to add tag to text tag table");
+    };
+}
+
+macro_rules! set_text {
+    ($tb:ident, $txt:expr) => {
+        $tb.set_text($txt);
+        let start = $tb.start_iter();
+        let end = $tb.end_iter();
+        $tb.apply_tag_by_name("default", &start, &end);
+    };
+}
+
+pub fn set_up_metadata_text_area(acp: &AppContextPointer) -> Result<(), SondeError> {
+    let text_area: TextView = acp.fetch_widget(TEXT_AREA_ID)?;
+
+    let key_press = EventControllerKey::new();
+    let ac = Rc::clone(acp);
+    key_press.connect_key_pressed(move |_key_press, key, _code, _key_modifier| {
+        crate::gui::key_press(key, &ac)
+    });
+    text_area.add_controller(key_press);
+
+    let tb = text_area.buffer();
+    make_default_tag!(tb, acp);
+    set_text!(tb, "No data loaded");
+
+    Ok(())
+}
+
+pub fn update_text_area(ac: &AppContext) {
+    let text_area: TextView = if let Ok(ta) = ac.fetch_widget(TEXT_AREA_ID) {
+        ta
+    } else {
+        return;
+    };
+
+    if let Some(anal) = ac.get_sounding_for_display() {
+        let tb = text_area.buffer();
+        let mut text = String::with_capacity(1024);
+
+        for (name, value) in describe(&anal.borrow(), &ac.config.borrow()) {
+            writeln!(text, "{:>12} : {}", name, value).unwrap();
+        }
+
+        set_text!(tb, &text);
+    }
+}
+
+/// Show the source and valid time of the displayed sounding in the window title.
+pub fn update_window_title(ac: &AppContext) {
+    let title: Label = if let Ok(title) = ac.fetch_widget("window-title") {
+        title
+    } else {
+        return;
+    };
+
+    let anal = match ac.get_sounding_for_display() {
+        Some(anal) => anal,
+        None => {
+            title.set_text("Sonde");
+            return;
+        }
+    };
+    let anal = anal.borrow();
+    let snd = anal.sounding();
+
+    let mut text = "Sonde".to_owned();
+    if let Some(src_desc) = snd.source_description() {
+        write!(text, " - {}", src_desc).unwrap();
+    }
+    if let Some(vt) = snd.valid_time() {
+        write!(text, " - {}", vt.format("%Y-%m-%d %HZ")).unwrap();
+    }
+    if let Some(lt) = snd.lead_time().into_option() {
+        write!(text, " F{:03}", lt).unwrap();
+    }
+
+    title.set_text(&text);
+}
+
+/// Name and value of everything known about where and when a sounding is from.
+fn describe(anal: &Analysis, config: &Config) -> Vec<(&'static str, String)> {
+    let snd = anal.sounding();
+    let info = snd.station_info();
+    let mut result = vec![];
+
+    let unknown = || "unknown".to_owned();
+
+    let station_id = info
+        .station_id()
+        .map(|id| id.to_string())
+        .or_else(|| snd.source_description().and_then(station_id_from_source))
+        .unwrap_or_else(unknown);
+    result.push(("Station", station_id));
+
+    let station_num = info
+        .station_num()
+        .into_option()
+        .map(|num| num.to_string())
+        .unwrap_or_else(unknown);
+    result.push(("Number", station_num));
+
+    let location = info.location();
+    if let Some((lat, lon)) = location {
+        result.push(("Location", format!("{:.3}, {:.3}", lat, lon)));
+    }
+
+    if let Some(elevation) = info.elevation().into_option() {
+        let units = config.height_units;
+        result.push((
+            "Elevation",
+            format!("{:.0}{}", units.value(elevation), units.label()),
+        ));
+    }
+
+    if let Some(src_desc) = snd.source_description() {
+        result.push(("Source", src_desc.to_owned()));
+    }
+    if anal.is_synthetic() {
+        result.push(("Synthetic", "interpolated, not from a data file".to_owned()));
+    }
+
+    let format = |time: NaiveDateTime| time.format("%a %Y-%m-%d %H:%MZ").to_string();
+
+    let valid_time = snd.valid_time();
+    let lead_time = snd.lead_time().into_option();
+
+    if let (Some(vt), Some(lt)) = (valid_time, lead_time) {
+        result.push(("Init", format(vt - Duration::hours(i64::from(lt)))));
+    }
+
+    if let Some(vt) = valid_time {
+        result.push(("Valid", format(vt)));
+    }
+
+    if let Some(lt) = lead_time {
+        result.push(("Lead", format!("F{:03}", lt)));
+    }
+
+    // Without a time zone database, local mean solar time is the best estimate of local time.
+    if let (Some(vt), Some((_, lon))) = (valid_time, location) {
+        let offset = Duration::minutes((lon * 4.0).round() as i64);
+        let local = (vt + offset).format("%a %Y-%m-%d %H:%M");
+        result.push(("Local", format!("{} (mean solar time)", local)));
+    }
+
+    result
+}
+
+/// Bufkit files are named after the model and station, e.g. `gfs3_kmso.buf`, so when the file
+/// doesn't say what station it is for the name usually does.
+fn station_id_from_source(src_desc: &str) -> Option<String> {
+    let stem = src_desc.trim_end_matches(".buf");
+    let id = stem.rsplit(['_', '/', '\\']).next()?;
+
+    let is_id = stem.len() > id.len()
+        && (3..=5).contains(&id.len())
+        && id.chars().all(|c| c.is_ascii_alphanumeric());
+
+    is_id.then(|| id.to_uppercase())
+}
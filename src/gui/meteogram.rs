@@ -0,0 +1,779 @@
+//! Plot values from the analysis of every loaded sounding against valid time.
+//!
+//! Each selected value gets its own panel, stacked top to bottom, and scaled to fit the range of
+//! that value across the series. The selected indexes come first, followed by any selected values
+//! from the provider analysis, and then a strip comparing the precipitation type algorithms.
+use crate::{
+    analysis::{provider, Analysis},
+    app::{
+        config::{Config, MeteogramIndex, Rgba},
+        AppContext, AppContextPointer, ZoomableDrawingAreas,
+    },
+    coords::{DeviceCoords, ScreenCoords, ScreenRect, XYCoords},
+    errors::SondeError,
+    gui::{
+        plot_context::{GenericContext, HasGenericContext, PlotContext, PlotContextExt},
+        sounding::{draw_precip_symbol, PRECIP_BOX_SIZE},
+        utility::{
+            closest_position, collect_time_labels, plot_curve_from_points, time_series_positions,
+        },
+        Drawable, DrawingArgs, MasterDrawable,
+    },
+};
+use gtk::{
+    gio::{Menu, MenuItem, SimpleAction, SimpleActionGroup},
+    glib::{Propagation, Variant, VariantTy},
+    prelude::*,
+    DrawingArea, EventControllerKey, EventControllerMotion, EventControllerScroll,
+    EventControllerScrollFlags, GestureClick, Window,
+};
+use metfor::{Mm, Quantity};
+use optional::Optioned;
+use sounding_analysis::PrecipType;
+use std::{cell::RefCell, collections::BTreeSet, rc::Rc};
+
+/// Space above and below the data in each panel, as a fraction of the panel height.
+const PANEL_PADDING: f64 = 0.1;
+
+impl MeteogramIndex {
+    /// Name used in the panel title.
+    pub(super) fn name(self) -> &'static str {
+        use MeteogramIndex::*;
+
+        match self {
+            SurfaceCape => "Surface CAPE",
+            MixedLayerCape => "Mixed Layer CAPE",
+            MostUnstableCape => "Most Unstable CAPE",
+            MixedLayerCin => "Mixed Layer CIN",
+            Dcape => "DCAPE",
+            PrecipitableWater => "PWAT",
+            Hdw => "HDW",
+            Pft => "PFT",
+            SrHelicitySurface => "Sfc SR Helicity (RM)",
+            SrHelicityEffective => "Eff SR Helicity (RM)",
+            Precip1Hour => "1-hour Precip",
+        }
+    }
+
+    pub(super) fn units(self, config: &Config) -> &'static str {
+        use MeteogramIndex::*;
+
+        match self {
+            SurfaceCape | MixedLayerCape | MostUnstableCape | MixedLayerCin | Dcape => "J/kg",
+            PrecipitableWater | Precip1Hour => config.precip_units.label(),
+            Hdw => "",
+            Pft => "GW",
+            SrHelicitySurface | SrHelicityEffective => "m\u{00b2}/s\u{00b2}",
+        }
+    }
+
+    /// Name of the action that toggles this index on the context menu.
+    fn action_name(self) -> &'static str {
+        use MeteogramIndex::*;
+
+        match self {
+            SurfaceCape => "show_sfc_cape",
+            MixedLayerCape => "show_ml_cape",
+            MostUnstableCape => "show_mu_cape",
+            MixedLayerCin => "show_ml_cin",
+            Dcape => "show_dcape",
+            PrecipitableWater => "show_pwat",
+            Hdw => "show_hdw",
+            Pft => "show_pft",
+            SrHelicitySurface => "show_srh_sfc",
+            SrHelicityEffective => "show_srh_eff",
+            Precip1Hour => "show_precip",
+        }
+    }
+
+    /// Number of decimal places worth showing.
+    pub(super) fn precision(self, config: &Config) -> usize {
+        use MeteogramIndex::*;
+
+        match self {
+            PrecipitableWater | Precip1Hour => config.precip_units.precision(),
+            _ => 0,
+        }
+    }
+
+    pub(super) fn value(self, anal: &Analysis, config: &Config) -> Option<f64> {
+        use MeteogramIndex::*;
+
+        fn scalar<T: Quantity + optional::Noned>(val: Optioned<T>) -> Option<f64> {
+            val.into_option().map(Quantity::unpack)
+        }
+
+        let precip = |val: Optioned<Mm>| val.map(|val| config.precip_units.value(val));
+
+        match self {
+            SurfaceCape => scalar(anal.surface_parcel_analysis()?.cape()),
+            MixedLayerCape => scalar(anal.mixed_layer_parcel_analysis()?.cape()),
+            MostUnstableCape => scalar(anal.most_unstable_parcel_analysis()?.cape()),
+            MixedLayerCin => scalar(anal.mixed_layer_parcel_analysis()?.cin()),
+            Dcape => scalar(anal.dcape()),
+            PrecipitableWater => precip(anal.pwat()),
+            Hdw => anal.hdw().into_option(),
+            Pft => anal.pft().map(|pft| pft.pft.unpack()),
+            SrHelicitySurface => scalar(anal.sr_helicity_sfc_rm()),
+            SrHelicityEffective => scalar(anal.sr_helicity_eff_rm()),
+            Precip1Hour => precip(anal.provider_1hr_precip()),
+        }
+    }
+}
+
+/// A value plotted in its own panel.
+enum Panel {
+    Index(MeteogramIndex),
+    Provider(String),
+}
+
+impl Panel {
+    /// All the selected panels, top to bottom.
+    fn selected(config: &Config) -> Vec<Panel> {
+        let indexes = config
+            .meteogram_indexes
+            .iter()
+            .map(|&idx| Panel::Index(idx));
+        let provider = config
+            .meteogram_provider_fields
+            .iter()
+            .map(|key| Panel::Provider(key.clone()));
+
+        indexes.chain(provider).collect()
+    }
+
+    fn title(&self, config: &Config) -> String {
+        let (name, units) = match self {
+            Panel::Index(index) => (index.name().to_owned(), index.units(config)),
+            Panel::Provider(key) => {
+                let field = provider::describe(key);
+                (field.description, field.units)
+            }
+        };
+
+        if units.is_empty() {
+            name
+        } else {
+            format!("{} ({})", name, units)
+        }
+    }
+
+    fn value(&self, anal: &Analysis, config: &Config) -> Option<f64> {
+        match self {
+            Panel::Index(index) => index.value(anal, config),
+            Panel::Provider(key) => anal.provider_analysis().get(key.as_str()).copied(),
+        }
+    }
+
+    /// Number of decimal places worth showing on the scale.
+    fn precision(&self, config: &Config, values: &[Option<f64>]) -> usize {
+        match self {
+            Panel::Index(index) => index.precision(config),
+            // The provider's units aren't known, so go by the size of the values instead.
+            Panel::Provider(_) => {
+                let biggest = values
+                    .iter()
+                    .flatten()
+                    .fold(0.0f64, |acc, v| acc.max(v.abs()));
+                if biggest < 1.0 {
+                    2
+                } else if biggest < 10.0 {
+                    1
+                } else {
+                    0
+                }
+            }
+        }
+    }
+}
+
+pub struct MeteogramContext {
+    generic: GenericContext,
+}
+
+impl MeteogramContext {
+    pub fn new() -> Self {
+        MeteogramContext {
+            generic: GenericContext::new(),
+        }
+    }
+
+    /// How much wider than tall the plot is, so the time axis fills the whole width.
+    fn stretch(&self) -> f64 {
+        let scale_factor = self.scale_factor();
+        if scale_factor > 0.0 {
+            self.get_device_rect().width / scale_factor
+        } else {
+            1.0
+        }
+    }
+
+    /// The number of panels selected, including the precipitation type strip.
+    fn num_panels(config: &Config) -> usize {
+        config.meteogram_indexes.len()
+            + config.meteogram_provider_fields.len()
+            + usize::from(config.meteogram_show_precip_type)
+    }
+
+    /// The bottom and top of the `i`th of `n` panels in `XYCoords`.
+    fn panel_bounds(i: usize, n: usize) -> (f64, f64) {
+        let height = 1.0 / n as f64;
+        let top = 1.0 - i as f64 * height;
+
+        (top - height, top)
+    }
+
+    fn draw_panel(
+        &self,
+        args: DrawingArgs<'_, '_>,
+        title: &str,
+        values: &[Option<f64>],
+        precision: usize,
+        xs: &[f64],
+        (bottom, top): (f64, f64),
+    ) {
+        let (ac, cr) = (args.ac, args.cr);
+        let config = ac.config.borrow();
+
+        // Always include zero so the panels are easy to compare at a glance.
+        let (lo, hi) = values
+            .iter()
+            .flatten()
+            .fold((0.0f64, 0.0f64), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+        let hi = if hi - lo < 1.0e-6 { lo + 1.0 } else { hi };
+
+        let pad = PANEL_PADDING * (top - bottom);
+        let to_y = |v: f64| bottom + pad + (v - lo) / (hi - lo) * (top - bottom - 2.0 * pad);
+
+        let rgba = config.meteogram_rgba;
+
+        // Break the line wherever a value is missing.
+        let mut run: Vec<ScreenCoords> = vec![];
+        for (&x, val) in xs.iter().zip(values) {
+            match val {
+                Some(v) => {
+                    let pnt = self.convert_xy_to_screen(XYCoords { x, y: to_y(*v) });
+                    run.push(pnt);
+                    Self::draw_point(pnt, rgba, args);
+                }
+                None => {
+                    plot_curve_from_points(cr, config.profile_line_width, rgba, run.drain(..));
+                }
+            }
+        }
+        plot_curve_from_points(cr, config.profile_line_width, rgba, run.into_iter());
+
+        // Label the panel and the extremes of its scale.
+        self.prepare_to_make_text(args);
+        self.draw_title(args, title, top);
+
+        let plot_area = self.get_plot_area();
+        let padding = cr
+            .device_to_user_distance(config.label_padding, 0.0)
+            .unwrap()
+            .0;
+
+        for v in [lo, hi] {
+            let label = format!("{:.*}", precision, v);
+            let height = cr.text_extents(&label).unwrap().height();
+            let ScreenCoords { y, .. } = self.convert_xy_to_screen(XYCoords { x: 0.0, y: to_y(v) });
+            let ScreenCoords { x: right, .. } = plot_area.upper_right;
+            let width = cr.text_extents(&label).unwrap().width();
+
+            self.draw_label(
+                args,
+                &label,
+                ScreenCoords {
+                    x: right - width - 2.0 * padding,
+                    y: y - height / 2.0,
+                },
+            );
+        }
+    }
+
+    /// Draw the weather symbol from each precipitation type algorithm at every valid time, one row
+    /// per algorithm, with the 1-hour precipitation amount below them.
+    fn draw_precip_type_strip(
+        &self,
+        args: DrawingArgs<'_, '_>,
+        anals: &[Rc<RefCell<Analysis>>],
+        xs: &[f64],
+        (bottom, top): (f64, f64),
+    ) {
+        let cr = args.cr;
+
+        // Provider, Bourgouin, and NSSL from top to bottom, then the precipitation amounts.
+        let row_height = (top - bottom) / 4.0;
+        let row_center = |row: usize| top - (row as f64 + 0.5) * row_height;
+        let to_screen = |x: f64, y: f64| self.convert_xy_to_screen(XYCoords { x, y });
+
+        // Shrink the symbols so they don't run into their neighbors.
+        let row_size = to_screen(0.0, row_height).y - to_screen(0.0, 0.0).y;
+        let col_size = xs
+            .windows(2)
+            .map(|w| to_screen(w[1], 0.0).x - to_screen(w[0], 0.0).x)
+            .fold(f64::INFINITY, f64::min);
+        let scale = (row_size.min(col_size) / (1.2 * PRECIP_BOX_SIZE)).min(1.0);
+
+        self.prepare_to_make_text(args);
+
+        let units = args.ac.config.borrow().precip_units;
+        for (&x, anal) in xs.iter().zip(anals) {
+            let anal = anal.borrow();
+
+            let codes = [
+                anal.provider_precip_type(),
+                anal.bourgouin_precip_type(),
+                anal.nssl_precip_type(),
+            ];
+
+            for (row, code) in codes.into_iter().enumerate() {
+                let code = match code {
+                    Some(code) if code != PrecipType::None => code,
+                    _ => continue,
+                };
+
+                let center = to_screen(x, row_center(row));
+                cr.save().unwrap();
+                cr.translate(center.x, center.y);
+                cr.scale(scale, scale);
+                cr.move_to(0.0, 0.0);
+                draw_precip_symbol(cr, code);
+                cr.restore().unwrap();
+            }
+
+            if let Some(precip) = anal
+                .provider_1hr_precip()
+                .into_option()
+                .filter(|precip| precip.unpack() > 0.0)
+            {
+                let label = format!("{:.*}", units.precision(), units.value(precip));
+                let extents = cr.text_extents(&label).unwrap();
+                let center = to_screen(x, row_center(3));
+
+                self.draw_label(
+                    args,
+                    &label,
+                    ScreenCoords {
+                        x: center.x - extents.width() / 2.0,
+                        y: center.y - extents.height() / 2.0,
+                    },
+                );
+            }
+        }
+
+        let title = format!(
+            "Precip type (Model, Bourgouin, NSSL) & 1-hour precip ({})",
+            units.label()
+        );
+        self.draw_title(args, &title, top);
+    }
+
+    /// Draw the title in the upper left corner of the panel with its top at `top`.
+    fn draw_title(&self, args: DrawingArgs<'_, '_>, title: &str, top: f64) {
+        let (cr, config) = (args.cr, args.ac.config.borrow());
+
+        let padding = cr
+            .device_to_user_distance(config.label_padding, 0.0)
+            .unwrap()
+            .0;
+        let left = self.get_plot_area().lower_left.x + 2.0 * padding;
+
+        let title_height = cr.text_extents(title).unwrap().height();
+        let ScreenCoords { y: top_y, .. } = self.convert_xy_to_screen(XYCoords { x: 0.0, y: top });
+        self.draw_label(
+            args,
+            title,
+            ScreenCoords {
+                x: left,
+                y: top_y - title_height - 2.0 * padding,
+            },
+        );
+    }
+
+    /// Draw text with its lower left corner at `lower_left` on a background colored box.
+    fn draw_label(&self, args: DrawingArgs<'_, '_>, label: &str, lower_left: ScreenCoords) {
+        let (cr, config) = (args.cr, args.ac.config.borrow());
+
+        let padding = cr
+            .device_to_user_distance(config.label_padding, 0.0)
+            .unwrap()
+            .0;
+        let extents = cr.text_extents(label).unwrap();
+
+        let rgba = config.background_rgba;
+        cr.set_source_rgba(rgba.0, rgba.1, rgba.2, rgba.3);
+        cr.rectangle(
+            lower_left.x - padding,
+            lower_left.y - padding,
+            extents.width() + 2.0 * padding,
+            extents.height() + 2.0 * padding,
+        );
+        cr.fill().unwrap();
+
+        let rgba = config.label_rgba;
+        cr.set_source_rgba(rgba.0, rgba.1, rgba.2, rgba.3);
+        cr.move_to(lower_left.x, lower_left.y);
+        cr.show_text(label).unwrap();
+    }
+}
+
+impl HasGenericContext for MeteogramContext {
+    fn get_generic_context(&self) -> &GenericContext {
+        &self.generic
+    }
+}
+
+impl PlotContextExt for MeteogramContext {
+    fn convert_xy_to_screen(&self, coords: XYCoords) -> ScreenCoords {
+        let translate = self.get_translate();
+
+        // Apply translation first
+        let x = coords.x - translate.x;
+        let y = coords.y - translate.y;
+
+        // Apply scaling, stretching the x-axis to fill the width
+        let x = self.get_zoom_factor() * x * self.stretch();
+        let y = self.get_zoom_factor() * y;
+        ScreenCoords { x, y }
+    }
+
+    fn convert_screen_to_xy(&self, coords: ScreenCoords) -> XYCoords {
+        let translate = self.get_translate();
+
+        let x = coords.x / self.stretch() / self.get_zoom_factor() + translate.x;
+        let y = coords.y / self.get_zoom_factor() + translate.y;
+        XYCoords { x, y }
+    }
+}
+
+impl Drawable for MeteogramContext {
+    /***********************************************************************************************
+     * Initialization
+     **********************************************************************************************/
+    fn set_up_drawing_area(acp: &AppContextPointer) -> Result<(), SondeError> {
+        let da: DrawingArea = acp.fetch_widget("meteogram_area")?;
+
+        // Set up the drawing function.
+        let ac = Rc::clone(acp);
+        da.set_draw_func(move |_da, cr, _width, _height| {
+            ac.meteogram.draw_callback(cr, &ac);
+        });
+
+        // Set up the scroll (or zoom in/out) callbacks.
+        let ac = Rc::clone(acp);
+        let scroll_control = EventControllerScroll::new(EventControllerScrollFlags::VERTICAL);
+        scroll_control.connect_scroll(move |_scroll_control, _dx, dy| {
+            ac.mark_background_dirty();
+            ac.meteogram.scroll_event(dy, &ac);
+
+            Propagation::Stop
+        });
+        da.add_controller(scroll_control);
+
+        // Set up the button clicks.
+        let left_mouse_button = GestureClick::builder().build();
+
+        let ac = Rc::clone(acp);
+        left_mouse_button.connect_pressed(move |_mouse_button, _n_pressed, x, y| {
+            ac.meteogram.left_button_press_event((x, y), &ac);
+        });
+
+        let ac = Rc::clone(acp);
+        left_mouse_button.connect_released(move |_mouse_button, _n_press, x, y| {
+            ac.meteogram.left_button_release_event((x, y), &ac);
+        });
+
+        da.add_controller(left_mouse_button);
+
+        let right_mouse_button = GestureClick::builder().button(3).build();
+        let ac = Rc::clone(acp);
+        right_mouse_button.connect_released(move |_mouse_button, _n_press, x, y| {
+            ac.meteogram.right_button_release_event((x, y), &ac);
+        });
+        da.add_controller(right_mouse_button);
+
+        // Set up the mouse motion events
+        let mouse_motion = EventControllerMotion::new();
+
+        let ac = Rc::clone(acp);
+        mouse_motion.connect_motion(move |mouse_motion, x, y| {
+            ac.meteogram.mouse_motion_event(mouse_motion, (x, y), &ac);
+        });
+
+        let ac = Rc::clone(acp);
+        mouse_motion.connect_enter(move |_mouse_motion, _x, _y| {
+            ac.meteogram.enter_event(&ac);
+        });
+
+        let ac = Rc::clone(acp);
+        mouse_motion.connect_leave(move |_mouse_motion| {
+            ac.meteogram.leave_event(&ac);
+        });
+
+        da.add_controller(mouse_motion);
+
+        // Set up the key presses.
+        let key_press = EventControllerKey::new();
+        let ac = Rc::clone(acp);
+        key_press.connect_key_pressed(move |_key_press, key, _code, _key_modifier| {
+            MeteogramContext::key_press_event(key, &ac)
+        });
+        da.add_controller(key_press);
+
+        let ac = Rc::clone(acp);
+        da.connect_resize(move |da, width, height| {
+            ac.meteogram.size_allocate_event(da);
+            ac.meteogram.resize_event(width, height, &ac);
+        });
+
+        build_meteogram_context_menu(acp)?;
+
+        Ok(())
+    }
+
+    /***********************************************************************************************
+     * Background Drawing.
+     **********************************************************************************************/
+    fn draw_background_lines(&self, args: DrawingArgs<'_, '_>) {
+        let (cr, config) = (args.cr, args.ac.config.borrow());
+
+        // Separate the panels.
+        let n = Self::num_panels(&config);
+        for i in 1..n {
+            let (_, y) = Self::panel_bounds(i, n);
+            let pnts = [XYCoords { x: 0.0, y }, XYCoords { x: 1.0, y }];
+            plot_curve_from_points(
+                cr,
+                config.background_line_width,
+                config.label_rgba,
+                pnts.iter().map(|&xy| self.convert_xy_to_screen(xy)),
+            );
+        }
+    }
+
+    fn collect_labels(&self, args: DrawingArgs<'_, '_>) -> Vec<(String, ScreenRect)> {
+        let mut labels = vec![];
+
+        let anals = args.ac.loaded_soundings();
+        let xs = time_series_positions(&anals);
+        collect_time_labels(self, args, &anals, &xs, &mut labels);
+
+        labels
+    }
+
+    fn build_legend_strings(ac: &AppContext) -> Vec<(String, Rgba)> {
+        vec![("Meteogram".to_owned(), ac.config.borrow().label_rgba)]
+    }
+
+    /***********************************************************************************************
+     * Data Drawing.
+     **********************************************************************************************/
+    fn draw_data(&self, args: DrawingArgs<'_, '_>) {
+        let ac = args.ac;
+
+        let (panels, num_panels, show_precip_type) = {
+            let config = ac.config.borrow();
+            (
+                Panel::selected(&config),
+                Self::num_panels(&config),
+                config.meteogram_show_precip_type,
+            )
+        };
+        let anals = ac.loaded_soundings();
+        if anals.is_empty() || num_panels == 0 {
+            self.set_has_data(false);
+            self.draw_no_data(args);
+            return;
+        }
+        self.set_has_data(true);
+
+        let xs = time_series_positions(&anals);
+
+        for (i, panel) in panels.iter().enumerate() {
+            let (values, precision, title) = {
+                let config = ac.config.borrow();
+                let values: Vec<Option<f64>> = anals
+                    .iter()
+                    .map(|anal| panel.value(&anal.borrow(), &config))
+                    .collect();
+                let precision = panel.precision(&config, &values);
+                (values, precision, panel.title(&config))
+            };
+
+            let bounds = Self::panel_bounds(i, num_panels);
+            self.draw_panel(args, &title, &values, precision, &xs, bounds);
+        }
+
+        // The precipitation type strip always goes on the bottom.
+        if show_precip_type {
+            let bounds = Self::panel_bounds(num_panels - 1, num_panels);
+            self.draw_precip_type_strip(args, &anals, &xs, bounds);
+        }
+
+        // Mark the displayed time.
+        if let Some(&x) = xs.get(ac.currently_displayed_index()) {
+            let config = ac.config.borrow();
+            let pnts = [XYCoords { x, y: 0.0 }, XYCoords { x, y: 1.0 }];
+            plot_curve_from_points(
+                args.cr,
+                config.active_readout_line_width,
+                config.active_readout_line_rgba,
+                pnts.iter().map(|&xy| self.convert_xy_to_screen(xy)),
+            );
+        }
+    }
+
+    /***********************************************************************************************
+     * Overlays Drawing.
+     **********************************************************************************************/
+    fn draw_active_readout(&self, _args: DrawingArgs<'_, '_>) {
+        // There is no vertical coordinate to sample.
+    }
+
+    fn draw_pinned_samples(&self, _args: DrawingArgs<'_, '_>) {
+        // There is no vertical coordinate to mark.
+    }
+
+    /***********************************************************************************************
+     * Events
+     **********************************************************************************************/
+    fn left_button_press_event(&self, position: (f64, f64), ac: &AppContextPointer) {
+        let position = DeviceCoords::from(position);
+        self.set_last_cursor_position(Some(position));
+        self.set_left_button_pressed(true);
+
+        // Clicking on a time displays that sounding everywhere else.
+        let x = self.convert_device_to_xy(position).x;
+        if let Some(index) = closest_position(&time_series_positions(&ac.loaded_soundings()), x) {
+            ac.display_sounding(index);
+        }
+    }
+
+    fn right_button_release_event(&self, _position: (f64, f64), ac: &AppContextPointer) {
+        update_provider_section(ac);
+
+        if let Ok(popover) = ac.fetch_widget::<gtk::PopoverMenu>("meteogram_popover") {
+            if let Some(pos) = self.get_last_cursor_position() {
+                let llx: i32 = pos.col as i32;
+                let lly: i32 = pos.row as i32;
+                let rect = gtk::gdk::Rectangle::new(llx, lly, 1, 1);
+                popover.set_pointing_to(Some(&rect));
+                popover.popup();
+            }
+        }
+    }
+
+    fn enter_event(&self, ac: &AppContextPointer) {
+        ac.set_last_focus(ZoomableDrawingAreas::Meteogram);
+    }
+}
+
+impl MasterDrawable for MeteogramContext {}
+
+/**************************************************************************************************
+ *                                   DrawingArea set up
+ **************************************************************************************************/
+fn build_meteogram_context_menu(acp: &AppContextPointer) -> Result<(), SondeError> {
+    let window: Window = acp.fetch_widget("main_window")?;
+
+    let meteogram_group = SimpleActionGroup::new();
+    window.insert_action_group("meteogram", Some(&meteogram_group));
+
+    let ac = Rc::clone(acp);
+    let action = SimpleAction::new("show_precip_type", None);
+    action.connect_activate(move |_action, _variant| {
+        // Nested scope for borrows.
+        {
+            let mut config = ac.config.borrow_mut();
+            config.meteogram_show_precip_type = !config.meteogram_show_precip_type;
+        }
+
+        ac.meteogram.mark_background_dirty();
+        crate::gui::draw_all(&ac);
+    });
+    meteogram_group.add_action(&action);
+
+    for index in MeteogramIndex::ALL {
+        let ac = Rc::clone(acp);
+        let action = SimpleAction::new(index.action_name(), None);
+        action.connect_activate(move |_action, _variant| {
+            // Nested scope for borrows.
+            {
+                let mut config = ac.config.borrow_mut();
+                let selected = &mut config.meteogram_indexes;
+
+                if let Some(pos) = selected.iter().position(|&idx| idx == index) {
+                    selected.remove(pos);
+                } else {
+                    selected.push(index);
+                    // Keep the panels in the same order no matter when they were added.
+                    selected.sort_by_key(|idx| MeteogramIndex::ALL.iter().position(|i| i == idx));
+                }
+            }
+
+            ac.meteogram.mark_background_dirty();
+            crate::gui::draw_all(&ac);
+        });
+        meteogram_group.add_action(&action);
+    }
+
+    let ac = Rc::clone(acp);
+    let action = SimpleAction::new("toggle_provider_field", Some(VariantTy::STRING));
+    action.connect_activate(move |_action, variant| {
+        let key = match variant.and_then(Variant::str) {
+            Some(key) => key,
+            None => return,
+        };
+
+        // Nested scope for borrows.
+        {
+            let mut config = ac.config.borrow_mut();
+            let selected = &mut config.meteogram_provider_fields;
+
+            if let Some(pos) = selected.iter().position(|k| k == key) {
+                selected.remove(pos);
+            } else {
+                selected.push(key.to_owned());
+                selected.sort();
+            }
+        }
+
+        ac.meteogram.mark_background_dirty();
+        crate::gui::draw_all(&ac);
+    });
+    meteogram_group.add_action(&action);
+
+    Ok(())
+}
+
+/// Fill the provider data section of the context menu with the keys available in the loaded data.
+fn update_provider_section(ac: &AppContext) {
+    let section: Menu = match ac.fetch_widget("meteogram_provider_section") {
+        Ok(section) => section,
+        Err(_) => return,
+    };
+    section.remove_all();
+
+    let keys: BTreeSet<&'static str> = ac
+        .loaded_soundings()
+        .iter()
+        .flat_map(|anal| {
+            anal.borrow()
+                .provider_analysis()
+                .keys()
+                .copied()
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    for key in keys {
+        let label = Panel::Provider(key.to_owned()).title(&ac.config.borrow());
+        let item = MenuItem::new(Some(&label), None);
+        item.set_action_and_target_value(
+            Some("meteogram.toggle_provider_field"),
+            Some(&key.to_variant()),
+        );
+        section.append_item(&item);
+    }
+}
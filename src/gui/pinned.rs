@@ -0,0 +1,201 @@
+use crate::{
+    analysis::Analysis,
+    app::{config::Config, AppContext, AppContextPointer},
+    errors::SondeError,
+};
+use gtk::{glib::translate::IntoGlib, prelude::*, EventControllerKey, TextTag, TextView};
+use metfor::{HectoPascal, Quantity};
+use sounding_analysis::{lift_parcel, linear_interpolate_sounding, Parcel};
+use std::{fmt::Write, rc::Rc};
+
+const TEXT_AREA_ID: &str = "pinned_text";
+
+const NO_PINNED_TEXT: &str = "No pinned samples, click on the skew-t to pin a level";
+
+macro_rules! make_default_tag {
+    ($tb:ident, $acp:ident) => {
+        let tag_table = $tb.tag_table();
+        let config = $acp.config.borrow();
+        let font = &config.font_name;
+        let font_size = config.text_area_font_size_points;
+
+        let tag = TextTag::builder()
+            .name("default")
+            .family(font)
+            .size_points(font_size)
+            .weight(gtk::pango::Weight::Bold.into_glib())
+            .build();
+
+        let success = tag_table.add(&tag);
+        debug_assert!(success, "Failed to add tag to text tag table");
+    };
+}
+
+macro_rules! set_text {
+    ($tb:ident, $txt:expr) => {
+        $tb.set_text($txt);
+        let start = $tb.start_iter();
+        let end = $tb.end_iter();
+        $tb.apply_tag_by_name("default", &start, &end);
+    };
+}
+
+/// Values sampled at a pinned level in one sounding.
+#[derive(Clone, Copy, Default)]
+struct LevelValues {
+    temperature: Option<f64>,
+    dew_point: Option<f64>,
+    rh: Option<f64>,
+    wind_direction: Option<f64>,
+    wind_speed: Option<f64>,
+    cape: Option<f64>,
+}
+
+impl LevelValues {
+    fn sample(anal: &Analysis, pressure: HectoPascal, config: &Config) -> Self {
+        let snd = anal.sounding();
+
+        let row = match linear_interpolate_sounding(snd, pressure) {
+            Ok(row) => row,
+            Err(_) => return Self::default(),
+        };
+
+        let t_units = config.temperature_units;
+        let w_units = config.wind_speed_units;
+
+        let t = row.temperature.into_option();
+        let dp = row.dew_point.into_option();
+        let wind = row.wind.into_option();
+
+        let cape = Parcel::from_datarow(row)
+            .and_then(|pcl| lift_parcel(pcl, snd).ok())
+            .and_then(|pcl_anal| pcl_anal.cape().into_option())
+            .map(|cape| cape.unpack());
+
+        LevelValues {
+            temperature: t.map(|t| t_units.value(t)),
+            dew_point: dp.map(|dp| t_units.value(dp)),
+            rh: t
+                .zip(dp)
+                .and_then(|(t, dp)| metfor::rh(t, dp))
+                .map(|rh| rh * 100.0),
+            wind_direction: wind.map(|wnd| wnd.direction),
+            wind_speed: wind.map(|wnd| w_units.value(wnd.speed)),
+            cape,
+        }
+    }
+}
+
+pub fn set_up_pinned_text_area(acp: &AppContextPointer) -> Result<(), SondeError> {
+    let text_area: TextView = acp.fetch_widget(TEXT_AREA_ID)?;
+
+    let key_press = EventControllerKey::new();
+    let ac = Rc::clone(acp);
+    key_press.connect_key_pressed(move |_key_press, key, _code, _key_modifier| {
+        crate::gui::key_press(key, &ac)
+    });
+    text_area.add_controller(key_press);
+
+    let tb = text_area.buffer();
+    make_default_tag!(tb, acp);
+    set_text!(tb, NO_PINNED_TEXT);
+
+    Ok(())
+}
+
+pub fn update_text_area(ac: &AppContext) {
+    let text_area: TextView = if let Ok(ta) = ac.fetch_widget(TEXT_AREA_ID) {
+        ta
+    } else {
+        return;
+    };
+    let tb = text_area.buffer();
+
+    let pinned = ac.pinned_samples();
+    let soundings = ac.loaded_soundings();
+    if pinned.is_empty() || soundings.is_empty() {
+        set_text!(tb, NO_PINNED_TEXT);
+        return;
+    }
+
+    let config = ac.config.borrow();
+    let displayed_idx = ac.currently_displayed_index();
+    let t_label = config.temperature_units.label();
+    let w_label = config.wind_speed_units.label();
+    let p_units = config.pressure_units;
+
+    let mut text = String::with_capacity(4096);
+
+    for pressure in pinned {
+        writeln!(
+            text,
+            "Pinned at {:.*} {}",
+            p_units.precision(),
+            p_units.value(pressure),
+            p_units.label()
+        )
+        .unwrap();
+        writeln!(
+            text,
+            "  {:<16} {:>7} {:>7} {:>5} {:>12} {:>6}",
+            "Valid Time",
+            format!("T {}", t_label),
+            format!("Td {}", t_label),
+            "RH %",
+            format!("Wind {}", w_label),
+            "CAPE"
+        )
+        .unwrap();
+
+        let series: Vec<LevelValues> = soundings
+            .iter()
+            .map(|anal| LevelValues::sample(&anal.borrow(), pressure, &config))
+            .collect();
+
+        for (idx, (anal, vals)) in soundings.iter().zip(&series).enumerate() {
+            let marker = if idx == displayed_idx { '>' } else { ' ' };
+            let valid_time = anal
+                .borrow()
+                .sounding()
+                .valid_time()
+                .map(|vt| vt.format("%Y-%m-%d %H%MZ").to_string())
+                .unwrap_or_else(|| "unknown".to_owned());
+
+            write!(text, "{} {:<16}", marker, valid_time).unwrap();
+            write_value(&mut text, vals.temperature, 7, 1);
+            write_value(&mut text, vals.dew_point, 7, 1);
+            write_value(&mut text, vals.rh, 5, 0);
+            match (vals.wind_direction, vals.wind_speed) {
+                (Some(dir), Some(spd)) => write!(text, "      {:03.0}/{:>3.0}", dir, spd).unwrap(),
+                _ => write!(text, " {:>12}", "-").unwrap(),
+            }
+            write_value(&mut text, vals.cape, 6, 0);
+            writeln!(text).unwrap();
+        }
+
+        // How much the level changed from the first to the last time.
+        if series.len() > 1 {
+            let (first, last) = (series[0], series[series.len() - 1]);
+            let change = |a: Option<f64>, b: Option<f64>| a.zip(b).map(|(a, b)| b - a);
+
+            write!(text, "  {:<16}", "Change").unwrap();
+            write_value(&mut text, change(first.temperature, last.temperature), 7, 1);
+            write_value(&mut text, change(first.dew_point, last.dew_point), 7, 1);
+            write_value(&mut text, change(first.rh, last.rh), 5, 0);
+            write_value(&mut text, change(first.wind_speed, last.wind_speed), 12, 0);
+            write_value(&mut text, change(first.cape, last.cape), 6, 0);
+            writeln!(text).unwrap();
+        }
+
+        writeln!(text).unwrap();
+    }
+
+    set_text!(tb, &text);
+}
+
+fn write_value(text: &mut String, val: Option<f64>, width: usize, precision: usize) {
+    match val {
+        Some(val) => write!(text, " {:>width$.precision$}", val).unwrap(),
+        None => write!(text, " {:>width$}", "-").unwrap(),
+    }
+}
@@ -6,15 +6,22 @@ use crate::{
 use gtk::{prelude::*, DrawingArea};
 
 pub mod cloud;
+pub mod difference;
 pub mod rh_omega;
 pub mod wind_speed;
 
 pub use self::cloud::CloudContext;
+pub use self::difference::DifferenceContext;
 pub use self::rh_omega::RHOmegaContext;
 pub use self::wind_speed::WindSpeedContext;
 
 pub fn draw_profiles(acp: &AppContext) {
-    const DRAWING_AREAS: [&str; 3] = ["rh_omega_area", "cloud_area", "wind_speed_area"];
+    const DRAWING_AREAS: [&str; 4] = [
+        "rh_omega_area",
+        "cloud_area",
+        "wind_speed_area",
+        "difference_area",
+    ];
 
     for &da in DRAWING_AREAS.iter() {
         if let Ok(da) = acp.fetch_widget::<DrawingArea>(da) {
@@ -27,6 +34,7 @@ pub fn initialize_profiles(acp: &AppContextPointer) -> Result<(), SondeError> {
     RHOmegaContext::set_up_drawing_area(acp)?;
     CloudContext::set_up_drawing_area(acp)?;
     WindSpeedContext::set_up_drawing_area(acp)?;
+    DifferenceContext::set_up_drawing_area(acp)?;
 
     Ok(())
 }
@@ -7,13 +7,14 @@ use crate::{
     },
     coords::{
         convert_pressure_to_y, convert_y_to_pressure, DeviceCoords, PPCoords, ScreenCoords,
-        ScreenRect, XYCoords,
+        ScreenRect, SkewTGeometry, XYCoords,
     },
     errors::SondeError,
     gui::{
         plot_context::{GenericContext, HasGenericContext, PlotContext, PlotContextExt},
         utility::{
-            check_overlap_then_add, draw_horizontal_bars, plot_curve_from_points, DrawingArgs,
+            check_overlap_then_add, collect_height_labels, draw_horizontal_bars,
+            plot_curve_from_points, DrawingArgs,
         },
         Drawable, SlaveProfileDrawable,
     },
@@ -33,34 +34,34 @@ impl CloudContext {
         }
     }
 
-    pub fn convert_pp_to_xy(coords: PPCoords) -> XYCoords {
-        let y = convert_pressure_to_y(coords.press);
+    pub fn convert_pp_to_xy(coords: PPCoords, geometry: SkewTGeometry) -> XYCoords {
+        let y = convert_pressure_to_y(coords.press, geometry);
 
         let x = coords.pcnt;
 
         XYCoords { x, y }
     }
 
-    pub fn convert_xy_to_pp(coords: XYCoords) -> PPCoords {
-        let press = convert_y_to_pressure(coords.y);
+    pub fn convert_xy_to_pp(coords: XYCoords, geometry: SkewTGeometry) -> PPCoords {
+        let press = convert_y_to_pressure(coords.y, geometry);
         let pcnt = coords.x;
 
         PPCoords { pcnt, press }
     }
 
-    pub fn convert_pp_to_screen(&self, coords: PPCoords) -> ScreenCoords {
-        let xy = CloudContext::convert_pp_to_xy(coords);
+    pub fn convert_pp_to_screen(&self, coords: PPCoords, geometry: SkewTGeometry) -> ScreenCoords {
+        let xy = CloudContext::convert_pp_to_xy(coords, geometry);
         self.convert_xy_to_screen(xy)
     }
 
-    pub fn convert_screen_to_pp(&self, coords: ScreenCoords) -> PPCoords {
+    pub fn convert_screen_to_pp(&self, coords: ScreenCoords, geometry: SkewTGeometry) -> PPCoords {
         let xy = self.convert_screen_to_xy(coords);
-        CloudContext::convert_xy_to_pp(xy)
+        CloudContext::convert_xy_to_pp(xy, geometry)
     }
 
-    pub fn convert_device_to_pp(&self, coords: DeviceCoords) -> PPCoords {
+    pub fn convert_device_to_pp(&self, coords: DeviceCoords, geometry: SkewTGeometry) -> PPCoords {
         let xy = self.convert_device_to_xy(coords);
-        Self::convert_xy_to_pp(xy)
+        Self::convert_xy_to_pp(xy, geometry)
     }
 }
 
@@ -199,13 +200,14 @@ impl Drawable for CloudContext {
      * Background Drawing.
      **********************************************************************************************/
     fn draw_background_fill(&self, args: DrawingArgs<'_, '_>) {
-        let (cr, config) = (args.cr, args.ac.config.borrow());
+        let (ac, cr, config) = (args.ac, args.cr, args.ac.config.borrow());
 
         if config.show_background_bands {
             let rgba = config.background_band_rgba;
             cr.set_source_rgba(rgba.0, rgba.1, rgba.2, rgba.3);
 
-            let mut lines = config::CLOUD_PERCENT_PNTS.iter();
+            let background = ac.skew_t.background();
+            let mut lines = background.cloud_percent_pnts.iter();
             let mut draw = true;
             let mut prev = lines.next();
             while let Some(prev_val) = prev {
@@ -229,11 +231,12 @@ impl Drawable for CloudContext {
     }
 
     fn draw_background_lines(&self, args: DrawingArgs<'_, '_>) {
-        let (cr, config) = (args.cr, args.ac.config.borrow());
+        let (ac, cr, config) = (args.ac, args.cr, args.ac.config.borrow());
+        let background = ac.skew_t.background();
 
         // Draw isobars
         if config.show_isobars {
-            for pnts in config::ISOBAR_PNTS.iter() {
+            for pnts in background.profile_isobar_pnts.iter() {
                 let pnts = pnts
                     .iter()
                     .map(|xy_coords| self.convert_xy_to_screen(*xy_coords));
@@ -242,7 +245,7 @@ impl Drawable for CloudContext {
         }
 
         // Draw percent values
-        for line in config::CLOUD_PERCENT_PNTS.iter() {
+        for line in background.cloud_percent_pnts.iter() {
             let pnts = line
                 .iter()
                 .map(|xy_coord| self.convert_xy_to_screen(*xy_coord));
@@ -261,11 +264,12 @@ impl Drawable for CloudContext {
 
         let screen_edges = self.calculate_plot_edges(cr, ac);
         let ScreenRect { lower_left, .. } = screen_edges;
+        let geometry = ac.skew_t.geometry();
 
         let PPCoords {
             press: screen_max_p,
             ..
-        } = self.convert_screen_to_pp(lower_left);
+        } = self.convert_screen_to_pp(lower_left, geometry);
 
         for pcnt in &config::PERCENTS {
             let label = format!("{:.0}%", *pcnt);
@@ -275,10 +279,13 @@ impl Drawable for CloudContext {
             let ScreenCoords {
                 x: mut xpos,
                 y: mut ypos,
-            } = ac.cloud.convert_pp_to_screen(PPCoords {
-                pcnt: *pcnt / 100.0,
-                press: screen_max_p,
-            });
+            } = ac.cloud.convert_pp_to_screen(
+                PPCoords {
+                    pcnt: *pcnt / 100.0,
+                    press: screen_max_p,
+                },
+                geometry,
+            );
             xpos -= extents.width() / 2.0; // Center
             ypos -= extents.height() / 2.0; // Center
             ypos += extents.height(); // Move up off bottom axis.
@@ -308,6 +315,8 @@ impl Drawable for CloudContext {
             check_overlap_then_add(cr, ac, &mut labels, &screen_edges, pair);
         }
 
+        collect_height_labels(self, args, &mut labels);
+
         labels
     }
 
@@ -358,7 +367,7 @@ impl Drawable for CloudContext {
         let position = DeviceCoords::from(new_position);
 
         if ac.plottable() && self.has_data() {
-            let pp_position = self.convert_device_to_pp(position);
+            let pp_position = self.convert_device_to_pp(position, ac.skew_t.geometry());
 
             let sample = ac
                 .get_sounding_for_display()
@@ -402,6 +411,7 @@ fn draw_cloud_profile(args: DrawingArgs<'_, '_>) {
         let anal = anal.borrow();
         let sndg = anal.sounding();
 
+        let geometry = ac.skew_t.geometry();
         let pres_data = sndg.pressure_profile();
         let c_data = sndg.cloud_fraction_profile();
 
@@ -415,13 +425,16 @@ fn draw_cloud_profile(args: DrawingArgs<'_, '_>) {
             // Filter out levels with missing data
             .filter_map(|(p, cld)| p.into_option().and_then(|p| cld.map(|cld| (p, cld))))
             // Only take up to the highest plottable pressu
-            .take_while(|(p, _)| *p > config::MINP)
+            .take_while(|(p, _)| *p > geometry.min_p)
             // Map into ScreenCoords for plotting
             .map(|(press, cld)| {
-                ac.cloud.convert_pp_to_screen(PPCoords {
-                    pcnt: cld / 100.0,
-                    press,
-                })
+                ac.cloud.convert_pp_to_screen(
+                    PPCoords {
+                        pcnt: cld / 100.0,
+                        press,
+                    },
+                    geometry,
+                )
             });
 
         let line_width = config.bar_graph_line_width;
@@ -0,0 +1,525 @@
+use crate::{
+    analysis::difference::{self, DifferenceProfile, LayerBias},
+    app::{
+        config::{Config, Rgba},
+        sample::{create_sample_sounding, Sample},
+        AppContext, AppContextPointer,
+    },
+    coords::{
+        convert_pressure_to_y, convert_y_to_pressure, DeviceCoords, ScreenCoords, ScreenRect,
+        SkewTGeometry, XYCoords,
+    },
+    errors::SondeError,
+    gui::{
+        plot_context::{GenericContext, HasGenericContext, PlotContext, PlotContextExt},
+        utility::{
+            check_overlap_then_add, collect_height_labels, plot_curve_from_points,
+            plot_dashed_curve_from_points, DrawingArgs,
+        },
+        Drawable, SlaveProfileDrawable,
+    },
+};
+use gtk::{prelude::*, DrawingArea, EventControllerKey, EventControllerMotion, GestureClick};
+use metfor::{HectoPascal, Quantity};
+use std::rc::Rc;
+
+/// Largest difference shown, in either direction, in Celsius or knots.
+const MAX_DIFFERENCE: f64 = 15.0;
+
+/// Differences to draw background lines at.
+const DIFFERENCE_LINES: [f64; 7] = [-15.0, -10.0, -5.0, 0.0, 5.0, 10.0, 15.0];
+
+pub struct DifferenceContext {
+    generic: GenericContext,
+}
+
+impl DifferenceContext {
+    pub fn new() -> Self {
+        DifferenceContext {
+            generic: GenericContext::new(),
+        }
+    }
+
+    /// Convert a difference and pressure to `XYCoords`, clamping the difference to the plot.
+    pub fn convert_dp_to_xy(diff: f64, press: HectoPascal, geometry: SkewTGeometry) -> XYCoords {
+        let y = convert_pressure_to_y(press, geometry);
+        let x =
+            (diff.clamp(-MAX_DIFFERENCE, MAX_DIFFERENCE) + MAX_DIFFERENCE) / (2.0 * MAX_DIFFERENCE);
+
+        XYCoords { x, y }
+    }
+
+    pub fn convert_dp_to_screen(
+        &self,
+        diff: f64,
+        press: HectoPascal,
+        geometry: SkewTGeometry,
+    ) -> ScreenCoords {
+        let xy = Self::convert_dp_to_xy(diff, press, geometry);
+        self.convert_xy_to_screen(xy)
+    }
+
+    pub fn convert_device_to_pressure(
+        &self,
+        coords: DeviceCoords,
+        geometry: SkewTGeometry,
+    ) -> HectoPascal {
+        let xy = self.convert_device_to_xy(coords);
+        convert_y_to_pressure(xy.y, geometry)
+    }
+}
+
+impl HasGenericContext for DifferenceContext {
+    fn get_generic_context(&self) -> &GenericContext {
+        &self.generic
+    }
+}
+
+impl PlotContextExt for DifferenceContext {
+    fn bound_view(&self) {
+        let device_rect = self.get_device_rect();
+
+        let bounds = DeviceCoords {
+            col: device_rect.width,
+            row: device_rect.height,
+        };
+        let lower_right = self.convert_device_to_xy(bounds);
+        let upper_left = self.convert_device_to_xy(device_rect.upper_left);
+        let height = upper_left.y - lower_right.y;
+
+        let mut translate = self.get_translate();
+        if height < 1.0 {
+            if translate.y < 0.0 {
+                translate.y = 0.0;
+            }
+            let max_y = 1.0 - height;
+            if translate.y > max_y {
+                translate.y = max_y;
+            }
+        } else {
+            translate.y = -(height - 1.0) / 2.0;
+        }
+        self.set_translate(translate);
+    }
+
+    fn convert_xy_to_screen(&self, coords: XYCoords) -> ScreenCoords {
+        // Apply translation first
+        let x = coords.x;
+        let y = coords.y - self.get_translate().y;
+
+        // Apply scaling
+        let y = y * self.get_zoom_factor();
+
+        ScreenCoords { x, y }
+    }
+
+    fn convert_screen_to_xy(&self, coords: ScreenCoords) -> XYCoords {
+        // Unapply scaling first
+        let x = coords.x;
+        let y = coords.y / self.get_zoom_factor();
+
+        // Unapply translation
+        let y = y + self.get_translate().y;
+
+        XYCoords { x, y }
+    }
+}
+
+impl Drawable for DifferenceContext {
+    /***********************************************************************************************
+     * Initialization
+     **********************************************************************************************/
+    fn set_up_drawing_area(acp: &AppContextPointer) -> Result<(), SondeError> {
+        let da: DrawingArea = acp.fetch_widget("difference_area")?;
+
+        // Set up the drawing function.
+        let ac = Rc::clone(acp);
+        da.set_draw_func(move |_da, cr, _width, _height| {
+            ac.difference.draw_callback(cr, &ac);
+        });
+
+        // Set up the button clicks.
+        let left_mouse_button = GestureClick::builder().build();
+
+        let ac = Rc::clone(acp);
+        left_mouse_button.connect_pressed(move |_mouse_button, _n_pressed, x, y| {
+            ac.difference.left_button_press_event((x, y), &ac);
+        });
+
+        let ac = Rc::clone(acp);
+        left_mouse_button.connect_released(move |_mouse_button, _n_press, x, y| {
+            ac.difference.left_button_release_event((x, y), &ac);
+        });
+
+        da.add_controller(left_mouse_button);
+
+        let right_mouse_button = GestureClick::builder().button(3).build();
+        let ac = Rc::clone(acp);
+        right_mouse_button.connect_released(move |_mouse_button, _n_press, x, y| {
+            ac.difference.right_button_release_event((x, y), &ac);
+        });
+        da.add_controller(right_mouse_button);
+
+        // Set up the mouse motion events
+        let mouse_motion = EventControllerMotion::new();
+
+        let ac = Rc::clone(acp);
+        mouse_motion.connect_motion(move |mouse_motion, x, y| {
+            ac.difference.mouse_motion_event(mouse_motion, (x, y), &ac);
+        });
+
+        let ac = Rc::clone(acp);
+        mouse_motion.connect_enter(move |_mouse_motion, _x, _y| {
+            ac.difference.enter_event(&ac);
+        });
+
+        let ac = Rc::clone(acp);
+        mouse_motion.connect_leave(move |_mouse_motion| {
+            ac.difference.leave_event(&ac);
+        });
+
+        da.add_controller(mouse_motion);
+
+        // Set up the key presses.
+        let key_press = EventControllerKey::new();
+        let ac = Rc::clone(acp);
+        key_press.connect_key_pressed(move |_key_press, key, _code, _key_modifier| {
+            DifferenceContext::key_press_event(key, &ac)
+        });
+        da.add_controller(key_press);
+
+        let ac = Rc::clone(acp);
+        da.connect_resize(move |da, width, height| {
+            ac.difference.size_allocate_event(da);
+            ac.difference.resize_event(width, height, &ac);
+        });
+
+        Ok(())
+    }
+
+    /***********************************************************************************************
+     * Background Drawing.
+     **********************************************************************************************/
+    fn draw_background_lines(&self, args: DrawingArgs<'_, '_>) {
+        let (ac, cr, config) = (args.ac, args.cr, args.ac.config.borrow());
+        let geometry = ac.skew_t.geometry();
+
+        // Draw isobars
+        if config.show_isobars {
+            for pnts in ac.skew_t.background().profile_isobar_pnts.iter() {
+                let pnts = pnts
+                    .iter()
+                    .map(|xy_coords| self.convert_xy_to_screen(*xy_coords));
+                plot_curve_from_points(cr, config.background_line_width, config.isobar_rgba, pnts);
+            }
+        }
+
+        let SkewTGeometry { max_p, min_p, .. } = geometry;
+        for &diff in DIFFERENCE_LINES.iter() {
+            let pnts = [max_p, min_p]
+                .into_iter()
+                .map(|p| self.convert_dp_to_screen(diff, p, geometry));
+
+            // Make the zero line stand out.
+            let line_width = if diff == 0.0 {
+                2.0 * config.background_line_width
+            } else {
+                config.background_line_width
+            };
+
+            plot_curve_from_points(cr, line_width, config.isobar_rgba, pnts);
+        }
+    }
+
+    fn build_legend_strings(ac: &AppContext) -> Vec<(String, Rgba)> {
+        let config = ac.config.borrow();
+
+        vec![
+            ("\u{0394}T".to_owned(), config.temperature_rgba),
+            ("\u{0394}Td".to_owned(), config.dew_point_rgba),
+            ("|\u{0394}V|".to_owned(), config.wind_rgba),
+        ]
+    }
+
+    fn collect_labels(&self, args: DrawingArgs<'_, '_>) -> Vec<(String, ScreenRect)> {
+        let (ac, cr) = (args.ac, args.cr);
+
+        let mut labels = vec![];
+
+        let screen_edges = self.calculate_plot_edges(cr, ac);
+        let ScreenRect { lower_left, .. } = screen_edges;
+
+        let geometry = ac.skew_t.geometry();
+        let screen_max_p = convert_y_to_pressure(self.convert_screen_to_xy(lower_left).y, geometry);
+
+        for &diff in DIFFERENCE_LINES.iter() {
+            let label = format!("{:+.0}", diff);
+
+            let extents = cr.text_extents(&label).unwrap();
+
+            let ScreenCoords {
+                x: mut xpos,
+                y: mut ypos,
+            } = self.convert_dp_to_screen(diff, screen_max_p, geometry);
+            xpos -= extents.width() / 2.0; // Center
+            ypos -= extents.height() / 2.0; // Center
+            ypos += extents.height(); // Move up off bottom axis.
+
+            let ScreenRect {
+                lower_left: ScreenCoords { x: xmin, .. },
+                upper_right: ScreenCoords { x: xmax, .. },
+            } = screen_edges;
+
+            if xpos < xmin || xpos + extents.width() > xmax {
+                continue;
+            }
+
+            let label_lower_left = ScreenCoords { x: xpos, y: ypos };
+            let label_upper_right = ScreenCoords {
+                x: xpos + extents.width(),
+                y: ypos + extents.height(),
+            };
+
+            let pair = (
+                label,
+                ScreenRect {
+                    lower_left: label_lower_left,
+                    upper_right: label_upper_right,
+                },
+            );
+            check_overlap_then_add(cr, ac, &mut labels, &screen_edges, pair);
+        }
+
+        collect_height_labels(self, args, &mut labels);
+
+        labels
+    }
+
+    /***********************************************************************************************
+     * Data Drawing.
+     **********************************************************************************************/
+    fn draw_data(&self, args: DrawingArgs<'_, '_>) {
+        let (ac, cr) = (args.ac, args.cr);
+        let config = ac.config.borrow();
+
+        let diff = displayed_difference(ac);
+        if diff.pressure.is_empty() {
+            self.set_has_data(false);
+            self.draw_no_data(args);
+            return;
+        }
+        self.set_has_data(true);
+
+        let biases = difference::layer_biases(&diff);
+        let geometry = ac.skew_t.geometry();
+
+        // Plot the size of the vector wind difference.
+        let wind_speed: Vec<Option<f64>> = diff
+            .wind
+            .iter()
+            .map(|wind| wind.map(|wind| wind.speed.unpack()))
+            .collect();
+
+        let profiles = [
+            (&diff.temperature, config.temperature_rgba),
+            (&diff.dew_point, config.dew_point_rgba),
+            (&wind_speed, config.wind_rgba),
+        ];
+
+        for (values, rgba) in profiles {
+            let pnts = diff
+                .pressure
+                .iter()
+                .zip(values)
+                .filter(|(p, _)| **p > geometry.min_p)
+                .filter_map(|(&p, val)| val.map(|val| self.convert_dp_to_screen(val, p, geometry)));
+
+            plot_curve_from_points(cr, config.profile_line_width, rgba, pnts);
+        }
+
+        // Show the layer mean biases as a dashed step behind each profile.
+        for bias in &biases {
+            let means = [
+                (bias.temperature, config.temperature_rgba),
+                (bias.dew_point, config.dew_point_rgba),
+                (bias.wind.map(|wind| wind.speed.unpack()), config.wind_rgba),
+            ];
+
+            for (mean, rgba) in means
+                .into_iter()
+                .filter_map(|(mean, rgba)| mean.map(|mean| (mean, rgba)))
+            {
+                let pnts = [bias.bottom, bias.top]
+                    .into_iter()
+                    .map(|p| self.convert_dp_to_screen(mean, p, geometry));
+
+                plot_dashed_curve_from_points(cr, config.profile_line_width, rgba, pnts);
+            }
+        }
+    }
+
+    /***********************************************************************************************
+     * Overlays Drawing.
+     **********************************************************************************************/
+    fn create_active_readout_text(vals: &Sample, ac: &AppContext) -> Vec<(String, Rgba)> {
+        let mut results = vec![];
+
+        let (data, reference) = match (vals, ac.difference_reference()) {
+            (Sample::Sounding { data, .. }, Some(reference)) => (data, reference),
+            _ => return results,
+        };
+
+        let pressure = match data.pressure.into_option() {
+            Some(pressure) => pressure,
+            None => return results,
+        };
+
+        let ref_data = match sounding_analysis::linear_interpolate_sounding(
+            reference.borrow().sounding(),
+            pressure,
+        ) {
+            Ok(ref_data) => ref_data,
+            Err(_) => return results,
+        };
+
+        let config = ac.config.borrow();
+
+        let (t_units, w_units) = (config.temperature_units, config.wind_speed_units);
+
+        let (temperature, dew_point, wind) = difference::level_difference(data, &ref_data);
+        if let Some(temperature) = temperature {
+            let line = format!(
+                "\u{0394}T {:+.1}{}\n",
+                t_units.difference(temperature),
+                t_units.label()
+            );
+            results.push((line, config.temperature_rgba));
+        }
+        if let Some(dew_point) = dew_point {
+            let line = format!(
+                "\u{0394}Td {:+.1}{}\n",
+                t_units.difference(dew_point),
+                t_units.label()
+            );
+            results.push((line, config.dew_point_rgba));
+        }
+        if let Some(wind) = wind {
+            let line = format!(
+                "\u{0394}V {:03.0} {:02.0}{}\n",
+                wind.direction,
+                w_units.value(wind.speed).round(),
+                w_units.label()
+            );
+            results.push((line, config.wind_rgba));
+        }
+
+        // Add the biases of the layer the sample is in.
+        let diff = displayed_difference(ac);
+        if let Some(bias) = difference::layer_biases(&diff)
+            .into_iter()
+            .find(|bias| pressure <= bias.bottom && pressure >= bias.top)
+        {
+            results.push((layer_bias_text(&bias, &config), config.label_rgba));
+        }
+
+        results
+    }
+
+    /***********************************************************************************************
+     * Events
+     **********************************************************************************************/
+    fn mouse_motion_event(
+        &self,
+        controller: &EventControllerMotion,
+        new_position: (f64, f64),
+        ac: &AppContextPointer,
+    ) {
+        let da: DrawingArea = controller.widget().unwrap().downcast().unwrap();
+        da.grab_focus();
+
+        let position = DeviceCoords::from(new_position);
+
+        if ac.plottable() && self.has_data() {
+            let pressure = self.convert_device_to_pressure(position, ac.skew_t.geometry());
+
+            let sample = ac
+                .get_sounding_for_display()
+                .and_then(|anal| {
+                    sounding_analysis::linear_interpolate_sounding(
+                        anal.borrow().sounding(),
+                        pressure,
+                    )
+                    .ok()
+                    .map(|data| create_sample_sounding(data, &anal.borrow()))
+                })
+                .unwrap_or(Sample::None);
+            ac.set_sample(sample);
+            ac.mark_overlay_dirty();
+            crate::gui::draw_all(ac);
+            crate::gui::text_area::update_text_highlight(ac);
+        }
+
+        self.set_last_cursor_position(Some(position));
+    }
+}
+
+impl SlaveProfileDrawable for DifferenceContext {
+    fn get_master_zoom(&self, acp: &AppContextPointer) -> f64 {
+        acp.skew_t.get_zoom_factor()
+    }
+
+    fn set_translate_y(&self, new_translate: XYCoords) {
+        let mut translate = self.get_translate();
+        translate.y = new_translate.y;
+        self.set_translate(translate);
+    }
+}
+
+/// The difference between the displayed sounding and the reference, empty if there isn't one.
+fn displayed_difference(ac: &AppContext) -> DifferenceProfile {
+    match (ac.get_sounding_for_display(), ac.difference_reference()) {
+        (Some(anal), Some(reference)) => {
+            difference::difference(anal.borrow().sounding(), reference.borrow().sounding())
+        }
+        _ => DifferenceProfile::default(),
+    }
+}
+
+fn layer_bias_text(bias: &LayerBias, config: &Config) -> String {
+    let (p_units, t_units) = (config.pressure_units, config.temperature_units);
+    let w_units = config.wind_speed_units;
+
+    let mut text = format!(
+        "{:.*}-{:.*} {} mean\n",
+        p_units.precision(),
+        p_units.value(bias.bottom),
+        p_units.precision(),
+        p_units.value(bias.top),
+        p_units.label()
+    );
+
+    if let Some(temperature) = bias.temperature {
+        text.push_str(&format!(
+            " \u{0394}T {:+.1}{}\n",
+            t_units.difference(temperature),
+            t_units.label()
+        ));
+    }
+    if let Some(dew_point) = bias.dew_point {
+        text.push_str(&format!(
+            " \u{0394}Td {:+.1}{}\n",
+            t_units.difference(dew_point),
+            t_units.label()
+        ));
+    }
+    if let Some(wind) = bias.wind {
+        text.push_str(&format!(
+            " \u{0394}V {:03.0} {:02.0}{}\n",
+            wind.direction,
+            w_units.value(wind.speed).round(),
+            w_units.label()
+        ));
+    }
+
+    text
+}
@@ -7,12 +7,15 @@ use crate::{
     },
     coords::{
         convert_pressure_to_y, convert_y_to_pressure, DeviceCoords, Rect, ScreenCoords, ScreenRect,
-        WPCoords, XYCoords,
+        SkewTGeometry, WPCoords, XYCoords,
     },
     errors::SondeError,
     gui::{
         plot_context::{GenericContext, HasGenericContext, PlotContext, PlotContextExt},
-        utility::{check_overlap_then_add, draw_horizontal_bars, plot_curve_from_points},
+        utility::{
+            check_overlap_then_add, collect_height_labels, draw_horizontal_bars,
+            plot_curve_from_points,
+        },
         Drawable, DrawingArgs, SlaveProfileDrawable,
     },
 };
@@ -36,8 +39,8 @@ impl RHOmegaContext {
         }
     }
 
-    pub fn convert_wp_to_xy(coords: WPCoords) -> XYCoords {
-        let y = convert_pressure_to_y(coords.p);
+    pub fn convert_wp_to_xy(coords: WPCoords, geometry: SkewTGeometry) -> XYCoords {
+        let y = convert_pressure_to_y(coords.p, geometry);
 
         // The + sign below looks weird, but is correct.
         let x = (coords.w + config::MAX_ABS_W) / (config::MAX_ABS_W * 2.0);
@@ -45,26 +48,26 @@ impl RHOmegaContext {
         XYCoords { x, y }
     }
 
-    pub fn convert_xy_to_wp(coords: XYCoords) -> WPCoords {
-        let p = convert_y_to_pressure(coords.y);
+    pub fn convert_xy_to_wp(coords: XYCoords, geometry: SkewTGeometry) -> WPCoords {
+        let p = convert_y_to_pressure(coords.y, geometry);
         let w = (config::MAX_ABS_W * 2.0) * coords.x - config::MAX_ABS_W;
 
         WPCoords { w, p }
     }
 
-    pub fn convert_screen_to_wp(&self, coords: ScreenCoords) -> WPCoords {
+    pub fn convert_screen_to_wp(&self, coords: ScreenCoords, geometry: SkewTGeometry) -> WPCoords {
         let xy = self.convert_screen_to_xy(coords);
-        RHOmegaContext::convert_xy_to_wp(xy)
+        RHOmegaContext::convert_xy_to_wp(xy, geometry)
     }
 
-    pub fn convert_wp_to_screen(&self, coords: WPCoords) -> ScreenCoords {
-        let xy = RHOmegaContext::convert_wp_to_xy(coords);
+    pub fn convert_wp_to_screen(&self, coords: WPCoords, geometry: SkewTGeometry) -> ScreenCoords {
+        let xy = RHOmegaContext::convert_wp_to_xy(coords, geometry);
         self.convert_xy_to_screen(xy)
     }
 
-    pub fn convert_device_to_wp(&self, coords: DeviceCoords) -> WPCoords {
+    pub fn convert_device_to_wp(&self, coords: DeviceCoords, geometry: SkewTGeometry) -> WPCoords {
         let xy = self.convert_device_to_xy(coords);
-        Self::convert_xy_to_wp(xy)
+        Self::convert_xy_to_wp(xy, geometry)
     }
 }
 
@@ -204,7 +207,8 @@ impl Drawable for RHOmegaContext {
      **********************************************************************************************/
 
     fn draw_background_fill(&self, args: DrawingArgs<'_, '_>) {
-        let (cr, config) = (args.cr, args.ac.config.borrow());
+        let (ac, cr, config) = (args.ac, args.cr, args.ac.config.borrow());
+        let geometry = ac.skew_t.geometry();
 
         if config.show_background_bands {
             let rgba = config.background_band_rgba;
@@ -219,14 +223,14 @@ impl Drawable for RHOmegaContext {
                     if draw {
                         let ll = WPCoords {
                             w: *prev_val,
-                            p: config::MAXP,
+                            p: geometry.max_p,
                         };
                         let ur = WPCoords {
                             w: *curr_val,
-                            p: config::MINP,
+                            p: geometry.min_p,
                         };
-                        let ll = self.convert_wp_to_screen(ll);
-                        let ur = self.convert_wp_to_screen(ur);
+                        let ll = self.convert_wp_to_screen(ll, geometry);
+                        let ur = self.convert_wp_to_screen(ur, geometry);
                         let ScreenCoords { x: xmin, y: ymin } = ll;
                         let ScreenCoords { x: xmax, y: ymax } = ur;
                         cr.rectangle(xmin, ymin, xmax - xmin, ymax - ymin);
@@ -242,11 +246,12 @@ impl Drawable for RHOmegaContext {
     }
 
     fn draw_background_lines(&self, args: DrawingArgs<'_, '_>) {
-        let (cr, config) = (args.cr, args.ac.config.borrow());
+        let (ac, cr, config) = (args.ac, args.cr, args.ac.config.borrow());
+        let (geometry, background) = (ac.skew_t.geometry(), ac.skew_t.background());
 
         // Draw isobars
         if config.show_isobars {
-            for pnts in config::ISOBAR_PNTS.iter() {
+            for pnts in background.profile_isobar_pnts.iter() {
                 let pnts = pnts
                     .iter()
                     .map(|xy_coords| self.convert_xy_to_screen(*xy_coords));
@@ -255,7 +260,7 @@ impl Drawable for RHOmegaContext {
         }
 
         // Draw w-lines
-        for v_line in config::ISO_OMEGA_PNTS.iter() {
+        for v_line in background.iso_omega_pnts.iter() {
             plot_curve_from_points(
                 cr,
                 config.background_line_width,
@@ -274,15 +279,15 @@ impl Drawable for RHOmegaContext {
             ([
                 WPCoords {
                     w: PaPS(0.0),
-                    p: config::MAXP,
+                    p: geometry.max_p,
                 },
                 WPCoords {
                     w: PaPS(0.0),
-                    p: config::MINP,
+                    p: geometry.min_p,
                 },
             ])
             .iter()
-            .map(|wp_coords| self.convert_wp_to_screen(*wp_coords)),
+            .map(|wp_coords| self.convert_wp_to_screen(*wp_coords, geometry)),
         );
     }
 
@@ -293,10 +298,11 @@ impl Drawable for RHOmegaContext {
 
         let screen_edges = ac.rh_omega.calculate_plot_edges(cr, ac);
         let ScreenRect { lower_left, .. } = screen_edges;
+        let geometry = ac.skew_t.geometry();
 
         let WPCoords {
             p: screen_max_p, ..
-        } = ac.rh_omega.convert_screen_to_wp(lower_left);
+        } = ac.rh_omega.convert_screen_to_wp(lower_left, geometry);
 
         for &w in [PaPS(0.0)].iter().chain(config::ISO_OMEGA.iter()) {
             let label = format!("{:.0}", w.unpack());
@@ -308,7 +314,7 @@ impl Drawable for RHOmegaContext {
                 y: mut ypos,
             } = ac
                 .rh_omega
-                .convert_wp_to_screen(WPCoords { w, p: screen_max_p });
+                .convert_wp_to_screen(WPCoords { w, p: screen_max_p }, geometry);
             xpos -= extents.width() / 2.0; // Center
             ypos -= extents.height() / 2.0; // Center
             ypos += extents.height(); // Move up off bottom axis.
@@ -338,6 +344,8 @@ impl Drawable for RHOmegaContext {
             check_overlap_then_add(cr, ac, &mut labels, &screen_edges, pair);
         }
 
+        collect_height_labels(self, args, &mut labels);
+
         labels
     }
 
@@ -445,7 +453,7 @@ impl Drawable for RHOmegaContext {
         let position = DeviceCoords::from(new_position);
 
         if ac.plottable() {
-            let wp_position = self.convert_device_to_wp(position);
+            let wp_position = self.convert_device_to_wp(position, ac.skew_t.geometry());
 
             let sample = ac
                 .get_sounding_for_display()
@@ -496,6 +504,7 @@ fn draw_rh_profile(args: DrawingArgs<'_, '_>) -> bool {
 
         let pres_data = sndg.pressure_profile();
         let rh_data = relative_humidity(sndg);
+        let geometry = ac.skew_t.geometry();
 
         let bb = ac.rh_omega.get_plot_area();
         let x0 = bb.lower_left.x;
@@ -505,12 +514,12 @@ fn draw_rh_profile(args: DrawingArgs<'_, '_>) -> bool {
             // Filter out levels with missing pressure and map missing RH to 0%
             .filter_map(|(p, rh)| p.map(|p| (p, rh.unwrap_or(0.0))))
             // Only take up to the highest plottable pressu
-            .take_while(|(p, _)| *p > config::MINP)
+            .take_while(|(p, _)| *p > geometry.min_p)
             // Map into ScreenCoords for plotting
             .map(|(p, rh)| {
                 let ScreenCoords { y, .. } = ac
                     .rh_omega
-                    .convert_wp_to_screen(WPCoords { w: PaPS(0.0), p });
+                    .convert_wp_to_screen(WPCoords { w: PaPS(0.0), p }, geometry);
                 let x = x0 + width * rh;
                 ScreenCoords { x, y }
             });
@@ -542,6 +551,7 @@ fn draw_rh_ice_profile(args: DrawingArgs<'_, '_>) -> bool {
 
         let pres_data = sndg.pressure_profile();
         let rh_data = relative_humidity_ice(sndg);
+        let geometry = ac.skew_t.geometry();
 
         let bb = ac.rh_omega.get_plot_area();
         let x0 = bb.lower_left.x;
@@ -551,12 +561,12 @@ fn draw_rh_ice_profile(args: DrawingArgs<'_, '_>) -> bool {
             // Filter out levels with missing pressure and map missing RH to 0%
             .filter_map(|(p, rh)| p.map(|p| (p, rh.unwrap_or(0.0))))
             // Only take up to the highest plottable pressu
-            .take_while(|(p, _)| *p > config::MINP)
+            .take_while(|(p, _)| *p > geometry.min_p)
             // Map into ScreenCoords for plotting
             .map(|(p, rh)| {
                 let ScreenCoords { y, .. } = ac
                     .rh_omega
-                    .convert_wp_to_screen(WPCoords { w: PaPS(0.0), p });
+                    .convert_wp_to_screen(WPCoords { w: PaPS(0.0), p }, geometry);
                 let x = x0 + width * rh;
                 ScreenCoords { x, y }
             });
@@ -588,12 +598,13 @@ fn draw_omega_profile(args: DrawingArgs<'_, '_>) -> bool {
         let omega_data = sndg.pvv_profile();
         let line_width = config.profile_line_width;
         let line_rgba = config.omega_rgba;
+        let geometry = ac.skew_t.geometry();
 
         let profile_data = izip!(pres_data, omega_data).filter_map(|(p, w)| {
             if let (Some(p), Some(w)) = (p.into(), w.into()) {
-                if p > config::MINP {
+                if p > geometry.min_p {
                     let wp_coords = WPCoords { w, p };
-                    Some(ac.rh_omega.convert_wp_to_screen(wp_coords))
+                    Some(ac.rh_omega.convert_wp_to_screen(wp_coords, geometry))
                 } else {
                     None
                 }
@@ -7,12 +7,14 @@ use crate::{
     },
     coords::{
         convert_pressure_to_y, convert_y_to_pressure, DeviceCoords, SPCoords, ScreenCoords,
-        ScreenRect, XYCoords,
+        ScreenRect, SkewTGeometry, XYCoords,
     },
     errors::SondeError,
     gui::{
         plot_context::{GenericContext, HasGenericContext, PlotContext, PlotContextExt},
-        utility::{check_overlap_then_add, plot_curve_from_points, DrawingArgs},
+        utility::{
+            check_overlap_then_add, collect_height_labels, plot_curve_from_points, DrawingArgs,
+        },
         Drawable, SlaveProfileDrawable,
     },
 };
@@ -32,8 +34,8 @@ impl WindSpeedContext {
         }
     }
 
-    pub fn convert_sp_to_xy(coords: SPCoords) -> XYCoords {
-        let y = convert_pressure_to_y(coords.press);
+    pub fn convert_sp_to_xy(coords: SPCoords, geometry: SkewTGeometry) -> XYCoords {
+        let y = convert_pressure_to_y(coords.press, geometry);
 
         let mut x = coords.spd.unpack();
         // Avoid infinity
@@ -46,8 +48,8 @@ impl WindSpeedContext {
         XYCoords { x, y }
     }
 
-    pub fn convert_xy_to_sp(coords: XYCoords) -> SPCoords {
-        let press = convert_y_to_pressure(coords.y);
+    pub fn convert_xy_to_sp(coords: XYCoords, geometry: SkewTGeometry) -> SPCoords {
+        let press = convert_y_to_pressure(coords.y, geometry);
 
         let spd = Knots(10.0f64.powf(
             coords.x * (f64::log10(config::MAX_PROFILE_SPEED.unpack()) - f64::log10(1.0))
@@ -57,19 +59,19 @@ impl WindSpeedContext {
         SPCoords { spd, press }
     }
 
-    pub fn convert_sp_to_screen(&self, coords: SPCoords) -> ScreenCoords {
-        let xy = Self::convert_sp_to_xy(coords);
+    pub fn convert_sp_to_screen(&self, coords: SPCoords, geometry: SkewTGeometry) -> ScreenCoords {
+        let xy = Self::convert_sp_to_xy(coords, geometry);
         self.convert_xy_to_screen(xy)
     }
 
-    pub fn convert_screen_to_sp(&self, coords: ScreenCoords) -> SPCoords {
+    pub fn convert_screen_to_sp(&self, coords: ScreenCoords, geometry: SkewTGeometry) -> SPCoords {
         let xy = self.convert_screen_to_xy(coords);
-        Self::convert_xy_to_sp(xy)
+        Self::convert_xy_to_sp(xy, geometry)
     }
 
-    pub fn convert_device_to_sp(&self, coords: DeviceCoords) -> SPCoords {
+    pub fn convert_device_to_sp(&self, coords: DeviceCoords, geometry: SkewTGeometry) -> SPCoords {
         let xy = self.convert_device_to_xy(coords);
-        Self::convert_xy_to_sp(xy)
+        Self::convert_xy_to_sp(xy, geometry)
     }
 }
 
@@ -208,13 +210,14 @@ impl Drawable for WindSpeedContext {
      * Background Drawing.
      **********************************************************************************************/
     fn draw_background_fill(&self, args: DrawingArgs<'_, '_>) {
-        let (cr, config) = (args.cr, args.ac.config.borrow());
+        let (ac, cr, config) = (args.ac, args.cr, args.ac.config.borrow());
 
         if config.show_background_bands {
             let rgba = config.background_band_rgba;
             cr.set_source_rgba(rgba.0, rgba.1, rgba.2, rgba.3);
 
-            let mut lines = config::PROFILE_SPEED_PNTS.iter();
+            let background = ac.skew_t.background();
+            let mut lines = background.profile_speed_pnts.iter();
             let mut draw = true;
             let mut prev = lines.next();
             while let Some(prev_val) = prev {
@@ -238,11 +241,12 @@ impl Drawable for WindSpeedContext {
     }
 
     fn draw_background_lines(&self, args: DrawingArgs<'_, '_>) {
-        let (cr, config) = (args.cr, args.ac.config.borrow());
+        let (ac, cr, config) = (args.ac, args.cr, args.ac.config.borrow());
+        let background = ac.skew_t.background();
 
         // Draw isobars
         if config.show_isobars {
-            for pnts in config::ISOBAR_PNTS.iter() {
+            for pnts in background.profile_isobar_pnts.iter() {
                 let pnts = pnts
                     .iter()
                     .map(|xy_coords| self.convert_xy_to_screen(*xy_coords));
@@ -250,7 +254,7 @@ impl Drawable for WindSpeedContext {
             }
         }
 
-        for line in config::PROFILE_SPEED_PNTS.iter() {
+        for line in background.profile_speed_pnts.iter() {
             let pnts = line
                 .iter()
                 .map(|xy_coord| self.convert_xy_to_screen(*xy_coord));
@@ -266,27 +270,32 @@ impl Drawable for WindSpeedContext {
         let (ac, cr) = (args.ac, args.cr);
 
         let mut labels = vec![];
+        let units = ac.config.borrow().wind_speed_units;
 
         let screen_edges = self.calculate_plot_edges(cr, ac);
         let ScreenRect { lower_left, .. } = screen_edges;
+        let geometry = ac.skew_t.geometry();
 
         let SPCoords {
             press: screen_max_p,
             ..
-        } = self.convert_screen_to_sp(lower_left);
+        } = self.convert_screen_to_sp(lower_left, geometry);
 
         for spd in &config::PROFILE_SPEEDS {
-            let label = format!("{:.0}", spd.unpack());
+            let label = format!("{:.0}", units.value(*spd));
 
             let extents = cr.text_extents(&label).unwrap();
 
             let ScreenCoords {
                 x: mut xpos,
                 y: mut ypos,
-            } = self.convert_sp_to_screen(SPCoords {
-                spd: *spd,
-                press: screen_max_p,
-            });
+            } = self.convert_sp_to_screen(
+                SPCoords {
+                    spd: *spd,
+                    press: screen_max_p,
+                },
+                geometry,
+            );
             xpos -= extents.width() / 2.0; // Center
             ypos -= extents.height() / 2.0; // Center
             ypos += extents.height(); // Move up off bottom axis.
@@ -316,6 +325,8 @@ impl Drawable for WindSpeedContext {
             check_overlap_then_add(cr, ac, &mut labels, &screen_edges, pair);
         }
 
+        collect_height_labels(self, args, &mut labels);
+
         labels
     }
 
@@ -338,8 +349,10 @@ impl Drawable for WindSpeedContext {
 
         if let Sample::Sounding { data, .. } = vals {
             if let Some(WindSpdDir { speed, .. }) = data.wind.into_option() {
-                let line = format!("{:.0}KT\n", speed.unpack());
-                results.push((line, ac.config.borrow().wind_rgba));
+                let config = ac.config.borrow();
+                let units = config.wind_speed_units;
+                let line = format!("{:.0}{}\n", units.value(speed), units.label());
+                results.push((line, config.wind_rgba));
             }
         }
 
@@ -361,7 +374,7 @@ impl Drawable for WindSpeedContext {
         let position = DeviceCoords::from(new_position);
 
         if ac.plottable() && self.has_data() {
-            let sp_position = self.convert_device_to_sp(position);
+            let sp_position = self.convert_device_to_sp(position, ac.skew_t.geometry());
 
             let sample = ac
                 .get_sounding_for_display()
@@ -404,6 +417,7 @@ fn draw_wind_speed_profile(args: DrawingArgs<'_, '_>) {
         let anal = anal.borrow();
         let sndg = anal.sounding();
 
+        let geometry = ac.skew_t.geometry();
         let pres_data = sndg.pressure_profile();
         let spd_data = sndg.wind_profile();
 
@@ -425,8 +439,11 @@ fn draw_wind_speed_profile(args: DrawingArgs<'_, '_>) {
             })
             .filter_map(|pair| {
                 let (press, spd) = pair;
-                if press > config::MINP {
-                    Some(ac.wind_speed.convert_sp_to_screen(SPCoords { spd, press }))
+                if press > geometry.min_p {
+                    Some(
+                        ac.wind_speed
+                            .convert_sp_to_screen(SPCoords { spd, press }, geometry),
+                    )
                 } else {
                     None
                 }
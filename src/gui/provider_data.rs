@@ -1,12 +1,9 @@
 use crate::{
+    analysis::provider,
     app::{AppContext, AppContextPointer},
     errors::SondeError,
 };
-use gtk::{
-    glib::{translate::IntoGlib, Propagation},
-    prelude::*,
-    EventControllerKey, TextTag, TextView,
-};
+use gtk::{glib::translate::IntoGlib, prelude::*, EventControllerKey, TextTag, TextView};
 use std::{fmt::Write, rc::Rc};
 
 const TEXT_AREA_ID: &str = "provider_data_text";
@@ -40,19 +37,12 @@ macro_rules! set_text {
 }
 
 pub fn set_up_provider_text_area(acp: &AppContextPointer) -> Result<(), SondeError> {
-    use gtk::gdk::Key;
-
     let text_area: TextView = acp.fetch_widget(TEXT_AREA_ID)?;
 
     let key_press = EventControllerKey::new();
     let ac = Rc::clone(acp);
     key_press.connect_key_pressed(move |_key_press, key, _code, _key_modifier| {
-        if key == Key::KP_Right || key == Key::Right {
-            ac.display_next();
-        } else if key == Key::KP_Left || key == Key::Left {
-            ac.display_previous();
-        }
-        Propagation::Stop
+        crate::gui::key_press(key, &ac)
     });
     text_area.add_controller(key_press);
 
@@ -79,7 +69,13 @@ pub fn update_text_area(ac: &AppContext) {
         provider_data.sort_by_key(|kv| kv.0);
 
         for (k, v) in provider_data.into_iter() {
-            writeln!(text, "{:-35} : {:9.3}", k, v).unwrap();
+            let field = provider::describe(k);
+            writeln!(
+                text,
+                "{:-35} : {:9.3} {}\n    {}",
+                field.description, v, field.units, k
+            )
+            .unwrap();
         }
 
         set_text!(tb, &text);
@@ -1,17 +1,20 @@
 use crate::{
     app::{
-        config::{self, Rgba},
+        config::{self, Rgba, SkewTBackground, ThermoDiagram},
         sample::{create_sample_sounding, Sample},
         AppContext, AppContextPointer, ZoomableDrawingAreas,
     },
     coords::{
         convert_pressure_to_y, convert_y_to_pressure, DeviceCoords, ScreenCoords, ScreenRect,
-        TPCoords, XYCoords,
+        SkewTGeometry, TPCoords, XYCoords,
     },
     errors::SondeError,
     gui::{
         plot_context::{GenericContext, HasGenericContext},
-        utility::{check_overlap_then_add, plot_curve_from_points, plot_dashed_curve_from_points},
+        utility::{
+            check_overlap_then_add, collect_height_labels, plot_curve_from_points,
+            plot_dashed_curve_from_points,
+        },
         Drawable, DrawingArgs, MasterDrawable, PlotContext, PlotContextExt,
     },
 };
@@ -20,57 +23,140 @@ use gtk::{
     EventControllerScroll, EventControllerScrollFlags, GestureClick,
 };
 use itertools::izip;
-use metfor::{Celsius, Feet, Quantity};
+use metfor::{Celsius, HectoPascal};
 use sounding_analysis::{self, Parcel, ParcelProfile};
-use std::rc::Rc;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 
 pub struct SkewTContext {
     generic: GenericContext,
+    // The part of the atmosphere on the chart, the profiles beside it share its vertical axis.
+    geometry: Cell<SkewTGeometry>,
+    // Background lines for the current geometry.
+    background: RefCell<Rc<SkewTBackground>>,
 }
 
 impl SkewTContext {
     pub fn new() -> Self {
+        let geometry = SkewTGeometry::default();
+
         SkewTContext {
             generic: GenericContext::new(),
+            geometry: Cell::new(geometry),
+            background: RefCell::new(Rc::new(SkewTBackground::new(geometry))),
         }
     }
 
-    pub fn convert_tp_to_xy(coords: TPCoords) -> XYCoords {
-        let y = convert_pressure_to_y(coords.pressure);
-        let x = (coords.temperature - config::MINT) / (config::MAXT - config::MINT);
+    /// The part of the atmosphere on the chart.
+    pub fn geometry(&self) -> SkewTGeometry {
+        self.geometry.get()
+    }
 
-        // do the skew
-        let x = x + y;
-        XYCoords { x, y }
+    /// Change the part of the atmosphere on the chart, the background lines are generated again
+    /// if it changed.
+    pub fn set_geometry(&self, geometry: SkewTGeometry) {
+        if geometry != self.geometry.replace(geometry) {
+            *self.background.borrow_mut() = Rc::new(SkewTBackground::new(geometry));
+        }
     }
 
-    pub fn convert_xy_to_tp(coords: XYCoords) -> TPCoords {
-        // undo the skew
-        let x = coords.x - coords.y;
-        let y = coords.y;
+    /// The background lines for the current geometry.
+    pub fn background(&self) -> Rc<SkewTBackground> {
+        Rc::clone(&self.background.borrow())
+    }
+
+    pub fn convert_tp_to_xy(coords: TPCoords, geometry: SkewTGeometry) -> XYCoords {
+        let SkewTGeometry {
+            min_t, max_t, skew, ..
+        } = geometry;
 
-        let t = config::MINT + (config::MAXT - config::MINT) * x;
-        let p = convert_y_to_pressure(y);
+        match geometry.diagram {
+            // The skew is zero for the emagram and Stuve diagram.
+            ThermoDiagram::SkewT | ThermoDiagram::Emagram | ThermoDiagram::Stuve => {
+                let y = convert_pressure_to_y(coords.pressure, geometry);
+                let x = (coords.temperature - min_t) / (max_t - min_t);
 
-        TPCoords {
-            temperature: t,
-            pressure: p,
+                // do the skew
+                let x = x + skew * y;
+                XYCoords { x, y }
+            }
+            ThermoDiagram::Tephigram => tephigram::convert_tp_to_xy(coords, geometry),
         }
     }
 
-    pub fn convert_tp_to_screen(&self, coords: TPCoords) -> ScreenCoords {
-        let xy = Self::convert_tp_to_xy(coords);
-        self.convert_xy_to_screen(xy)
+    pub fn convert_xy_to_tp(coords: XYCoords, geometry: SkewTGeometry) -> TPCoords {
+        let SkewTGeometry {
+            min_t, max_t, skew, ..
+        } = geometry;
+
+        match geometry.diagram {
+            ThermoDiagram::SkewT | ThermoDiagram::Emagram | ThermoDiagram::Stuve => {
+                // undo the skew
+                let x = coords.x - skew * coords.y;
+                let y = coords.y;
+
+                let t = min_t + (max_t - min_t) * x;
+                let p = convert_y_to_pressure(y, geometry);
+
+                TPCoords {
+                    temperature: t,
+                    pressure: p,
+                }
+            }
+            ThermoDiagram::Tephigram => tephigram::convert_xy_to_tp(coords, geometry),
+        }
+    }
+
+    /// Find the temperature at a horizontal position along an isobar.
+    pub fn temperature_at(x: f64, pressure: HectoPascal, geometry: SkewTGeometry) -> Celsius {
+        match geometry.diagram {
+            ThermoDiagram::SkewT | ThermoDiagram::Emagram | ThermoDiagram::Stuve => {
+                let y = convert_pressure_to_y(pressure, geometry);
+                Self::convert_xy_to_tp(XYCoords { x, y }, geometry).temperature
+            }
+            ThermoDiagram::Tephigram => tephigram::temperature_at(x, pressure, geometry),
+        }
+    }
+
+    /// Find where an isotherm crosses the bottom and top edges of the chart. Isotherms are
+    /// straight on all the diagrams.
+    pub fn isotherm_xy(temperature: Celsius, geometry: SkewTGeometry) -> [XYCoords; 2] {
+        let SkewTGeometry { max_p, min_p, .. } = geometry;
+
+        let bottom = Self::convert_tp_to_xy(
+            TPCoords {
+                temperature,
+                pressure: max_p,
+            },
+            geometry,
+        );
+        let top = Self::convert_tp_to_xy(
+            TPCoords {
+                temperature,
+                pressure: min_p,
+            },
+            geometry,
+        );
+
+        let slope = (top.x - bottom.x) / (top.y - bottom.y);
+        let at = |y: f64| XYCoords {
+            x: bottom.x + slope * (y - bottom.y),
+            y,
+        };
+
+        [at(0.0), at(1.0)]
     }
 
-    pub fn convert_screen_to_tp(&self, coords: ScreenCoords) -> TPCoords {
-        let xy = self.convert_screen_to_xy(coords);
-        Self::convert_xy_to_tp(xy)
+    pub fn convert_tp_to_screen(&self, coords: TPCoords) -> ScreenCoords {
+        let xy = Self::convert_tp_to_xy(coords, self.geometry());
+        self.convert_xy_to_screen(xy)
     }
 
     pub fn convert_device_to_tp(&self, coords: DeviceCoords) -> TPCoords {
         let xy = self.convert_device_to_xy(coords);
-        Self::convert_xy_to_tp(xy)
+        Self::convert_xy_to_tp(xy, self.geometry())
     }
 }
 
@@ -191,12 +277,14 @@ impl Drawable for SkewTContext {
 
     fn draw_background_lines(&self, args: DrawingArgs<'_, '_>) {
         let (cr, config) = (args.cr, args.ac.config.borrow());
+        let background = self.background();
+        let geometry = self.geometry();
 
         // Draws background lines from the bottom up.
 
         // Draw isentrops
         if config.show_isentrops {
-            for pnts in config::ISENTROP_PNTS.iter() {
+            for pnts in background.isentrop_pnts.iter() {
                 let pnts = pnts
                     .iter()
                     .map(|xy_coords| self.convert_xy_to_screen(*xy_coords));
@@ -211,7 +299,7 @@ impl Drawable for SkewTContext {
 
         // Draw theta-e lines
         if config.show_iso_theta_e {
-            for pnts in config::ISO_THETA_E_PNTS.iter() {
+            for pnts in background.iso_theta_e_pnts.iter() {
                 let pnts = pnts
                     .iter()
                     .map(|xy_coords| self.convert_xy_to_screen(*xy_coords));
@@ -226,7 +314,7 @@ impl Drawable for SkewTContext {
 
         // Draw mixing ratio lines
         if config.show_iso_mixing_ratio {
-            for pnts in config::ISO_MIXING_RATIO_PNTS.iter() {
+            for pnts in background.iso_mixing_ratio_pnts.iter() {
                 let pnts = pnts
                     .iter()
                     .map(|xy_coords| self.convert_xy_to_screen(*xy_coords));
@@ -241,7 +329,7 @@ impl Drawable for SkewTContext {
 
         // Draw isotherms
         if config.show_isotherms {
-            for pnts in config::ISOTHERM_PNTS.iter() {
+            for pnts in background.isotherm_pnts.iter() {
                 let pnts = pnts
                     .iter()
                     .map(|tp_coords| self.convert_xy_to_screen(*tp_coords));
@@ -256,7 +344,7 @@ impl Drawable for SkewTContext {
 
         // Draw isobars
         if config.show_isobars {
-            for pnts in config::ISOBAR_PNTS.iter() {
+            for pnts in background.isobar_pnts.iter() {
                 let pnts = pnts
                     .iter()
                     .map(|xy_coords| self.convert_xy_to_screen(*xy_coords));
@@ -270,11 +358,11 @@ impl Drawable for SkewTContext {
             let pnts = &[
                 TPCoords {
                     temperature: Celsius(0.0),
-                    pressure: config::MAXP,
+                    pressure: geometry.max_p,
                 },
                 TPCoords {
                     temperature: Celsius(0.0),
-                    pressure: config::MINP,
+                    pressure: geometry.min_p,
                 },
             ];
             let pnts = pnts
@@ -298,8 +386,14 @@ impl Drawable for SkewTContext {
         let ScreenRect { lower_left, .. } = screen_edges;
 
         if config.show_isobars {
-            for &p in &config::ISOBARS {
-                let label = format!("{:.0}", p.unpack());
+            let SkewTGeometry { max_p, min_p, .. } = self.geometry();
+            let isobars = config::ISOBARS
+                .iter()
+                .filter(|&&p| p >= min_p && p <= max_p);
+
+            let units = config.pressure_units;
+            for &p in isobars {
+                let label = format!("{:.*}", units.precision(), units.value(p));
 
                 let extents = cr.text_extents(&label).unwrap();
 
@@ -331,22 +425,20 @@ impl Drawable for SkewTContext {
         }
 
         if config.show_isotherms {
-            let TPCoords {
-                pressure: screen_max_p,
-                ..
-            } = self.convert_screen_to_tp(lower_left);
+            let screen_min_y = self.convert_screen_to_xy(lower_left).y;
+            let units = config.temperature_units;
             for &t in &config::ISOTHERMS {
-                let label = format!("{:.0}", t.unpack());
+                let label = format!("{:.0}", units.value(t));
 
                 let extents = cr.text_extents(&label).unwrap();
 
+                // Label where the isotherm crosses the bottom of the screen.
+                let [bottom, top] = Self::isotherm_xy(t, self.geometry());
+                let x = bottom.x + (top.x - bottom.x) * screen_min_y;
                 let ScreenCoords {
                     x: mut xpos,
                     y: mut ypos,
-                } = self.convert_tp_to_screen(TPCoords {
-                    temperature: t,
-                    pressure: screen_max_p,
-                });
+                } = self.convert_xy_to_screen(XYCoords { x, y: screen_min_y });
                 xpos -= extents.width() / 2.0; // Center
                 ypos -= extents.height() / 2.0; // Center
                 ypos += extents.height(); // Move up off bottom axis.
@@ -369,6 +461,8 @@ impl Drawable for SkewTContext {
             }
         }
 
+        collect_height_labels(self, args, &mut labels);
+
         labels
     }
 
@@ -383,6 +477,10 @@ impl Drawable for SkewTContext {
             if let Some(src_desc) = anal.borrow().sounding().source_description() {
                 result.push((src_desc.to_owned(), color));
             }
+
+            if anal.borrow().is_synthetic() {
+                result.push(("SYNTHETIC - not from a data file".to_owned(), color));
+            }
         }
 
         if let Some(anal) = ac.get_sounding_for_display() {
@@ -428,11 +526,8 @@ impl Drawable for SkewTContext {
                     }
                 }
                 if let Some(el) = elevation.into_option() {
-                    location.push_str(&format!(
-                        "{:.0}m ({:.0}ft)",
-                        el.unpack(),
-                        Feet::from(el).unpack()
-                    ));
+                    let units = ac.config.borrow().height_units;
+                    location.push_str(&format!("{:.0}{}", units.value(el), units.label()));
                 }
 
                 result.push((location, color));
@@ -446,9 +541,13 @@ impl Drawable for SkewTContext {
      * Data Drawing.
      **********************************************************************************************/
     fn draw_data(&self, args: DrawingArgs<'_, '_>) {
+        Self::draw_ghost_profiles(args);
+        Self::draw_ensemble_envelope(args);
         Self::draw_temperature_profiles(args);
         Self::draw_wind_profile(args);
         Self::draw_data_overlays(args);
+        Self::draw_reference_lines(args);
+        Self::draw_edit_handles(args);
         // Drawing the precip icon requires self because it draws relative to the window (like the
         // legend) and not just in data or X-Y coordinates.
         self.draw_precip_icons(args);
@@ -530,6 +629,8 @@ impl Drawable for SkewTContext {
                 Sample::None => {}
             }
         }
+
+        Self::draw_layer_measurement(args);
     }
 
     /***********************************************************************************************
@@ -539,6 +640,43 @@ impl Drawable for SkewTContext {
         ac.set_last_focus(ZoomableDrawingAreas::SkewT);
     }
 
+    fn left_button_press_event(&self, position: (f64, f64), ac: &AppContextPointer) {
+        let position = DeviceCoords::from(position);
+        self.set_last_cursor_position(Some(position));
+
+        // While editing, grabbing a point drags it instead of panning the view.
+        match self
+            .find_edit_target(position, ac)
+            .filter(|_| ac.is_editing())
+        {
+            Some(target) => ac.start_edit(target),
+            None if ac.is_measuring_layer() => {
+                ac.start_layer(self.convert_device_to_tp(position).pressure)
+            }
+            None if ac.is_placing_reference_lines() => {
+                ac.add_reference_lines(self.convert_device_to_tp(position))
+            }
+            None if ac.is_adding_custom_parcels() => {
+                AppContext::add_custom_parcel_at(ac, self.convert_device_to_tp(position).pressure)
+            }
+            None => self.set_left_button_pressed(true),
+        }
+    }
+
+    fn left_button_release_event(&self, _position: (f64, f64), ac: &AppContextPointer) {
+        if ac.edit_target().is_some() {
+            AppContext::finish_edit(ac);
+        } else if ac.is_dragging_layer() {
+            ac.finish_layer();
+        } else if self.get_left_button_pressed() && !ac.is_editing() {
+            // Panning clears the sample, so there is only one to pin after a click in place.
+            ac.pin_sample();
+        }
+
+        self.set_last_cursor_position(None);
+        self.set_left_button_pressed(false);
+    }
+
     fn right_button_release_event(&self, _position: (f64, f64), ac: &AppContextPointer) {
         if let Ok(popover) = ac.fetch_widget::<gtk::PopoverMenu>("skew_t_popover") {
             if let Some(pos) = self.get_last_cursor_position() {
@@ -562,7 +700,11 @@ impl Drawable for SkewTContext {
 
         let position = DeviceCoords::from(new_position);
 
-        if self.get_left_button_pressed() {
+        if let Some(target) = ac.edit_target() {
+            self.drag_edit_target(target, position, ac);
+        } else if ac.is_dragging_layer() {
+            ac.drag_layer(self.convert_device_to_tp(position).pressure);
+        } else if self.get_left_button_pressed() {
             if let Some(last_position) = self.get_last_cursor_position() {
                 let old_position = self.convert_device_to_xy(last_position);
 
@@ -615,8 +757,12 @@ impl MasterDrawable for SkewTContext {}
 mod active_readout;
 mod background;
 mod data_layer;
+mod edit;
 mod menu;
-mod wind;
+mod tephigram;
+pub mod wind;
+
+pub use self::data_layer::{draw_precip_symbol, PRECIP_BOX_SIZE};
 
 impl SkewTContext {
     fn draw_parcel_profile(args: DrawingArgs<'_, '_>, profile: &ParcelProfile, line_rgba: Rgba) {
@@ -629,7 +775,7 @@ impl SkewTContext {
         let line_width = config.temperature_line_width;
 
         let profile_data = izip!(pres_data, temp_data).filter_map(|(&pressure, &temperature)| {
-            if pressure > config::MINP {
+            if pressure > self.geometry().min_p {
                 let tp_coords = TPCoords {
                     temperature,
                     pressure,
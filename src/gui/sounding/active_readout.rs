@@ -1,15 +1,18 @@
 use super::SkewTContext;
 use crate::{
-    analysis::Analysis,
-    app::config::{self, Config, Rgba},
-    coords::TPCoords,
+    analysis::{
+        measure::{measure_layer, LayerMeasurement},
+        Analysis,
+    },
+    app::config::{Config, Rgba},
+    coords::{convert_pressure_to_y, TPCoords, XYCoords},
     gui::{
         utility::{draw_filled_polygon, plot_curve_from_points},
-        Drawable, DrawingArgs,
+        Drawable, DrawingArgs, PlotContextExt,
     },
 };
 use itertools::izip;
-use metfor::{rh, Celsius, Fahrenheit, Feet, GigaWatts, HectoPascal, Quantity};
+use metfor::{rh, Celsius, GigaWatts, HectoPascal, Quantity};
 use sounding_analysis::{
     self, experimental::fire_briggs::PlumeAscentAnalysis, DataRow, Parcel, ParcelAscentAnalysis,
 };
@@ -23,6 +26,8 @@ impl SkewTContext {
         results: &mut Vec<(String, Rgba)>,
     ) {
         let default_color = config.label_rgba;
+        let (t_units, p_units) = (config.temperature_units, config.pressure_units);
+        let (h_units, w_units) = (config.height_units, config.wind_speed_units);
 
         let t_c = data.temperature;
         let dp_c = data.dew_point;
@@ -35,7 +40,11 @@ impl SkewTContext {
         if t_c.is_some() || dp_c.is_some() || omega.is_some() {
             if let Some(t_c) = t_c.into_option() {
                 let mut line = String::with_capacity(10);
-                line.push_str(&format!("{:.0}\u{00B0}C", t_c.unpack().round()));
+                line.push_str(&format!(
+                    "{:.0}{}",
+                    t_units.value(t_c).round(),
+                    t_units.label()
+                ));
                 if dp_c.is_none() && omega.is_none() {
                     line.push('\n');
                 } else if dp_c.is_none() {
@@ -48,7 +57,11 @@ impl SkewTContext {
                     results.push(("/".to_owned(), default_color));
                 }
                 let mut line = String::with_capacity(10);
-                line.push_str(&format!("{:.0}\u{00B0}C", dp_c.unpack().round()));
+                line.push_str(&format!(
+                    "{:.0}{}",
+                    t_units.value(dp_c).round(),
+                    t_units.label()
+                ));
                 if t_c.is_none() && omega.is_none() {
                     line.push('\n');
                 } else {
@@ -81,7 +94,12 @@ impl SkewTContext {
         if pres.is_some() || wind.is_some() {
             if let Some(pres) = pres.into_option() {
                 let mut line = String::with_capacity(10);
-                line.push_str(&format!("{:.0}hPa", pres.unpack()));
+                line.push_str(&format!(
+                    "{:.*}{}",
+                    p_units.precision(),
+                    p_units.value(pres),
+                    p_units.label()
+                ));
                 if wind.is_none() {
                     line.push('\n');
                 } else {
@@ -92,9 +110,10 @@ impl SkewTContext {
             if let Some(wind) = wind.into_option() {
                 results.push((
                     format!(
-                        "{:03.0} {:02.0}KT\n",
+                        "{:03.0} {:02.0}{}\n",
                         wind.direction,
-                        wind.speed.unpack().round()
+                        w_units.value(wind.speed).round(),
+                        w_units.label()
                     ),
                     config.wind_rgba,
                 ));
@@ -106,9 +125,9 @@ impl SkewTContext {
 
             results.push((
                 format!(
-                    "ASL: {:5.0}m ({:5.0}ft)\n",
-                    hgt.unpack().round(),
-                    Feet::from(hgt).unpack().round()
+                    "ASL: {:5.0}{}\n",
+                    h_units.value(hgt).round(),
+                    h_units.label()
                 ),
                 color,
             ));
@@ -119,9 +138,9 @@ impl SkewTContext {
                 let color = config.active_readout_line_rgba;
                 let mut line = String::with_capacity(128);
                 line.push_str(&format!(
-                    "AGL: {:5.0}m ({:5.0}ft)\n",
-                    (hgt - elev).unpack().round(),
-                    Feet::from(hgt - elev).unpack().round(),
+                    "AGL: {:5.0}{}\n",
+                    h_units.value(hgt - elev).round(),
+                    h_units.label()
                 ));
                 results.push((line, color));
             }
@@ -158,6 +177,7 @@ impl SkewTContext {
         results: &mut Vec<(String, Rgba)>,
     ) {
         let default_color = config.label_rgba;
+        let h_units = config.height_units;
 
         let mut line = String::with_capacity(10);
         line.push_str(&format!("Fire Power {:.0}GW\n", fire_power.unpack()));
@@ -185,9 +205,10 @@ impl SkewTContext {
                 plume_anal_high.el_height.into_option(),
             ) {
                 line.push_str(&format!(
-                    "LMIB: {:.0} - {:.0} m\n",
-                    el_high.unpack(),
-                    el_low.unpack()
+                    "LMIB: {:.0} - {:.0} {}\n",
+                    h_units.value(el_high),
+                    h_units.value(el_low),
+                    h_units.label()
                 ));
             }
             results.push((line, default_color));
@@ -197,9 +218,10 @@ impl SkewTContext {
                 plume_anal_high.max_height.into_option(),
             ) {
                 line.push_str(&format!(
-                    "Max Height: {:.0} - {:.0} m\n",
-                    mh_high.unpack(),
-                    mh_low.unpack()
+                    "Max Height: {:.0} - {:.0} {}\n",
+                    h_units.value(mh_high),
+                    h_units.value(mh_low),
+                    h_units.label()
                 ));
             }
             results.push((line, default_color));
@@ -263,9 +285,10 @@ impl SkewTContext {
         let config = ac.config.borrow();
 
         let line_width = config.profile_line_width;
+        let min_p = ac.skew_t.geometry().min_p;
 
         let profile_data = izip!(pres_data, temp_data).filter_map(|(&pressure, &temperature)| {
-            if pressure > config::MINP {
+            if pressure > min_p {
                 let tp_coords = TPCoords {
                     temperature,
                     pressure,
@@ -279,6 +302,153 @@ impl SkewTContext {
         plot_curve_from_points(cr, line_width, line_rgba, profile_data);
     }
 
+    /// Shade the layer picked with the layer tool and list its measurements beside it.
+    pub fn draw_layer_measurement(args: DrawingArgs<'_, '_>) {
+        let (ac, cr) = (args.ac, args.cr);
+
+        let ((p0, p1), anal) = match (ac.measured_layer(), ac.get_sounding_for_display()) {
+            (Some(layer), Some(anal)) => (layer, anal),
+            _ => return,
+        };
+
+        let config = ac.config.borrow();
+        let rgba = config.layer_measure_rgba;
+
+        // Shade the layer even when it can't be measured so the user can see what they picked.
+        let bb = ac.skew_t.get_plot_area();
+        let (left, right) = (bb.lower_left.x, bb.upper_right.x);
+        let geometry = ac.skew_t.geometry();
+        let (y0, y1) = (
+            convert_pressure_to_y(p0, geometry),
+            convert_pressure_to_y(p1, geometry),
+        );
+        let corners = [
+            XYCoords { x: left, y: y0 },
+            XYCoords { x: right, y: y0 },
+            XYCoords { x: right, y: y1 },
+            XYCoords { x: left, y: y1 },
+        ];
+        draw_filled_polygon(
+            cr,
+            (rgba.0, rgba.1, rgba.2, rgba.3 * 0.2),
+            corners.iter().map(|&xy| ac.skew_t.convert_xy_to_screen(xy)),
+        );
+        for edge in [&corners[..2], &corners[2..]] {
+            plot_curve_from_points(
+                cr,
+                config.active_readout_line_width,
+                rgba,
+                edge.iter().map(|&xy| ac.skew_t.convert_xy_to_screen(xy)),
+            );
+        }
+
+        let measurement = match measure_layer(anal.borrow().sounding(), p0, p1) {
+            Some(measurement) => measurement,
+            None => return,
+        };
+
+        let lines = Self::create_layer_measurement_text(&measurement, &config);
+
+        ac.skew_t.prepare_to_make_text(args);
+        let box_rect = ac
+            .skew_t
+            .calculate_active_readout_box(args, &lines, measurement.top);
+        Self::draw_sample_readout_text_box(&box_rect, cr, ac, &lines);
+    }
+
+    fn create_layer_measurement_text(
+        measurement: &LayerMeasurement,
+        config: &Config,
+    ) -> Vec<(String, Rgba)> {
+        let default_color = config.label_rgba;
+        let (t_units, p_units) = (config.temperature_units, config.pressure_units);
+        let (h_units, w_units) = (config.height_units, config.wind_speed_units);
+        let pw_units = config.precip_units;
+
+        let mut results = vec![(
+            format!(
+                "Layer {:.*}-{:.*} {}\n",
+                p_units.precision(),
+                p_units.value(measurement.bottom),
+                p_units.precision(),
+                p_units.value(measurement.top),
+                p_units.label()
+            ),
+            config.layer_measure_rgba,
+        )];
+
+        let mut line = format!(
+            "Depth {:.*} {}",
+            p_units.precision(),
+            p_units.value(measurement.depth()),
+            p_units.label()
+        );
+        if let Some(thickness) = measurement.thickness {
+            line.push_str(&format!(
+                ", {:.0} {}",
+                h_units.value(thickness),
+                h_units.label()
+            ));
+        }
+        line.push('\n');
+        results.push((line, default_color));
+
+        if let Some(lapse_rate) = measurement.lapse_rate {
+            results.push((
+                format!(
+                    "Lapse rate {:.1}{}/km\n",
+                    t_units.difference(lapse_rate),
+                    t_units.label()
+                ),
+                config.temperature_rgba,
+            ));
+        }
+
+        if let Some(mean_rh) = measurement.mean_rh {
+            results.push((format!("Mean RH {:.0}%\n", mean_rh), config.rh_rgba));
+        }
+
+        if let Some(mean_mw) = measurement.mean_mixing_ratio {
+            results.push((
+                format!("Mean mixing ratio {:.1} g/kg\n", mean_mw),
+                config.dew_point_rgba,
+            ));
+        }
+
+        if let Some(pw) = measurement.precipitable_water {
+            results.push((
+                format!(
+                    "PW {:.*} {}\n",
+                    pw_units.precision(),
+                    pw_units.value(pw),
+                    pw_units.label()
+                ),
+                config.dew_point_rgba,
+            ));
+        }
+
+        let winds = [
+            ("Bulk shear", measurement.bulk_shear),
+            ("Mean wind", measurement.mean_wind),
+        ];
+        for (name, wind) in winds {
+            if let Some(wind) = wind {
+                results.push((
+                    format!(
+                        "{} {:03.0} {:02.0}{}\n",
+                        name,
+                        wind.direction,
+                        w_units.value(wind.speed).round(),
+                        w_units.label()
+                    ),
+                    config.wind_rgba,
+                ));
+            }
+        }
+
+        results
+    }
+
     pub fn draw_sample_parcel_profile(
         args: DrawingArgs<'_, '_>,
         parcel_analysis: &Option<ParcelAscentAnalysis>,
@@ -324,12 +494,9 @@ impl SkewTContext {
                 temperature,
                 pressure,
             });
-            let deg_f = format!(
-                "{:.0}\u{00B0}F",
-                Fahrenheit::from(temperature).unpack().round()
-            );
+            let units = config.temperature_units;
             ac.skew_t.draw_tag(
-                &format!("{}/{:.0}\u{00B0}C", deg_f, temperature.unpack().round()),
+                &format!("{:.0}{}", units.value(temperature).round(), units.label()),
                 pos,
                 color,
                 args,
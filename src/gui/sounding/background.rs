@@ -1,10 +1,6 @@
 use super::SkewTContext;
-use crate::{
-    app::config::{self},
-    coords::TPCoords,
-    gui::DrawingArgs,
-};
-use metfor::{Celsius, CelsiusDiff, HectoPascal, Quantity};
+use crate::gui::{DrawingArgs, PlotContextExt};
+use metfor::{Celsius, CelsiusDiff};
 
 impl SkewTContext {
     pub fn draw_clear_background(&self, args: DrawingArgs<'_, '_>) {
@@ -53,35 +49,25 @@ impl SkewTContext {
 
     fn draw_temperature_band(&self, cold_t: Celsius, warm_t: Celsius, args: DrawingArgs<'_, '_>) {
         let cr = args.cr;
+        let geometry = self.geometry();
 
         // Assume color has already been set up for us.
 
-        const MAXP: HectoPascal = config::MAXP;
-        const MINP: HectoPascal = config::MINP;
-
-        let mut coords = [
-            (warm_t.unpack(), MAXP.unpack()),
-            (warm_t.unpack(), MINP.unpack()),
-            (cold_t.unpack(), MINP.unpack()),
-            (cold_t.unpack(), MAXP.unpack()),
-        ];
+        // Run the sides of the band all the way to the top and bottom edges, the corners of the
+        // chart are outside the pressure range on a tephigram.
+        let [warm_bottom, warm_top] = Self::isotherm_xy(warm_t, geometry);
+        let [cold_bottom, cold_top] = Self::isotherm_xy(cold_t, geometry);
 
         // Convert points to screen coords
-        for coord in &mut coords {
-            let screen_coords = self.convert_tp_to_screen(TPCoords {
-                temperature: Celsius(coord.0),
-                pressure: HectoPascal(coord.1),
-            });
-            coord.0 = screen_coords.x;
-            coord.1 = screen_coords.y;
-        }
+        let coords = [warm_bottom, warm_top, cold_top, cold_bottom]
+            .map(|xy_coords| self.convert_xy_to_screen(xy_coords));
 
         let mut coord_iter = coords.iter();
         for coord in coord_iter.by_ref().take(1) {
-            cr.move_to(coord.0, coord.1);
+            cr.move_to(coord.x, coord.y);
         }
         for coord in coord_iter {
-            cr.line_to(coord.0, coord.1);
+            cr.line_to(coord.x, coord.y);
         }
 
         cr.close_path();
@@ -1,21 +1,26 @@
 use super::SkewTContext;
 use crate::{
-    analysis::{Analysis, PrecipTypeAlgorithm},
-    app::config::{self},
+    analysis::{ensemble, plume::Pft, Analysis, PrecipTypeAlgorithm},
+    app::{
+        config::{self, EnsembleStyle, Rgba},
+        AppContext,
+    },
     coords::{ScreenCoords, TPCoords, XYCoords},
     gui::{
-        utility::{draw_filled_polygon, plot_curve_from_points},
+        utility::{draw_filled_polygon, plot_curve_from_points, plot_dashed_curve_from_points},
         Drawable, DrawingArgs, PlotContextExt,
     },
 };
 use itertools::izip;
 use log::warn;
-use metfor::{Celsius, Fahrenheit, HectoPascal, JpKg, Quantity};
-use sounding_analysis::{self, Parcel, ParcelAscentAnalysis};
-use std::iter::once;
+use metfor::{Celsius, HectoPascal, JpKg, Quantity};
+use sounding_analysis::{self, Parcel, ParcelAscentAnalysis, Sounding};
+use std::{cell::Ref, iter::once};
 
 mod precip_type;
 
+pub use self::precip_type::{draw_precip_symbol, PRECIP_BOX_SIZE};
+
 #[derive(Clone, Copy, Debug)]
 enum TemperatureType {
     DryBulb,
@@ -40,6 +45,176 @@ impl SkewTContext {
         }
     }
 
+    /// Draw the soundings chosen for comparison as faded, dashed lines behind the displayed one.
+    pub fn draw_ghost_profiles(args: DrawingArgs<'_, '_>) {
+        let (ac, cr) = (args.ac, args.cr);
+        let config = ac.config.borrow();
+
+        for ghost in ac.ghost_soundings() {
+            let ghost = ghost.borrow();
+            let sndg = ghost.sounding();
+
+            if config.show_dew_point {
+                plot_dashed_curve_from_points(
+                    cr,
+                    config.dew_point_line_width,
+                    config.ghost_rgba,
+                    Self::temperature_profile_points(TemperatureType::DewPoint, sndg, ac),
+                );
+            }
+
+            if config.show_temperature {
+                plot_dashed_curve_from_points(
+                    cr,
+                    config.temperature_line_width,
+                    config.ghost_rgba,
+                    Self::temperature_profile_points(TemperatureType::DryBulb, sndg, ac),
+                );
+            }
+        }
+    }
+
+    /// Draw the spread of the ensemble members as a shaded band with the median dashed.
+    pub fn draw_ensemble_envelope(args: DrawingArgs<'_, '_>) {
+        let (ac, cr) = (args.ac, args.cr);
+        let config = ac.config.borrow();
+
+        if config.ensemble_style != EnsembleStyle::Envelope {
+            return;
+        }
+
+        let members = ac.ensemble_members();
+        let members: Vec<Ref<'_, Analysis>> = members.iter().map(|anal| anal.borrow()).collect();
+        let sndgs: Vec<&Sounding> = members.iter().map(|anal| anal.sounding()).collect();
+        let env = ensemble::envelope(&sndgs);
+
+        let mut profiles = vec![];
+        if config.show_dew_point {
+            profiles.push((
+                &env.dew_point,
+                config.dew_point_rgba,
+                config.dew_point_line_width,
+            ));
+        }
+        if config.show_temperature {
+            profiles.push((
+                &env.temperature,
+                config.temperature_rgba,
+                config.temperature_line_width,
+            ));
+        }
+
+        let to_screen = |temperature: f64, pressure: HectoPascal| {
+            ac.skew_t.convert_tp_to_screen(TPCoords {
+                temperature: Celsius(temperature),
+                pressure,
+            })
+        };
+
+        for (spreads, rgba, line_width) in profiles {
+            let levels: Vec<_> = izip!(&env.pressure, spreads)
+                .filter_map(|(&p, spread)| spread.map(|spread| (p, spread)))
+                .filter(|(p, _)| *p > ac.skew_t.geometry().min_p)
+                .collect();
+
+            let up_side = levels.iter().map(|(p, spread)| to_screen(spread.max, *p));
+            let down_side = levels
+                .iter()
+                .rev()
+                .map(|(p, spread)| to_screen(spread.min, *p));
+            let fill_rgba = (rgba.0, rgba.1, rgba.2, config.ensemble_envelope_alpha);
+            draw_filled_polygon(cr, fill_rgba, up_side.chain(down_side));
+
+            let median = levels
+                .iter()
+                .map(|(p, spread)| to_screen(spread.median, *p));
+            plot_dashed_curve_from_points(cr, line_width, rgba, median);
+        }
+    }
+
+    /// Draw the dry adiabat, moist adiabat, and saturation mixing ratio line through each of the
+    /// reference points, labeled at their tops.
+    pub fn draw_reference_lines(args: DrawingArgs<'_, '_>) {
+        let (ac, cr) = (args.ac, args.cr);
+        let points = ac.reference_points();
+        if points.is_empty() {
+            return;
+        }
+
+        let config = ac.config.borrow();
+        let geometry = ac.skew_t.geometry();
+        let t_units = config.temperature_units;
+        let line_width = 2.0 * config.background_line_width;
+        let opaque = |rgba: Rgba| (rgba.0, rgba.1, rgba.2, 1.0);
+
+        for TPCoords {
+            temperature,
+            pressure,
+        } in points
+        {
+            let theta = metfor::potential_temperature(pressure, temperature);
+            let theta_e = metfor::equiv_pot_temperature(temperature, temperature, pressure);
+            let mw = metfor::mixing_ratio(temperature, pressure).map(|mw| mw * 1000.0);
+
+            let mut lines = vec![(
+                config::generate_isentrop(theta, geometry),
+                format!(
+                    "\u{03B8} {:.0}{}",
+                    t_units.value(Celsius::from(theta)),
+                    t_units.label()
+                ),
+                opaque(config.isentrop_rgba),
+            )];
+
+            if let Some(theta_e) = theta_e {
+                lines.push((
+                    config::generate_theta_e_isopleth(theta_e, geometry),
+                    format!(
+                        "\u{03B8}e {:.0}{}",
+                        t_units.value(Celsius::from(theta_e)),
+                        t_units.label()
+                    ),
+                    opaque(config.iso_theta_e_rgba),
+                ));
+            }
+
+            if let Some(mw) = mw {
+                // Reach at least as high as the point, even above where the background stops.
+                let top_p = if pressure < config::ISO_MIXING_RATIO_TOP_P {
+                    pressure
+                } else {
+                    config::ISO_MIXING_RATIO_TOP_P
+                };
+
+                lines.push((
+                    config::generate_iso_mixing_ratio_to(mw, top_p, geometry),
+                    format!("{:.1} g/kg", mw),
+                    opaque(config.iso_mixing_ratio_rgba),
+                ));
+            }
+
+            for (pnts, label, rgba) in lines {
+                plot_curve_from_points(
+                    cr,
+                    line_width,
+                    rgba,
+                    pnts.iter().map(|&xy| ac.skew_t.convert_xy_to_screen(xy)),
+                );
+
+                if let Some(&top) = pnts.iter().max_by(|a, b| a.y.total_cmp(&b.y)) {
+                    let pos = ac.skew_t.convert_xy_to_screen(top);
+                    ac.skew_t.draw_tag(&label, pos, rgba, args);
+                }
+            }
+
+            let pos = ac.skew_t.convert_tp_to_screen(TPCoords {
+                temperature,
+                pressure,
+            });
+            Self::draw_point(pos, config.label_rgba, args);
+        }
+    }
+
     fn draw_temperature_profile(t_type: TemperatureType, args: DrawingArgs<'_, '_>) {
         let (ac, cr) = (args.ac, args.cr);
         let config = ac.config.borrow();
@@ -52,14 +227,6 @@ impl SkewTContext {
 
         let anal = anal.borrow();
 
-        let sndg = anal.sounding();
-        let pres_data = sndg.pressure_profile();
-        let temp_data = match t_type {
-            TemperatureType::DryBulb => sndg.temperature_profile(),
-            TemperatureType::WetBulb => sndg.wet_bulb_profile(),
-            TemperatureType::DewPoint => sndg.dew_point_profile(),
-        };
-
         let line_width = match t_type {
             TemperatureType::DryBulb => config.temperature_line_width,
             TemperatureType::WetBulb => config.wet_bulb_line_width,
@@ -72,9 +239,27 @@ impl SkewTContext {
             TemperatureType::DewPoint => config.dew_point_rgba,
         };
 
-        let profile_data = izip!(pres_data, temp_data).filter_map(|(pres, temp)| {
+        let profile_data = Self::temperature_profile_points(t_type, anal.sounding(), ac);
+
+        plot_curve_from_points(cr, line_width, line_rgba, profile_data);
+    }
+
+    fn temperature_profile_points<'a>(
+        t_type: TemperatureType,
+        sndg: &'a Sounding,
+        ac: &'a AppContext,
+    ) -> impl Iterator<Item = ScreenCoords> + 'a {
+        let min_p = ac.skew_t.geometry().min_p;
+        let pres_data = sndg.pressure_profile();
+        let temp_data = match t_type {
+            TemperatureType::DryBulb => sndg.temperature_profile(),
+            TemperatureType::WetBulb => sndg.wet_bulb_profile(),
+            TemperatureType::DewPoint => sndg.dew_point_profile(),
+        };
+
+        izip!(pres_data, temp_data).filter_map(move |(pres, temp)| {
             if let (Some(pressure), Some(temperature)) = (pres.into(), temp.into()) {
-                if pressure > config::MINP {
+                if pressure > min_p {
                     let tp_coords = TPCoords {
                         temperature,
                         pressure,
@@ -86,9 +271,7 @@ impl SkewTContext {
             } else {
                 None
             }
-        });
-
-        plot_curve_from_points(cr, line_width, line_rgba, profile_data);
+        })
     }
 
     pub fn draw_data_overlays(args: DrawingArgs<'_, '_>) {
@@ -203,6 +386,25 @@ impl SkewTContext {
             });
         }
 
+        for parcel in config.custom_parcels.iter().filter(|pcl| pcl.show) {
+            if let Some(p_analysis) = anal.custom_parcel_analysis(parcel.source) {
+                let p_profile = p_analysis.profile();
+
+                Self::draw_parcel_profile(args, p_profile, parcel.rgba);
+
+                // Label the parcel where it starts.
+                if let (Some(&pressure), Some(&temperature)) =
+                    (p_profile.pressure.first(), p_profile.parcel_t.first())
+                {
+                    let pos = ac.skew_t.convert_tp_to_screen(TPCoords {
+                        temperature,
+                        pressure,
+                    });
+                    ac.skew_t.draw_tag(&parcel.name, pos, parcel.rgba, args);
+                }
+            }
+        }
+
         if config.show_downburst {
             Self::draw_downburst(args, &anal);
         }
@@ -219,19 +421,16 @@ impl SkewTContext {
                 Self::draw_parcel_profile(args, &parcel_profile, color);
 
                 if let (Some(&pressure), Some(&temperature)) = (
-                    parcel_profile.pressure.get(0),
-                    parcel_profile.parcel_t.get(0),
+                    parcel_profile.pressure.first(),
+                    parcel_profile.parcel_t.first(),
                 ) {
                     let pos = ac.skew_t.convert_tp_to_screen(TPCoords {
                         temperature,
                         pressure,
                     });
-                    let deg_f = format!(
-                        "{:.0}\u{00B0}F",
-                        Fahrenheit::from(temperature).unpack().round()
-                    );
+                    let units = config.temperature_units;
                     ac.skew_t.draw_tag(
-                        &format!("{}/{:.0}\u{00B0}C", deg_f, temperature.unpack().round()),
+                        &format!("{:.0}{}", units.value(temperature).round(), units.label()),
                         pos,
                         color,
                         args,
@@ -304,7 +503,7 @@ impl SkewTContext {
         let line_width = config.pft_line_width;
 
         // This plots an SP-curve and the cloud parcel above the LFC.
-        let plot_single_sp_curve_cloud_parcel = |pft_anal: &sounding_analysis::PFTAnalysis| {
+        let plot_single_sp_curve_cloud_parcel = |pft_anal: &Pft| {
             let sp_curve = pft_anal.sp_curve.iter().map(|(p, t)| {
                 let tp_coords = TPCoords {
                     temperature: *t,
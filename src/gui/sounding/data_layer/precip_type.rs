@@ -7,7 +7,8 @@ use crate::{
 };
 use sounding_analysis::{self, PrecipType};
 
-const PRECIP_BOX_SIZE: f64 = 0.07;
+/// Size of a weather symbol and the box around it on the skew-t.
+pub const PRECIP_BOX_SIZE: f64 = 0.07;
 
 impl SkewTContext {
     pub fn draw_precip_icon(
@@ -73,54 +74,7 @@ impl SkewTContext {
         };
 
         cr.move_to(box_center.x, box_center.y);
-        use PrecipType::*;
-        match wx_symbol_code {
-            // Drizzle
-            LightDrizzle => draw_point_symbol(cr, Intensity::Light, GREEN, draw_drizzle_comma),
-            ModerateDrizzle => {
-                draw_point_symbol(cr, Intensity::Moderate, GREEN, draw_drizzle_comma)
-            }
-            HeavyDrizzle => draw_point_symbol(cr, Intensity::Heavy, GREEN, draw_drizzle_comma),
-            LightFreezingDrizzle => {
-                draw_freezing_liquid_precip(cr, Intensity::Light, draw_drizzle_comma)
-            }
-            ModerateFreezingDrizzle => {
-                draw_freezing_liquid_precip(cr, Intensity::Moderate, draw_drizzle_comma)
-            }
-            HeavyFreezingDrizzle => {
-                draw_freezing_liquid_precip(cr, Intensity::Heavy, draw_drizzle_comma)
-            }
-
-            // Rain
-            LightRain => draw_point_symbol(cr, Intensity::Light, GREEN, draw_rain_dot),
-            ModerateRain => draw_point_symbol(cr, Intensity::Moderate, GREEN, draw_rain_dot),
-            HeavyRain => draw_point_symbol(cr, Intensity::Heavy, GREEN, draw_rain_dot),
-            LightFreezingRain => draw_freezing_liquid_precip(cr, Intensity::Light, draw_rain_dot),
-            ModerateFreezingRain => {
-                draw_freezing_liquid_precip(cr, Intensity::Moderate, draw_rain_dot)
-            }
-            HeavyFreezingRain => draw_freezing_liquid_precip(cr, Intensity::Heavy, draw_rain_dot),
-            LightRainAndSnow => draw_mixed_rain_snow(cr, Intensity::Light),
-            ModerateRainAndSnow => draw_mixed_rain_snow(cr, Intensity::Moderate),
-
-            // Ice precipitation
-            LightSnow => draw_point_symbol(cr, Intensity::Light, BLUE, draw_snowflake),
-            ModerateSnow => draw_point_symbol(cr, Intensity::Moderate, BLUE, draw_snowflake),
-            HeavySnow => draw_point_symbol(cr, Intensity::Heavy, BLUE, draw_snowflake),
-            LightIcePellets => draw_ice_pellets(cr),
-            ModerateIcePellets => draw_point_symbol(cr, Intensity::Moderate, RED, draw_ice_pellet),
-            HeavyIcePellets => draw_point_symbol(cr, Intensity::Heavy, RED, draw_ice_pellet),
-
-            // Showers
-            LightRainShowers => draw_showers(cr, Intensity::Light, GREEN, draw_rain_dot),
-            ModerateRainShowers => draw_showers(cr, Intensity::Moderate, GREEN, draw_rain_dot),
-            HeavyRainShowers => draw_showers(cr, Intensity::Heavy, GREEN, draw_rain_dot),
-            LightSnowShowers => draw_showers(cr, Intensity::Light, BLUE, draw_snowflake),
-            ModerateSnowShowers => draw_showers(cr, Intensity::Moderate, BLUE, draw_snowflake),
-            HeavySnowShowers => draw_showers(cr, Intensity::Heavy, BLUE, draw_snowflake),
-
-            _ => draw_red_x(cr),
-        }
+        draw_precip_symbol(cr, wx_symbol_code);
 
         let mut text_home = ScreenCoords {
             x: box_area.lower_left.x + padding,
@@ -146,6 +100,55 @@ impl SkewTContext {
         cr.stroke().unwrap();
     }
 }
+
+/// Draw the weather symbol for `code` centered on the current point.
+pub fn draw_precip_symbol(cr: &gtk::cairo::Context, code: PrecipType) {
+    use PrecipType::*;
+    match code {
+        // Drizzle
+        LightDrizzle => draw_point_symbol(cr, Intensity::Light, GREEN, draw_drizzle_comma),
+        ModerateDrizzle => draw_point_symbol(cr, Intensity::Moderate, GREEN, draw_drizzle_comma),
+        HeavyDrizzle => draw_point_symbol(cr, Intensity::Heavy, GREEN, draw_drizzle_comma),
+        LightFreezingDrizzle => {
+            draw_freezing_liquid_precip(cr, Intensity::Light, draw_drizzle_comma)
+        }
+        ModerateFreezingDrizzle => {
+            draw_freezing_liquid_precip(cr, Intensity::Moderate, draw_drizzle_comma)
+        }
+        HeavyFreezingDrizzle => {
+            draw_freezing_liquid_precip(cr, Intensity::Heavy, draw_drizzle_comma)
+        }
+
+        // Rain
+        LightRain => draw_point_symbol(cr, Intensity::Light, GREEN, draw_rain_dot),
+        ModerateRain => draw_point_symbol(cr, Intensity::Moderate, GREEN, draw_rain_dot),
+        HeavyRain => draw_point_symbol(cr, Intensity::Heavy, GREEN, draw_rain_dot),
+        LightFreezingRain => draw_freezing_liquid_precip(cr, Intensity::Light, draw_rain_dot),
+        ModerateFreezingRain => draw_freezing_liquid_precip(cr, Intensity::Moderate, draw_rain_dot),
+        HeavyFreezingRain => draw_freezing_liquid_precip(cr, Intensity::Heavy, draw_rain_dot),
+        LightRainAndSnow => draw_mixed_rain_snow(cr, Intensity::Light),
+        ModerateRainAndSnow => draw_mixed_rain_snow(cr, Intensity::Moderate),
+
+        // Ice precipitation
+        LightSnow => draw_point_symbol(cr, Intensity::Light, BLUE, draw_snowflake),
+        ModerateSnow => draw_point_symbol(cr, Intensity::Moderate, BLUE, draw_snowflake),
+        HeavySnow => draw_point_symbol(cr, Intensity::Heavy, BLUE, draw_snowflake),
+        LightIcePellets => draw_ice_pellets(cr),
+        ModerateIcePellets => draw_point_symbol(cr, Intensity::Moderate, RED, draw_ice_pellet),
+        HeavyIcePellets => draw_point_symbol(cr, Intensity::Heavy, RED, draw_ice_pellet),
+
+        // Showers
+        LightRainShowers => draw_showers(cr, Intensity::Light, GREEN, draw_rain_dot),
+        ModerateRainShowers => draw_showers(cr, Intensity::Moderate, GREEN, draw_rain_dot),
+        HeavyRainShowers => draw_showers(cr, Intensity::Heavy, GREEN, draw_rain_dot),
+        LightSnowShowers => draw_showers(cr, Intensity::Light, BLUE, draw_snowflake),
+        ModerateSnowShowers => draw_showers(cr, Intensity::Moderate, BLUE, draw_snowflake),
+        HeavySnowShowers => draw_showers(cr, Intensity::Heavy, BLUE, draw_snowflake),
+
+        _ => draw_red_x(cr),
+    }
+}
+
 fn draw_point_symbol<F: Fn(&gtk::cairo::Context, f64)>(
     cr: &gtk::cairo::Context,
     inten: Intensity,
@@ -0,0 +1,302 @@
+use super::SkewTContext;
+use crate::{
+    app::{AppContext, AppContextPointer, EditTarget},
+    coords::{DeviceCoords, ScreenCoords, TPCoords},
+    errors::SondeError,
+    gui::{Drawable, DrawingArgs, PlotContextExt},
+};
+use chrono::{Duration, NaiveDateTime};
+use gtk::{prelude::*, Dialog, DialogFlags, Grid, Label, ResponseType, SpinButton, Window};
+use itertools::izip;
+use metfor::{Celsius, Quantity};
+use std::rc::Rc;
+
+/// How close, in pixels, the cursor must be to a point to grab it for editing.
+const EDIT_HIT_RADIUS: f64 = 8.0;
+
+impl SkewTContext {
+    /// Find the temperature or dew point closest to the cursor, if there is one close enough.
+    pub fn find_edit_target(&self, position: DeviceCoords, ac: &AppContext) -> Option<EditTarget> {
+        let anal = ac.get_sounding_for_display()?;
+        let anal = anal.borrow();
+        let snd = anal.sounding();
+        let config = ac.config.borrow();
+
+        let cursor = self.convert_device_to_screen(position);
+        let max_dist = EDIT_HIT_RADIUS / self.scale_factor();
+
+        let distance = |coords: ScreenCoords| {
+            ((coords.x - cursor.x).powi(2) + (coords.y - cursor.y).powi(2)).sqrt()
+        };
+
+        izip!(
+            0..,
+            snd.pressure_profile(),
+            snd.temperature_profile(),
+            snd.dew_point_profile()
+        )
+        .filter_map(|(i, p, t, dp)| p.into_option().map(|p| (i, p, t, dp)))
+        .flat_map(|(i, pressure, t, dp)| {
+            let t = t
+                .into_option()
+                .filter(|_| config.show_temperature)
+                .map(|temperature| {
+                    (
+                        EditTarget::Temperature(i),
+                        TPCoords {
+                            temperature,
+                            pressure,
+                        },
+                    )
+                });
+            let dp = dp
+                .into_option()
+                .filter(|_| config.show_dew_point)
+                .map(|temperature| {
+                    (
+                        EditTarget::DewPoint(i),
+                        TPCoords {
+                            temperature,
+                            pressure,
+                        },
+                    )
+                });
+
+            t.into_iter().chain(dp)
+        })
+        .map(|(target, tp)| (target, distance(self.convert_tp_to_screen(tp))))
+        .filter(|&(_, dist)| dist <= max_dist)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(target, _)| target)
+    }
+
+    /// Move the point being edited to the cursor, keeping it at the same pressure level.
+    pub fn drag_edit_target(
+        &self,
+        target: EditTarget,
+        position: DeviceCoords,
+        ac: &AppContextPointer,
+    ) {
+        let i = match target {
+            EditTarget::Temperature(i) | EditTarget::DewPoint(i) => i,
+            EditTarget::Wind(_) => return,
+        };
+
+        let pressure = match ac.get_sounding_for_display().and_then(|anal| {
+            anal.borrow()
+                .sounding()
+                .pressure_profile()
+                .get(i)
+                .and_then(|p| p.into_option())
+        }) {
+            Some(p) => p,
+            None => return,
+        };
+
+        let cursor = self.convert_device_to_xy(position);
+        let temperature = Self::temperature_at(cursor.x, pressure, self.geometry());
+
+        AppContext::drag_temperature_edit(ac, temperature);
+    }
+
+    /// Mark the points that can be dragged while in editing mode.
+    pub fn draw_edit_handles(args: DrawingArgs<'_, '_>) {
+        let ac = args.ac;
+
+        if !ac.is_editing() {
+            return;
+        }
+
+        let anal = match ac.get_sounding_for_display() {
+            Some(anal) => anal,
+            None => return,
+        };
+        let anal = anal.borrow();
+        let snd = anal.sounding();
+        let config = ac.config.borrow();
+
+        for (pressure, t, dp) in izip!(
+            snd.pressure_profile(),
+            snd.temperature_profile(),
+            snd.dew_point_profile()
+        ) {
+            let pressure = match pressure.into_option() {
+                Some(p) => p,
+                None => continue,
+            };
+
+            if let Some(temperature) = t.into_option().filter(|_| config.show_temperature) {
+                let coords = ac.skew_t.convert_tp_to_screen(TPCoords {
+                    temperature,
+                    pressure,
+                });
+                Self::draw_point(coords, config.temperature_rgba, args);
+            }
+
+            if let Some(temperature) = dp.into_option().filter(|_| config.show_dew_point) {
+                let coords = ac.skew_t.convert_tp_to_screen(TPCoords {
+                    temperature,
+                    pressure,
+                });
+                Self::draw_point(coords, config.dew_point_rgba, args);
+            }
+        }
+    }
+
+    /// Ask for a new surface temperature and dew point and mix the boundary layer up from them.
+    pub fn show_modify_surface_dialog(acp: &AppContextPointer) -> Result<(), SondeError> {
+        let win: Window = acp.fetch_widget("main_window")?;
+
+        let (temperature, dew_point) = match acp.get_sounding_for_display() {
+            Some(anal) => anal
+                .borrow()
+                .sounding()
+                .bottom_up()
+                .find(|row| row.temperature.is_some())
+                .map(|row| {
+                    let t = row.temperature.unwrap();
+                    (t, row.dew_point.unwrap_or(t))
+                })
+                .unwrap_or((Celsius(20.0), Celsius(0.0))),
+            None => return Ok(()),
+        };
+
+        let dialog = Dialog::with_buttons(
+            Some("Modify Surface"),
+            Some(&win),
+            DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT,
+            &[
+                ("Apply", ResponseType::Ok),
+                ("Cancel", ResponseType::Cancel),
+            ],
+        );
+
+        let grid = Grid::new();
+        grid.set_row_spacing(5);
+        grid.set_column_spacing(5);
+        grid.set_margin_top(5);
+        grid.set_margin_bottom(5);
+        grid.set_margin_start(5);
+        grid.set_margin_end(5);
+
+        let t_units = acp.config.borrow().temperature_units;
+        let (min_t, max_t) = (t_units.value(Celsius(-60.0)), t_units.value(Celsius(60.0)));
+
+        let t_spin = SpinButton::with_range(min_t, max_t, 0.5);
+        t_spin.set_digits(1);
+        t_spin.set_value(t_units.value(temperature));
+        let t_label = format!("Temperature ({})", t_units.label());
+        grid.attach(&Label::new(Some(&t_label)), 0, 0, 1, 1);
+        grid.attach(&t_spin, 1, 0, 1, 1);
+
+        let dp_spin = SpinButton::with_range(min_t, max_t, 0.5);
+        dp_spin.set_digits(1);
+        dp_spin.set_value(t_units.value(dew_point));
+        let dp_label = format!("Dew point ({})", t_units.label());
+        grid.attach(&Label::new(Some(&dp_label)), 0, 1, 1, 1);
+        grid.attach(&dp_spin, 1, 1, 1, 1);
+
+        dialog.content_area().append(&grid);
+
+        let ac = Rc::clone(acp);
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Ok {
+                let t = t_units.to_celsius(t_spin.value());
+                let dp = t_units.to_celsius(dp_spin.value());
+                AppContext::modify_surface(&ac, t, dp);
+            }
+
+            dialog.close();
+        });
+
+        dialog.show();
+
+        Ok(())
+    }
+
+    /// Ask for a time between the loaded soundings and insert a sounding interpolated to it.
+    pub fn show_interpolate_dialog(acp: &AppContextPointer) -> Result<(), SondeError> {
+        let win: Window = acp.fetch_widget("main_window")?;
+
+        let displayed_time = match acp
+            .get_sounding_for_display()
+            .and_then(|anal| anal.borrow().sounding().valid_time())
+        {
+            Some(vt) => vt,
+            None => return Ok(()),
+        };
+
+        let valid_times: Vec<_> = acp
+            .loaded_soundings()
+            .iter()
+            .filter_map(|anal| anal.borrow().sounding().valid_time())
+            .collect();
+        let hours_to = |vt: NaiveDateTime| (vt - displayed_time).num_minutes() as f64 / 60.0;
+        let (first, last) = match (valid_times.first(), valid_times.last()) {
+            (Some(&first), Some(&last)) if first < last => (hours_to(first), hours_to(last)),
+            _ => return Ok(()),
+        };
+
+        // Start halfway to the next sounding, or the previous one if this is the last.
+        let start = valid_times
+            .iter()
+            .find(|&&vt| vt > displayed_time)
+            .or_else(|| valid_times.iter().rev().find(|&&vt| vt < displayed_time))
+            .map(|&vt| hours_to(vt) / 2.0)
+            .unwrap_or(0.0);
+
+        let dialog = Dialog::with_buttons(
+            Some("Interpolate Sounding"),
+            Some(&win),
+            DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT,
+            &[
+                ("Insert", ResponseType::Ok),
+                ("Cancel", ResponseType::Cancel),
+            ],
+        );
+
+        let grid = Grid::new();
+        grid.set_row_spacing(5);
+        grid.set_column_spacing(5);
+        grid.set_margin_top(5);
+        grid.set_margin_bottom(5);
+        grid.set_margin_start(5);
+        grid.set_margin_end(5);
+
+        let hours_spin = SpinButton::with_range(first, last, 0.25);
+        hours_spin.set_digits(2);
+        hours_spin.set_value(start);
+        grid.attach(&Label::new(Some("Hours from displayed")), 0, 0, 1, 1);
+        grid.attach(&hours_spin, 1, 0, 1, 1);
+
+        let valid_time = move |spin: &SpinButton| {
+            displayed_time + Duration::minutes((spin.value() * 60.0).round() as i64)
+        };
+
+        let valid_label = Label::new(None);
+        let update_label = move |spin: &SpinButton, label: &Label| {
+            label.set_text(&valid_time(spin).format("%Y-%m-%d %H:%MZ").to_string());
+        };
+        update_label(&hours_spin, &valid_label);
+        grid.attach(&Label::new(Some("Valid")), 0, 1, 1, 1);
+        grid.attach(&valid_label, 1, 1, 1, 1);
+
+        let label = valid_label.clone();
+        hours_spin.connect_value_changed(move |spin| update_label(spin, &label));
+
+        dialog.content_area().append(&grid);
+
+        let ac = Rc::clone(acp);
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Ok {
+                AppContext::insert_interpolated(&ac, valid_time(&hours_spin));
+            }
+
+            dialog.close();
+        });
+
+        dialog.show();
+
+        Ok(())
+    }
+}
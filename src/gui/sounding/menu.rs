@@ -3,11 +3,17 @@ use gtk::{
     prelude::*,
     Window,
 };
+use metfor::{HectoPascal, Quantity};
 
 use super::SkewTContext;
 
 use crate::{
-    app::{config::ParcelType, AppContextPointer},
+    app::{
+        config::{
+            EnsembleStyle, HeightReference, ParcelType, SampleStep, ThermoDiagram, WindBarbLevels,
+        },
+        AppContextPointer,
+    },
     errors::SondeError,
 };
 
@@ -68,6 +74,44 @@ impl SkewTContext {
             show_sample_mix_down
         );
 
+        // Set how far the up and down keys move the sample
+        let current_step = {
+            let config = acp.config.borrow();
+            match config.sample_step {
+                SampleStep::DataLevel => "level".to_owned(),
+                SampleStep::Pressure => format!("{:.0}", config.sample_step_pressure.unpack()),
+            }
+        };
+
+        let ac = acp.clone();
+        let step_variant = unsafe {
+            &gtk::glib::Variant::from_data_with_type_trusted(
+                current_step.as_str(),
+                gtk::glib::VariantTy::STRING,
+            )
+        };
+        let step_action = SimpleAction::new_stateful(
+            "sample_step",
+            Some(gtk::glib::VariantTy::STRING),
+            step_variant,
+        );
+
+        step_action.connect_activate(move |action, variant| {
+            let var = variant.unwrap();
+            let val: &str = var.str().unwrap();
+            action.set_state(var);
+
+            let mut config = ac.config.borrow_mut();
+            match val {
+                "level" => config.sample_step = SampleStep::DataLevel,
+                hpa => {
+                    config.sample_step = SampleStep::Pressure;
+                    config.sample_step_pressure = HectoPascal(hpa.parse().unwrap());
+                }
+            }
+        });
+        skew_t_group.add_action(&step_action);
+
         // Set the parcel type
         let current_parcel = match acp.config.borrow().parcel_type {
             ParcelType::Surface => "surface",
@@ -136,6 +180,251 @@ impl SkewTContext {
         make_check_item!(skew_t_group, "show_wet_bulb", acp, show_wet_bulb);
         make_check_item!(skew_t_group, "show_dew_point", acp, show_dew_point);
         make_check_item!(skew_t_group, "show_wind_profile", acp, show_wind_profile);
+        make_check_item!(
+            skew_t_group,
+            "color_wind_barbs_by_speed",
+            acp,
+            color_wind_barbs_by_speed
+        );
+
+        make_check_item!(
+            skew_t_group,
+            "show_previous_ghost",
+            acp,
+            show_previous_ghost
+        );
+        make_check_item!(skew_t_group, "show_next_ghost", acp, show_next_ghost);
+
+        let ac = acp.clone();
+        let action = SimpleAction::new("pin_ghost", None);
+        action.connect_activate(move |_action, _variant| ac.pin_displayed_ghost());
+        skew_t_group.add_action(&action);
+
+        let ac = acp.clone();
+        let action = SimpleAction::new("clear_ghosts", None);
+        action.connect_activate(move |_action, _variant| ac.clear_ghosts());
+        skew_t_group.add_action(&action);
+
+        let ac = acp.clone();
+        let action = SimpleAction::new("clear_pinned_samples", None);
+        action.connect_activate(move |_action, _variant| ac.clear_pinned_samples());
+        skew_t_group.add_action(&action);
+
+        let ac = acp.clone();
+        let action = SimpleAction::new("measure_layer", None);
+        action.connect_activate(move |_action, _variant| ac.toggle_measuring_layer());
+        skew_t_group.add_action(&action);
+
+        let ac = acp.clone();
+        let action = SimpleAction::new("place_reference_lines", None);
+        action.connect_activate(move |_action, _variant| ac.toggle_placing_reference_lines());
+        skew_t_group.add_action(&action);
+
+        let ac = acp.clone();
+        let action = SimpleAction::new("clear_reference_lines", None);
+        action.connect_activate(move |_action, _variant| ac.clear_reference_lines());
+        skew_t_group.add_action(&action);
+
+        let ac = acp.clone();
+        let action = SimpleAction::new("add_custom_parcels", None);
+        action.connect_activate(move |_action, _variant| ac.toggle_adding_custom_parcels());
+        skew_t_group.add_action(&action);
+
+        // Set the ensemble style
+        let current_style = match acp.config.borrow().ensemble_style {
+            EnsembleStyle::Spaghetti => "spaghetti",
+            EnsembleStyle::Envelope => "envelope",
+        };
+
+        let ac = acp.clone();
+        let style_variant = unsafe {
+            &gtk::glib::Variant::from_data_with_type_trusted(
+                current_style,
+                gtk::glib::VariantTy::STRING,
+            )
+        };
+        let style_action = SimpleAction::new_stateful(
+            "ensemble_style",
+            Some(gtk::glib::VariantTy::STRING),
+            style_variant,
+        );
+
+        style_action.connect_activate(move |action, variant| {
+            let var = variant.unwrap();
+            let val: &str = var.str().unwrap();
+            action.set_state(var);
+
+            let style = match val {
+                "spaghetti" => EnsembleStyle::Spaghetti,
+                "envelope" => EnsembleStyle::Envelope,
+                _ => unreachable!(),
+            };
+
+            ac.config.borrow_mut().ensemble_style = style;
+            ac.mark_data_dirty();
+            crate::gui::draw_all(&ac);
+        });
+        skew_t_group.add_action(&style_action);
+
+        // Set the wind barb levels
+        let current_levels = match acp.config.borrow().wind_barb_levels {
+            WindBarbLevels::Thinned => "thinned",
+            WindBarbLevels::EveryLevel => "every",
+            WindBarbLevels::PressureInterval => "pressure",
+            WindBarbLevels::HeightInterval => "height",
+            WindBarbLevels::Mandatory => "mandatory",
+        };
+
+        let ac = acp.clone();
+        let levels_variant = unsafe {
+            &gtk::glib::Variant::from_data_with_type_trusted(
+                current_levels,
+                gtk::glib::VariantTy::STRING,
+            )
+        };
+        let levels_action = SimpleAction::new_stateful(
+            "wind_barb_levels",
+            Some(gtk::glib::VariantTy::STRING),
+            levels_variant,
+        );
+
+        levels_action.connect_activate(move |action, variant| {
+            let var = variant.unwrap();
+            let val: &str = var.str().unwrap();
+            action.set_state(var);
+
+            let levels = match val {
+                "thinned" => WindBarbLevels::Thinned,
+                "every" => WindBarbLevels::EveryLevel,
+                "pressure" => WindBarbLevels::PressureInterval,
+                "height" => WindBarbLevels::HeightInterval,
+                "mandatory" => WindBarbLevels::Mandatory,
+                _ => unreachable!(),
+            };
+
+            ac.config.borrow_mut().wind_barb_levels = levels;
+            ac.mark_data_dirty();
+            crate::gui::draw_all(&ac);
+        });
+        skew_t_group.add_action(&levels_action);
+
+        // Set the thermodynamic diagram
+        let current_diagram = match acp.config.borrow().thermo_diagram {
+            ThermoDiagram::SkewT => "skew-t",
+            ThermoDiagram::Emagram => "emagram",
+            ThermoDiagram::Stuve => "stuve",
+            ThermoDiagram::Tephigram => "tephigram",
+        };
+
+        let ac = acp.clone();
+        let diagram_variant = unsafe {
+            &gtk::glib::Variant::from_data_with_type_trusted(
+                current_diagram,
+                gtk::glib::VariantTy::STRING,
+            )
+        };
+        let diagram_action = SimpleAction::new_stateful(
+            "thermo_diagram",
+            Some(gtk::glib::VariantTy::STRING),
+            diagram_variant,
+        );
+
+        diagram_action.connect_activate(move |action, variant| {
+            let var = variant.unwrap();
+            let val: &str = var.str().unwrap();
+            action.set_state(var);
+
+            let diagram = match val {
+                "skew-t" => ThermoDiagram::SkewT,
+                "emagram" => ThermoDiagram::Emagram,
+                "stuve" => ThermoDiagram::Stuve,
+                "tephigram" => ThermoDiagram::Tephigram,
+                _ => unreachable!(),
+            };
+
+            ac.config.borrow_mut().thermo_diagram = diagram;
+            ac.update_skew_t_geometry();
+        });
+        skew_t_group.add_action(&diagram_action);
+
+        // Set the height axis
+        let current_axis = {
+            let config = acp.config.borrow();
+            match (config.show_height_axis, config.height_axis_reference) {
+                (false, _) => "none",
+                (true, HeightReference::Agl) => "agl",
+                (true, HeightReference::Msl) => "msl",
+            }
+        };
+
+        let ac = acp.clone();
+        let axis_variant = unsafe {
+            &gtk::glib::Variant::from_data_with_type_trusted(
+                current_axis,
+                gtk::glib::VariantTy::STRING,
+            )
+        };
+        let axis_action = SimpleAction::new_stateful(
+            "height_axis",
+            Some(gtk::glib::VariantTy::STRING),
+            axis_variant,
+        );
+
+        axis_action.connect_activate(move |action, variant| {
+            let var = variant.unwrap();
+            let val: &str = var.str().unwrap();
+            action.set_state(var);
+
+            // Nested scope for borrows.
+            {
+                let mut config = ac.config.borrow_mut();
+                match val {
+                    "none" => config.show_height_axis = false,
+                    _ => {
+                        config.show_height_axis = true;
+                        config.height_axis_reference = match val {
+                            "agl" => HeightReference::Agl,
+                            "msl" => HeightReference::Msl,
+                            _ => unreachable!(),
+                        };
+                    }
+                }
+            }
+
+            ac.mark_background_dirty();
+            crate::gui::draw_all(&ac);
+        });
+        skew_t_group.add_action(&axis_action);
+
+        let ac = acp.clone();
+        let action = SimpleAction::new("set_difference_reference", None);
+        action.connect_activate(move |_action, _variant| ac.set_difference_reference());
+        skew_t_group.add_action(&action);
+
+        let ac = acp.clone();
+        let action = SimpleAction::new("clear_difference_reference", None);
+        action.connect_activate(move |_action, _variant| ac.clear_difference_reference());
+        skew_t_group.add_action(&action);
+
+        crate::gui::add_edit_actions(&skew_t_group, acp);
+
+        let ac = acp.clone();
+        let action = SimpleAction::new("modify_surface", None);
+        action.connect_activate(move |_action, _variant| {
+            if let Err(err) = SkewTContext::show_modify_surface_dialog(&ac) {
+                eprintln!("Error showing dialog: {}", err);
+            }
+        });
+        skew_t_group.add_action(&action);
+
+        let ac = acp.clone();
+        let action = SimpleAction::new("interpolate_sounding", None);
+        action.connect_activate(move |_action, _variant| {
+            if let Err(err) = SkewTContext::show_interpolate_dialog(&ac) {
+                eprintln!("Error showing dialog: {}", err);
+            }
+        });
+        skew_t_group.add_action(&action);
 
         Ok(())
     }
@@ -0,0 +1,83 @@
+//! Coordinates for the tephigram.
+//!
+//! The axes are temperature and the log of potential temperature, turned 45 degrees so the
+//! isotherms lean to the right, the dry adiabats lean to the left, and the isobars are nearly
+//! level. The isobars are flattest where they cross 0C, and they are scaled to be at the same
+//! height there as on the skew-t, so the profiles beside the chart still line up. The left and
+//! right edges are where the coldest and warmest temperatures cross the bottom isobar.
+use crate::coords::{SkewTGeometry, TPCoords, XYCoords, KAPPA};
+use metfor::{Celsius, HectoPascal, Kelvin, Quantity};
+
+/// Temperature where the isobars are level, K.
+const T0: f64 = 273.15;
+
+pub(super) fn convert_tp_to_xy(coords: TPCoords, geometry: SkewTGeometry) -> XYCoords {
+    let t = Kelvin::from(coords.temperature).unpack();
+    let lift = lift(coords.pressure, geometry);
+
+    let x = (along(t) + lift - along_min(geometry)) / width(geometry);
+    let y = (across(t) + lift) / height(geometry);
+
+    XYCoords { x, y }
+}
+
+pub(super) fn convert_xy_to_tp(coords: XYCoords, geometry: SkewTGeometry) -> TPCoords {
+    let along_t_and_lift = coords.x * width(geometry) + along_min(geometry);
+    let across_t_and_lift = coords.y * height(geometry);
+
+    // The potential temperature parts cancel, leaving 2T - T0. Stay above absolute zero for points
+    // far off the chart.
+    let t = ((along_t_and_lift - across_t_and_lift + T0) / 2.0).max(1.0);
+    let lift = across_t_and_lift - across(t);
+
+    TPCoords {
+        temperature: Celsius::from(Kelvin(t)),
+        pressure: geometry.max_p * (-lift / (T0 * KAPPA)).exp(),
+    }
+}
+
+/// Find the temperature at a horizontal position along an isobar.
+pub(super) fn temperature_at(x: f64, pressure: HectoPascal, geometry: SkewTGeometry) -> Celsius {
+    let target = x * width(geometry) + along_min(geometry) - lift(pressure, geometry);
+
+    // Newton's method, along() is smooth and always increasing.
+    let mut t = T0;
+    for _ in 0..20 {
+        let dt = (along(t) - target) / (1.0 + T0 / t);
+        t = (t - dt).max(1.0);
+
+        if dt.abs() < 1.0e-6 {
+            break;
+        }
+    }
+
+    Celsius::from(Kelvin(t))
+}
+
+// How far a temperature is along the x-axis, leaving out the part from the pressure.
+fn along(t: f64) -> f64 {
+    t + T0 * (t / T0).ln()
+}
+
+// How far a temperature is along the y-axis, leaving out the part from the pressure. Zero at T0.
+fn across(t: f64) -> f64 {
+    T0 * (t / T0).ln() - (t - T0)
+}
+
+// How far the potential temperature axis moves everything up and to the right at a pressure
+// compared to the bottom of the chart.
+fn lift(pressure: HectoPascal, geometry: SkewTGeometry) -> f64 {
+    T0 * KAPPA * (geometry.max_p / pressure).ln()
+}
+
+fn along_min(geometry: SkewTGeometry) -> f64 {
+    along(Kelvin::from(geometry.min_t).unpack())
+}
+
+fn width(geometry: SkewTGeometry) -> f64 {
+    along(Kelvin::from(geometry.max_t).unpack()) - along_min(geometry)
+}
+
+fn height(geometry: SkewTGeometry) -> f64 {
+    lift(geometry.min_p, geometry)
+}
@@ -1,14 +1,16 @@
 use super::SkewTContext;
 use crate::{
-    app::config::{self},
+    analysis::{pressure_at_height, surface},
+    app::config::{Config, WindBarbLevels, MANDATORY_LEVELS},
     coords::{Rect, ScreenCoords, ScreenRect, TPCoords, XYCoords},
     gui::{DrawingArgs, PlotContextExt},
 };
 use gtk::cairo::Context;
 use itertools::izip;
-use metfor::{Celsius, HectoPascal, Knots, WindSpdDir};
+use metfor::{Celsius, HectoPascal, Knots, Quantity, WindSpdDir};
+use sounding_analysis::Sounding;
 
-struct WindBarbConfig {
+pub struct WindBarbConfig {
     shaft_length: f64,
     barb_length: f64,
     pennant_width: f64,
@@ -17,7 +19,8 @@ struct WindBarbConfig {
 }
 
 impl WindBarbConfig {
-    fn init(args: DrawingArgs<'_, '_>) -> Self {
+    /// The size of the barbs in screen coordinates, for plots that place the barbs themselves.
+    pub fn sizes(args: DrawingArgs<'_, '_>) -> Self {
         let (ac, cr) = (args.ac, args.cr);
         let config = ac.config.borrow();
 
@@ -28,6 +31,22 @@ impl WindBarbConfig {
         let (dot_size, pennant_width) = cr
             .device_to_user_distance(config.wind_barb_dot_radius, -config.wind_barb_pennant_width)
             .unwrap();
+
+        WindBarbConfig {
+            shaft_length,
+            barb_length,
+            pennant_width,
+            xcoord: 0.0,
+            dot_size,
+        }
+    }
+
+    fn init(args: DrawingArgs<'_, '_>) -> Self {
+        let (ac, cr) = (args.ac, args.cr);
+        let config = ac.config.borrow();
+
+        let sizes = Self::sizes(args);
+
         let padding = cr
             .device_to_user_distance(config.edge_padding, 0.0)
             .unwrap()
@@ -44,19 +63,14 @@ impl WindBarbConfig {
         let ScreenCoords { x: xmax, .. } =
             ac.skew_t.convert_xy_to_screen(XYCoords { x: xmax, y: 0.0 });
 
-        let xcoord = xmax - padding - shaft_length;
+        let xcoord = xmax - padding - sizes.shaft_length;
 
-        WindBarbConfig {
-            shaft_length,
-            barb_length,
-            pennant_width,
-            xcoord,
-            dot_size,
-        }
+        WindBarbConfig { xcoord, ..sizes }
     }
 }
 
-struct WindBarbData {
+pub struct WindBarbData {
+    speed: Knots,
     center: ScreenCoords,
     shaft_end: ScreenCoords,
     num_pennants: usize,
@@ -75,6 +89,15 @@ impl WindBarbData {
     ) -> Self {
         let center = SkewTContext::get_wind_barb_center(pressure, barb_config.xcoord, args);
 
+        Self::create_at(center, wind, barb_config)
+    }
+
+    /// Create a barb centered on any point on the screen.
+    pub fn create_at(
+        center: ScreenCoords,
+        wind: WindSpdDir<Knots>,
+        barb_config: &WindBarbConfig,
+    ) -> Self {
         let WindSpdDir {
             speed: Knots(speed),
             direction,
@@ -178,6 +201,7 @@ impl WindBarbData {
         let point_radius = barb_config.dot_size;
 
         WindBarbData {
+            speed: Knots(speed),
             center,
             shaft_end,
             num_pennants,
@@ -188,7 +212,11 @@ impl WindBarbData {
         }
     }
 
-    fn bounding_box(&self) -> ScreenRect {
+    pub fn speed(&self) -> Knots {
+        self.speed
+    }
+
+    pub fn bounding_box(&self) -> ScreenRect {
         let mut bbox = ScreenRect {
             lower_left: ScreenCoords {
                 x: self.center.x - self.point_radius,
@@ -217,7 +245,7 @@ impl WindBarbData {
         bbox
     }
 
-    fn draw(&self, cr: &Context) {
+    pub fn draw(&self, cr: &Context) {
         // Assume color and line width are already taken care of.
         cr.arc(
             self.center.x,
@@ -269,12 +297,16 @@ impl SkewTContext {
             let anal = anal.borrow();
             let snd = anal.sounding();
 
+            let levels = config.wind_barb_levels;
             let barb_config = WindBarbConfig::init(args);
-            let barb_data = Self::gather_wind_data(snd, &barb_config, args);
-            let barb_data = Self::filter_wind_data(args, barb_data);
+            let winds = Self::gather_wind_data(snd, &config, ac.skew_t.geometry().min_p);
+            let barb_data = winds
+                .into_iter()
+                .map(|(p, w)| WindBarbData::create(p, w, &barb_config, args))
+                .collect();
+            let barb_data =
+                Self::filter_wind_data(args, barb_data, levels == WindBarbLevels::Thinned);
 
-            let rgba = config.wind_rgba;
-            cr.set_source_rgba(rgba.0, rgba.1, rgba.2, rgba.3);
             cr.set_line_width(
                 cr.device_to_user_distance(config.wind_barb_line_width, 0.0)
                     .unwrap()
@@ -282,35 +314,67 @@ impl SkewTContext {
             );
 
             for bdata in &barb_data {
+                let rgba = config.wind_barb_rgba(bdata.speed());
+                cr.set_source_rgba(rgba.0, rgba.1, rgba.2, rgba.3);
                 bdata.draw(cr);
             }
         }
     }
 
+    /// The pressure and wind at each level that gets a barb, levels above `min_p` are off the
+    /// chart.
     fn gather_wind_data(
-        snd: &::sounding_analysis::Sounding,
-        barb_config: &WindBarbConfig,
-        args: DrawingArgs<'_, '_>,
-    ) -> Vec<WindBarbData> {
-        let wind = snd.wind_profile();
-        let pres = snd.pressure_profile();
-
-        izip!(pres, wind)
-            .filter_map(|tuple| {
-                let (p, w) = (*tuple.0, *tuple.1);
-                if let (Some(p), Some(w)) = (p.into(), w.into()) {
-                    if p > config::MINP {
-                        Some((p, w))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
+        snd: &Sounding,
+        config: &Config,
+        min_p: HectoPascal,
+    ) -> Vec<(HectoPascal, WindSpdDir<Knots>)> {
+        let levels: Vec<HectoPascal> = match config.wind_barb_levels {
+            WindBarbLevels::Thinned | WindBarbLevels::EveryLevel => {
+                let wind = snd.wind_profile();
+                let pres = snd.pressure_profile();
+
+                return izip!(pres, wind)
+                    .filter_map(|(p, w)| p.into_option().zip(w.into_option()))
+                    .filter(|&(p, _)| p > min_p)
+                    .collect();
+            }
+            WindBarbLevels::PressureInterval => {
+                let step = config.wind_barb_pressure_interval.unpack().max(1.0);
+                let sfc_p = match surface(snd) {
+                    Some((sfc_p, _)) => sfc_p.unpack(),
+                    None => return vec![],
+                };
+
+                // Start at the first round level above the ground.
+                let mut p = (sfc_p / step).floor() * step;
+                let mut levels = vec![];
+                while p > min_p.unpack() {
+                    levels.push(HectoPascal(p));
+                    p -= step;
                 }
-            })
-            .map(|tuple| {
-                let (p, w) = tuple;
-                WindBarbData::create(p, w, barb_config, args)
+                levels
+            }
+            WindBarbLevels::HeightInterval => {
+                let step = config.wind_barb_height_interval.unpack().max(10.0);
+
+                (0..)
+                    .map(|i| i as f64 * step)
+                    .map_while(|agl| pressure_at_height(snd, agl))
+                    .take_while(|&p| p > min_p)
+                    .collect()
+            }
+            WindBarbLevels::Mandatory => MANDATORY_LEVELS
+                .iter()
+                .cloned()
+                .filter(|&p| p > min_p)
+                .collect(),
+        };
+
+        levels
+            .into_iter()
+            .filter_map(|p| {
+                let row = sounding_analysis::linear_interpolate_sounding(snd, p).ok()?;
+                Some((p, row.wind.into_option()?))
             })
             .collect()
     }
@@ -318,10 +382,11 @@ impl SkewTContext {
     fn filter_wind_data(
         args: DrawingArgs<'_, '_>,
         barb_data: Vec<WindBarbData>,
+        thin: bool,
     ) -> Vec<WindBarbData> {
         let ac = args.ac;
 
-        // Remove overlapping barbs, or barbs not on the screen
+        // Remove barbs not on the screen, and overlapping barbs if thinning.
         let mut keepers: Vec<WindBarbData> = vec![];
         let screen_box = ac.skew_t.get_plot_area();
         let mut last_added_bbox: ScreenRect = ScreenRect {
@@ -336,7 +401,7 @@ impl SkewTContext {
         };
         for bdata in barb_data {
             let bbox = bdata.bounding_box();
-            if !bbox.inside(&screen_box) || bbox.overlaps(&last_added_bbox) {
+            if !bbox.inside(&screen_box) || (thin && bbox.overlaps(&last_added_bbox)) {
                 continue;
             }
             last_added_bbox = bbox;
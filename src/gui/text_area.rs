@@ -1,12 +1,8 @@
 use crate::{
-    app::{sample::Sample, AppContext, AppContextPointer},
+    app::{config::Config, sample::Sample, AppContext, AppContextPointer},
     errors::SondeError,
 };
-use gtk::{
-    glib::{translate::IntoGlib, Propagation},
-    prelude::*,
-    EventControllerKey, TextTag, TextView,
-};
+use gtk::{glib::translate::IntoGlib, prelude::*, EventControllerKey, TextTag, TextView};
 use metfor::{HectoPascal, Quantity};
 use sounding_analysis::DataRow;
 use std::{fmt::Write, rc::Rc};
@@ -40,24 +36,20 @@ macro_rules! set_text {
 }
 
 pub fn set_up_text_area(acp: &AppContextPointer) -> Result<(), SondeError> {
-    use gtk::gdk::Key;
-
     const TEXT_AREA_ID: &str = "text_area";
     let text_area: TextView = acp.fetch_widget(TEXT_AREA_ID)?;
 
     let key_press = EventControllerKey::new();
     let ac = Rc::clone(acp);
     key_press.connect_key_pressed(move |_key_press, key, _code, _key_modifier| {
-        if key == Key::KP_Right || key == Key::Right {
-            ac.display_next();
-        } else if key == Key::KP_Left || key == Key::Left {
-            ac.display_previous();
-        }
-        Propagation::Stop
+        crate::gui::key_press(key, &ac)
     });
     text_area.add_controller(key_press);
 
-    fill_header_text_area(acp)?;
+    let header: TextView = acp.fetch_widget("text_header")?;
+    let header_tb = header.buffer();
+    make_default_tag!(header_tb, acp);
+    update_text_header(acp);
 
     let tb = text_area.buffer();
     make_default_tag!(tb, acp);
@@ -78,8 +70,6 @@ pub fn set_up_text_area(acp: &AppContextPointer) -> Result<(), SondeError> {
 }
 
 pub fn update_text_area(ac: &AppContext) {
-    use crate::app::config;
-
     let text_area: TextView = if let Ok(ta) = ac.fetch_widget("text_area") {
         ta
     } else {
@@ -91,12 +81,15 @@ pub fn update_text_area(ac: &AppContext) {
         let anal = anal.borrow();
         let mut text = String::with_capacity(4096);
 
+        let config = ac.config.borrow();
+        let min_p = ac.skew_t.geometry().min_p;
         anal.sounding()
             .top_down()
-            .filter(|row| row.pressure.map(|p| p > config::MINP).unwrap_or(false))
+            .filter(|row| row.pressure.map(|p| p > min_p).unwrap_or(false))
             .for_each(|row| {
-                write_row(&mut text, row);
+                write_row(&mut text, row, &config);
             });
+        drop(config);
 
         // Get the scroll position before setting the text
         let old_adj = text_area.vadjustment().map(|adj| adj.value());
@@ -131,24 +124,44 @@ pub fn update_text_area(ac: &AppContext) {
         };
     }
 
-    fn write_row(buf: &mut String, row: DataRow) {
-        write_opt!(row.pressure, "{:5.0}", "{:5}", buf);
-        write_opt!(row.height, "{:6.0}", "{:6}", buf);
-        write_opt!(row.temperature, "{:6.1}", "{:6}", buf);
-        write_opt!(row.wet_bulb, "{:6.1}", "{:6}", buf);
-        write_opt!(row.dew_point, "{:6.1}", "{:6}", buf);
+    fn write_row(buf: &mut String, row: DataRow, config: &Config) {
+        let (p_units, t_units) = (config.pressure_units, config.temperature_units);
+        let (h_units, w_units) = (config.height_units, config.wind_speed_units);
+
+        let height = row.height.map_t(|h| h_units.value(h));
+        let temperature = row.temperature.map_t(|t| t_units.value(t));
+        let wet_bulb = row.wet_bulb.map_t(|t| t_units.value(t));
+        let dew_point = row.dew_point.map_t(|t| t_units.value(t));
+        let speed = row.wind.map_t(|wnd| w_units.value(wnd.speed));
+
+        if let Some(p) = row.pressure.into_option() {
+            write!(buf, "{:5.*}", p_units.precision(), p_units.value(p)).unwrap();
+        } else {
+            write!(buf, "{:5}", "").unwrap();
+        }
+        write_opt!(height, "{:6.0}", "{:6}", buf);
+        write_opt!(temperature, "{:6.1}", "{:6}", buf);
+        write_opt!(wet_bulb, "{:6.1}", "{:6}", buf);
+        write_opt!(dew_point, "{:6.1}", "{:6}", buf);
         write_opt!(row.theta_e, "{:^8.0}", "{:^8}", buf);
         write_opt!(row.wind.map_t(|wnd| wnd.direction), "{:5.0}", "{:5}", buf);
-        write_opt!(row.wind.map_t(|wnd| wnd.speed), "{:4.0}", "{:4}", buf);
+        write_opt!(speed, "{:4.0}", "{:4}", buf);
         write_opt!(row.pvv, "{:6.1}", "{:6}", buf);
         write_opt!(row.cloud_fraction, "{:6.0}", "{:6}", buf);
         writeln!(buf).unwrap();
     }
 }
 
-pub fn fill_header_text_area(acp: &AppContextPointer) -> Result<(), SondeError> {
-    const HEADER_ID: &str = "text_header";
-    let header: TextView = acp.fetch_widget(HEADER_ID)?;
+/// Fill in the column names and units above the text area.
+pub fn update_text_header(ac: &AppContext) {
+    let header: TextView = if let Ok(header) = ac.fetch_widget("text_header") {
+        header
+    } else {
+        return;
+    };
+
+    let config = ac.config.borrow();
+    let t_units = config.temperature_units.label();
 
     let tb = header.buffer();
     let mut text = String::with_capacity(512);
@@ -159,13 +172,19 @@ pub fn fill_header_text_area(acp: &AppContextPointer) -> Result<(), SondeError>
     ));
     text.push_str(&format!(
         " {:^4} {:^5} {:^5} {:^5} {:^5} {:^7}  {:^3} {:^4} {:^5}  {:^3}",
-        "hPa", "m", "\u{00b0}C", "\u{00b0}C", "\u{00b0}C", "\u{00b0}K", "deg", "KT", "Pa/s", "%",
+        config.pressure_units.label(),
+        config.height_units.label(),
+        t_units,
+        t_units,
+        t_units,
+        "\u{00b0}K",
+        "deg",
+        config.wind_speed_units.label(),
+        "Pa/s",
+        "%",
     ));
 
-    make_default_tag!(tb, acp);
     set_text!(tb, &text);
-
-    Ok(())
 }
 
 pub fn update_text_highlight(ac: &AppContext) {
@@ -203,6 +222,8 @@ pub fn update_text_highlight(ac: &AppContext) {
     tb.remove_tag_by_name("highlight_above", &start, &end);
     tb.remove_tag_by_name("highlight_below", &start, &end);
 
+    let p_units = config.pressure_units;
+
     let lines = tb.line_count();
     for i in 0..(lines - 1) {
         if let (Some(start_above), Some(start_below)) = (tb.iter_at_line(i), tb.iter_at_line(i + 1))
@@ -211,14 +232,14 @@ pub fn update_text_highlight(ac: &AppContext) {
             end_above.forward_chars(5);
             let above_val: HectoPascal =
                 f64::from_str(tb.text(&start_above, &end_above, false).trim())
-                    .map(HectoPascal)
+                    .map(|val| p_units.to_hpa(val))
                     .unwrap_or(HectoPascal(0.0));
 
             let mut end_below = start_below;
             end_below.forward_chars(5);
             let below_val: HectoPascal =
                 f64::from_str(tb.text(&start_below, &end_below, false).trim())
-                    .map(HectoPascal)
+                    .map(|val| p_units.to_hpa(val))
                     .unwrap_or(HectoPascal(0.0));
 
             if tp > above_val && tp <= below_val {
@@ -0,0 +1,939 @@
+//! A Bufkit style time-height cross section of all the loaded soundings.
+//!
+//! Every sounding is interpolated to a common set of rows, then relative humidity is shaded and
+//! temperature and vertical velocity are contoured across the series. Time runs along the x-axis
+//! and pressure or height above ground level up the y-axis.
+use crate::{
+    analysis::{pressure_at_height, surface, Analysis},
+    app::{
+        config::{self, Rgba, TimeHeightAxis},
+        sample::Sample,
+        AppContext, AppContextPointer, ZoomableDrawingAreas,
+    },
+    coords::{
+        convert_pressure_to_y, convert_y_to_pressure, DeviceCoords, Rect, ScreenCoords, ScreenRect,
+        SkewTGeometry, XYCoords,
+    },
+    errors::SondeError,
+    gui::{
+        plot_context::{GenericContext, HasGenericContext, PlotContext, PlotContextExt},
+        sounding::wind::{WindBarbConfig, WindBarbData},
+        utility::{
+            check_overlap_then_add, closest_position, collect_time_labels, plot_curve_from_points,
+            time_series_positions, TIME_MARGIN,
+        },
+        Drawable, DrawingArgs, MasterDrawable,
+    },
+};
+use gtk::{
+    gio::{SimpleAction, SimpleActionGroup},
+    glib::Propagation,
+    prelude::*,
+    DrawingArea, EventControllerKey, EventControllerMotion, EventControllerScroll,
+    EventControllerScrollFlags, GestureClick, Window,
+};
+use metfor::{Celsius, HectoPascal, Knots, Meters, Quantity, WindSpdDir};
+use sounding_analysis::{DataRow, Sounding};
+use std::{cell::RefCell, rc::Rc};
+
+/// Number of rows each sounding is interpolated to.
+const NUM_ROWS: usize = 100;
+/// Only relative humidity above this is shaded.
+const MIN_SHADED_RH: f64 = 0.5;
+/// Temperature contour interval in the display units.
+const TEMPERATURE_INTERVAL: f64 = 5.0;
+/// Vertical velocity contours in Pa/s, negative values are upward motion.
+const OMEGA_LEVELS: [f64; 10] = [-8.0, -4.0, -2.0, -1.0, -0.5, 0.5, 1.0, 2.0, 4.0, 8.0];
+/// Plot wind barbs on every few rows so they don't all overlap.
+const WIND_ROW_STRIDE: usize = 5;
+/// Height lines on the background when using height for the vertical axis.
+const HEIGHT_LINES_KM: [f64; 12] = [
+    1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0,
+];
+
+pub struct TimeHeightContext {
+    generic: GenericContext,
+}
+
+impl TimeHeightContext {
+    pub fn new() -> Self {
+        TimeHeightContext {
+            generic: GenericContext::new(),
+        }
+    }
+
+    /// How much wider than tall the plot is, so the cross section fills the whole width.
+    fn stretch(&self) -> f64 {
+        let scale_factor = self.scale_factor();
+        if scale_factor > 0.0 {
+            self.get_device_rect().width / scale_factor
+        } else {
+            1.0
+        }
+    }
+
+    /// The index of the sounding closest to the cursor.
+    fn find_column(&self, position: DeviceCoords, ac: &AppContext) -> Option<usize> {
+        let x = self.convert_device_to_xy(position).x;
+
+        closest_position(&time_series_positions(&ac.loaded_soundings()), x)
+    }
+}
+
+impl HasGenericContext for TimeHeightContext {
+    fn get_generic_context(&self) -> &GenericContext {
+        &self.generic
+    }
+}
+
+impl PlotContextExt for TimeHeightContext {
+    fn convert_xy_to_screen(&self, coords: XYCoords) -> ScreenCoords {
+        let translate = self.get_translate();
+
+        // Apply translation first
+        let x = coords.x - translate.x;
+        let y = coords.y - translate.y;
+
+        // Apply scaling, stretching the x-axis to fill the width
+        let x = self.get_zoom_factor() * x * self.stretch();
+        let y = self.get_zoom_factor() * y;
+        ScreenCoords { x, y }
+    }
+
+    fn convert_screen_to_xy(&self, coords: ScreenCoords) -> XYCoords {
+        let translate = self.get_translate();
+
+        let x = coords.x / self.stretch() / self.get_zoom_factor() + translate.x;
+        let y = coords.y / self.get_zoom_factor() + translate.y;
+        XYCoords { x, y }
+    }
+}
+
+impl Drawable for TimeHeightContext {
+    /***********************************************************************************************
+     * Initialization
+     **********************************************************************************************/
+    fn set_up_drawing_area(acp: &AppContextPointer) -> Result<(), SondeError> {
+        let da: DrawingArea = acp.fetch_widget("time_height_area")?;
+
+        // Set up the drawing function.
+        let ac = Rc::clone(acp);
+        da.set_draw_func(move |_da, cr, _width, _height| {
+            ac.time_height.draw_callback(cr, &ac);
+        });
+
+        // Set up the scroll (or zoom in/out) callbacks.
+        let ac = Rc::clone(acp);
+        let scroll_control = EventControllerScroll::new(EventControllerScrollFlags::VERTICAL);
+        scroll_control.connect_scroll(move |_scroll_control, _dx, dy| {
+            ac.mark_background_dirty();
+            ac.time_height.scroll_event(dy, &ac);
+
+            Propagation::Stop
+        });
+        da.add_controller(scroll_control);
+
+        // Set up the button clicks.
+        let left_mouse_button = GestureClick::builder().build();
+
+        let ac = Rc::clone(acp);
+        left_mouse_button.connect_pressed(move |_mouse_button, _n_pressed, x, y| {
+            ac.time_height.left_button_press_event((x, y), &ac);
+        });
+
+        let ac = Rc::clone(acp);
+        left_mouse_button.connect_released(move |_mouse_button, _n_press, x, y| {
+            ac.time_height.left_button_release_event((x, y), &ac);
+        });
+
+        da.add_controller(left_mouse_button);
+
+        let right_mouse_button = GestureClick::builder().button(3).build();
+        let ac = Rc::clone(acp);
+        right_mouse_button.connect_released(move |_mouse_button, _n_press, x, y| {
+            ac.time_height.right_button_release_event((x, y), &ac);
+        });
+        da.add_controller(right_mouse_button);
+
+        // Set up the mouse motion events
+        let mouse_motion = EventControllerMotion::new();
+
+        let ac = Rc::clone(acp);
+        mouse_motion.connect_motion(move |mouse_motion, x, y| {
+            ac.time_height.mouse_motion_event(mouse_motion, (x, y), &ac);
+        });
+
+        let ac = Rc::clone(acp);
+        mouse_motion.connect_enter(move |_mouse_motion, _x, _y| {
+            ac.time_height.enter_event(&ac);
+        });
+
+        let ac = Rc::clone(acp);
+        mouse_motion.connect_leave(move |_mouse_motion| {
+            ac.time_height.leave_event(&ac);
+        });
+
+        da.add_controller(mouse_motion);
+
+        // Set up the key presses.
+        let key_press = EventControllerKey::new();
+        let ac = Rc::clone(acp);
+        key_press.connect_key_pressed(move |_key_press, key, _code, _key_modifier| {
+            TimeHeightContext::key_press_event(key, &ac)
+        });
+        da.add_controller(key_press);
+
+        let ac = Rc::clone(acp);
+        da.connect_resize(move |da, width, height| {
+            ac.time_height.size_allocate_event(da);
+            ac.time_height.resize_event(width, height, &ac);
+        });
+
+        build_time_height_context_menu(acp)?;
+
+        Ok(())
+    }
+
+    /***********************************************************************************************
+     * Background Drawing.
+     **********************************************************************************************/
+    fn draw_background_lines(&self, args: DrawingArgs<'_, '_>) {
+        let (ac, cr, config) = (args.ac, args.cr, args.ac.config.borrow());
+
+        if !config.show_isobars {
+            return;
+        }
+
+        let geometry = ac.skew_t.geometry();
+        let SkewTGeometry { max_p, min_p, .. } = geometry;
+        let ys: Vec<f64> = match config.time_height_axis {
+            TimeHeightAxis::Pressure => config::ISOBARS
+                .iter()
+                .filter(|&&p| p >= min_p && p <= max_p)
+                .map(|&p| convert_pressure_to_y(p, geometry))
+                .collect(),
+            TimeHeightAxis::Height => HEIGHT_LINES_KM
+                .iter()
+                .map(|&km| height_to_y(Meters(km * 1000.0)))
+                .collect(),
+        };
+
+        for y in ys {
+            let pnts = [XYCoords { x: 0.0, y }, XYCoords { x: 1.0, y }];
+            plot_curve_from_points(
+                cr,
+                config.background_line_width,
+                config.isobar_rgba,
+                pnts.iter().map(|&xy| self.convert_xy_to_screen(xy)),
+            );
+        }
+    }
+
+    fn collect_labels(&self, args: DrawingArgs<'_, '_>) -> Vec<(String, ScreenRect)> {
+        let (ac, cr, config) = (args.ac, args.cr, args.ac.config.borrow());
+
+        let mut labels = vec![];
+
+        let screen_edges = self.calculate_plot_edges(cr, ac);
+        let ScreenRect { lower_left, .. } = screen_edges;
+
+        // Vertical axis labels along the left side.
+        if config.show_isobars {
+            let geometry = ac.skew_t.geometry();
+            let SkewTGeometry { max_p, min_p, .. } = geometry;
+            let (p_units, h_units) = (config.pressure_units, config.height_units);
+            let levels: Vec<(String, f64)> = match config.time_height_axis {
+                TimeHeightAxis::Pressure => config::ISOBARS
+                    .iter()
+                    .filter(|&&p| p >= min_p && p <= max_p)
+                    .map(|&p| {
+                        let label = format!("{:.*}", p_units.precision(), p_units.value(p));
+                        (label, convert_pressure_to_y(p, geometry))
+                    })
+                    .collect(),
+                TimeHeightAxis::Height => HEIGHT_LINES_KM
+                    .iter()
+                    .map(|&km| {
+                        let h = Meters(km * 1000.0);
+                        let label = format!(
+                            "{:.0} {}",
+                            h_units.value(h) / 1000.0,
+                            h_units.thousands_label()
+                        );
+                        (label, height_to_y(h))
+                    })
+                    .collect(),
+            };
+
+            for (label, y) in levels {
+                let extents = cr.text_extents(&label).unwrap();
+
+                let ScreenCoords { y: screen_y, .. } =
+                    self.convert_xy_to_screen(XYCoords { x: 0.0, y });
+                let screen_y = screen_y - extents.height() / 2.0;
+
+                let label_lower_left = ScreenCoords {
+                    x: lower_left.x,
+                    y: screen_y,
+                };
+                let label_upper_right = ScreenCoords {
+                    x: lower_left.x + extents.width(),
+                    y: screen_y + extents.height(),
+                };
+
+                let pair = (
+                    label,
+                    ScreenRect {
+                        lower_left: label_lower_left,
+                        upper_right: label_upper_right,
+                    },
+                );
+
+                check_overlap_then_add(cr, ac, &mut labels, &screen_edges, pair);
+            }
+        }
+
+        // Valid times along the bottom.
+        let anals = ac.loaded_soundings();
+        let xs = time_series_positions(&anals);
+        collect_time_labels(self, args, &anals, &xs, &mut labels);
+
+        labels
+    }
+
+    fn build_legend_strings(ac: &AppContext) -> Vec<(String, Rgba)> {
+        let config = ac.config.borrow();
+
+        let mut result = vec![("Time-Height".to_owned(), config.label_rgba)];
+
+        if config.time_height_show_rh {
+            result.push(("RH > 50%".to_owned(), config.rh_rgba));
+        }
+        if config.time_height_show_temperature {
+            result.push((
+                format!("Temperature ({})", config.temperature_units.label()),
+                config.temperature_rgba,
+            ));
+        }
+        if config.time_height_show_omega {
+            result.push(("Omega (Pa/s)".to_owned(), config.omega_rgba));
+        }
+
+        result
+    }
+
+    /***********************************************************************************************
+     * Data Drawing.
+     **********************************************************************************************/
+    fn draw_data(&self, args: DrawingArgs<'_, '_>) {
+        let ac = args.ac;
+
+        let anals = ac.loaded_soundings();
+        if anals.is_empty() {
+            self.set_has_data(false);
+            self.draw_no_data(args);
+            return;
+        }
+        self.set_has_data(true);
+
+        let axis = ac.config.borrow().time_height_axis;
+        let section = CrossSection::new(&anals, axis, ac.skew_t.geometry());
+
+        self.draw_rh(&section, args);
+        self.draw_temperature(&section, args);
+        self.draw_omega(&section, args);
+        self.draw_wind(&section, args);
+        self.draw_displayed_marker(&section, args);
+    }
+
+    /***********************************************************************************************
+     * Overlays Drawing.
+     **********************************************************************************************/
+    fn draw_active_sample(&self, args: DrawingArgs<'_, '_>) {
+        if !self.has_data() {
+            return;
+        }
+
+        let (ac, cr) = (args.ac, args.cr);
+        let config = ac.config.borrow();
+
+        if !config.show_active_readout_line {
+            return;
+        }
+
+        let (pressure, height) = match *ac.get_sample() {
+            Sample::Sounding {
+                data: DataRow {
+                    pressure, height, ..
+                },
+                ..
+            } => (pressure, height),
+            Sample::FirePlume { .. } | Sample::None => return,
+        };
+
+        let y = match config.time_height_axis {
+            TimeHeightAxis::Pressure => pressure
+                .into_option()
+                .map(|p| convert_pressure_to_y(p, ac.skew_t.geometry())),
+            TimeHeightAxis::Height => ac.get_sounding_for_display().and_then(|anal| {
+                let (_, sfc_h) = surface(anal.borrow().sounding())?;
+                height
+                    .into_option()
+                    .map(|h| height_to_y(Meters(h.unpack() - sfc_h.unpack())))
+            }),
+        };
+
+        if let Some(y) = y {
+            let pnts = [XYCoords { x: 0.0, y }, XYCoords { x: 1.0, y }];
+            plot_curve_from_points(
+                cr,
+                config.active_readout_line_width,
+                config.active_readout_line_rgba,
+                pnts.iter().map(|&xy| self.convert_xy_to_screen(xy)),
+            );
+        }
+    }
+
+    fn draw_pinned_samples(&self, args: DrawingArgs<'_, '_>) {
+        if !self.has_data() {
+            return;
+        }
+
+        let (ac, cr) = (args.ac, args.cr);
+        let config = ac.config.borrow();
+
+        let anal = match ac.get_sounding_for_display() {
+            Some(anal) => anal,
+            None => return,
+        };
+        let anal = anal.borrow();
+        let sfc_h = surface(anal.sounding()).map(|(_, sfc_h)| sfc_h);
+
+        for pressure in ac.pinned_samples() {
+            let y = match config.time_height_axis {
+                TimeHeightAxis::Pressure => {
+                    Some(convert_pressure_to_y(pressure, ac.skew_t.geometry()))
+                }
+                TimeHeightAxis::Height => {
+                    sounding_analysis::linear_interpolate_sounding(anal.sounding(), pressure)
+                        .ok()
+                        .and_then(|row| row.height.into_option())
+                        .zip(sfc_h)
+                        .map(|(h, sfc_h)| height_to_y(Meters(h.unpack() - sfc_h.unpack())))
+                }
+            };
+
+            if let Some(y) = y {
+                let pnts = [XYCoords { x: 0.0, y }, XYCoords { x: 1.0, y }];
+                plot_curve_from_points(
+                    cr,
+                    config.active_readout_line_width,
+                    config.pinned_sample_rgba,
+                    pnts.iter().map(|&xy| self.convert_xy_to_screen(xy)),
+                );
+            }
+        }
+    }
+
+    /***********************************************************************************************
+     * Events
+     **********************************************************************************************/
+    fn left_button_press_event(&self, position: (f64, f64), ac: &AppContextPointer) {
+        let position = DeviceCoords::from(position);
+        self.set_last_cursor_position(Some(position));
+        self.set_left_button_pressed(true);
+
+        // Clicking on a column displays that sounding everywhere else.
+        if let Some(index) = self.find_column(position, ac) {
+            ac.display_sounding(index);
+        }
+    }
+
+    fn right_button_release_event(&self, _position: (f64, f64), ac: &AppContextPointer) {
+        if let Ok(popover) = ac.fetch_widget::<gtk::PopoverMenu>("time_height_popover") {
+            if let Some(pos) = self.get_last_cursor_position() {
+                let llx: i32 = pos.col as i32;
+                let lly: i32 = pos.row as i32;
+                let rect = gtk::gdk::Rectangle::new(llx, lly, 1, 1);
+                popover.set_pointing_to(Some(&rect));
+                popover.popup();
+            }
+        }
+    }
+
+    fn enter_event(&self, ac: &AppContextPointer) {
+        ac.set_last_focus(ZoomableDrawingAreas::TimeHeight);
+    }
+}
+
+impl MasterDrawable for TimeHeightContext {}
+
+/**************************************************************************************************
+ *                                   DrawingArea set up
+ **************************************************************************************************/
+macro_rules! make_check_item {
+    ($group:ident, $action:expr, $acp:ident, $check_val:ident) => {
+        let ac = $acp.clone();
+        let action = SimpleAction::new($action, None);
+        action.connect_activate(move |_action, _variant| {
+            // Nested scope for borrows.
+            {
+                let mut config = ac.config.borrow_mut();
+                config.$check_val = !config.$check_val;
+            }
+            ac.time_height.mark_data_dirty();
+            crate::gui::draw_all(&ac);
+        });
+        $group.add_action(&action);
+    };
+}
+
+fn build_time_height_context_menu(acp: &AppContextPointer) -> Result<(), SondeError> {
+    let window: Window = acp.fetch_widget("main_window")?;
+    let config = acp.config.borrow();
+
+    let time_height_group = SimpleActionGroup::new();
+    window.insert_action_group("time-height", Some(&time_height_group));
+
+    make_check_item!(time_height_group, "show_rh", acp, time_height_show_rh);
+    make_check_item!(
+        time_height_group,
+        "show_temperature",
+        acp,
+        time_height_show_temperature
+    );
+    make_check_item!(time_height_group, "show_omega", acp, time_height_show_omega);
+    make_check_item!(time_height_group, "show_wind", acp, time_height_show_wind);
+
+    // Configure the vertical axis
+    let current_axis = match config.time_height_axis {
+        TimeHeightAxis::Pressure => "pressure",
+        TimeHeightAxis::Height => "height",
+    };
+
+    let axis_variant = unsafe {
+        &gtk::glib::Variant::from_data_with_type_trusted(current_axis, gtk::glib::VariantTy::STRING)
+    };
+    let axis_action = SimpleAction::new_stateful(
+        "vertical_axis",
+        Some(gtk::glib::VariantTy::STRING),
+        axis_variant,
+    );
+
+    let ac = Rc::clone(acp);
+    axis_action.connect_activate(move |action, variant| {
+        let var = variant.unwrap();
+        let val: &str = var.str().unwrap();
+        action.set_state(var);
+
+        let axis = match val {
+            "pressure" => TimeHeightAxis::Pressure,
+            "height" => TimeHeightAxis::Height,
+            _ => unreachable!(),
+        };
+
+        ac.config.borrow_mut().time_height_axis = axis;
+        ac.time_height.mark_background_dirty();
+        crate::gui::draw_all(&ac);
+    });
+    time_height_group.add_action(&axis_action);
+
+    Ok(())
+}
+
+/**************************************************************************************************
+ *                                   Data Layer Drawing
+ **************************************************************************************************/
+impl TimeHeightContext {
+    fn draw_rh(&self, section: &CrossSection, args: DrawingArgs<'_, '_>) {
+        let (cr, config) = (args.cr, args.ac.config.borrow());
+
+        if !config.time_height_show_rh {
+            return;
+        }
+
+        let half_row = 0.5 / (NUM_ROWS - 1) as f64;
+        let (r, g, b, a) = config.rh_rgba;
+
+        for ((left, right), column) in section.column_edges().into_iter().zip(&section.rh) {
+            for (row, rh) in column.iter().enumerate() {
+                let rh = match rh {
+                    Some(rh) if *rh > MIN_SHADED_RH => rh.min(1.0),
+                    _ => continue,
+                };
+
+                let y = row_y(row);
+                let ll = self.convert_xy_to_screen(XYCoords {
+                    x: left,
+                    y: (y - half_row).max(0.0),
+                });
+                let ur = self.convert_xy_to_screen(XYCoords {
+                    x: right,
+                    y: (y + half_row).min(1.0),
+                });
+
+                // Fade in from the minimum shaded value to saturation.
+                let alpha = a * (rh - MIN_SHADED_RH) / (1.0 - MIN_SHADED_RH);
+                cr.set_source_rgba(r, g, b, alpha);
+                cr.rectangle(ll.x, ll.y, ur.x - ll.x, ur.y - ll.y);
+                cr.fill().unwrap();
+            }
+        }
+    }
+
+    fn draw_temperature(&self, section: &CrossSection, args: DrawingArgs<'_, '_>) {
+        let (cr, config) = (args.cr, args.ac.config.borrow());
+
+        if !config.time_height_show_temperature {
+            return;
+        }
+
+        let t_units = config.temperature_units;
+        let temperature: Vec<Vec<Option<f64>>> = section
+            .temperature
+            .iter()
+            .map(|col| {
+                col.iter()
+                    .map(|t| t.map(|t| t_units.value(Celsius(t))))
+                    .collect()
+            })
+            .collect();
+
+        let (min_t, max_t) = temperature
+            .iter()
+            .flatten()
+            .flatten()
+            .fold((f64::MAX, f64::MIN), |(lo, hi), &t| (lo.min(t), hi.max(t)));
+
+        // The freezing level isn't on the regular interval in every unit, so it is drawn on its
+        // own to make it stand out.
+        let freezing = t_units.value(Celsius(0.0));
+
+        let mut level = (min_t / TEMPERATURE_INTERVAL).ceil() * TEMPERATURE_INTERVAL;
+        while level <= max_t {
+            if level != freezing {
+                let segments = section.contour(&temperature, level);
+                let width = config.temperature_line_width / 2.0;
+                self.stroke_segments(cr, width, config.temperature_rgba, false, &segments);
+            }
+
+            level += TEMPERATURE_INTERVAL;
+        }
+
+        if min_t <= freezing && freezing <= max_t {
+            let segments = section.contour(&temperature, freezing);
+            let width = config.temperature_line_width;
+            self.stroke_segments(cr, width, config.temperature_rgba, false, &segments);
+        }
+    }
+
+    fn draw_omega(&self, section: &CrossSection, args: DrawingArgs<'_, '_>) {
+        let (cr, config) = (args.cr, args.ac.config.borrow());
+
+        if !config.time_height_show_omega {
+            return;
+        }
+
+        // Rising motion is solid, sinking motion is dashed.
+        for &level in &OMEGA_LEVELS {
+            let segments = section.contour(&section.omega, level);
+            self.stroke_segments(
+                cr,
+                config.profile_line_width,
+                config.omega_rgba,
+                level > 0.0,
+                &segments,
+            );
+        }
+    }
+
+    fn draw_wind(&self, section: &CrossSection, args: DrawingArgs<'_, '_>) {
+        let (cr, config) = (args.cr, args.ac.config.borrow());
+
+        if !config.time_height_show_wind {
+            return;
+        }
+
+        let barb_config = WindBarbConfig::sizes(args);
+        let screen_box = self.get_plot_area();
+
+        // Remove overlapping barbs, or barbs not on the screen
+        let mut keepers: Vec<WindBarbData> = vec![];
+        let mut kept_boxes: Vec<ScreenRect> = vec![];
+        for (&x, column) in section.xs.iter().zip(&section.wind) {
+            for (row, wind) in column.iter().enumerate() {
+                let wind = match wind {
+                    Some(wind) if row % WIND_ROW_STRIDE == WIND_ROW_STRIDE / 2 => *wind,
+                    _ => continue,
+                };
+
+                let center = self.convert_xy_to_screen(XYCoords { x, y: row_y(row) });
+                let bdata = WindBarbData::create_at(center, wind, &barb_config);
+                let bbox = bdata.bounding_box();
+
+                if !bbox.inside(&screen_box) || kept_boxes.iter().any(|kept| bbox.overlaps(kept)) {
+                    continue;
+                }
+
+                kept_boxes.push(bbox);
+                keepers.push(bdata);
+            }
+        }
+
+        cr.set_line_width(
+            cr.device_to_user_distance(config.wind_barb_line_width, 0.0)
+                .unwrap()
+                .0,
+        );
+
+        for bdata in &keepers {
+            let rgba = config.wind_barb_rgba(bdata.speed());
+            cr.set_source_rgba(rgba.0, rgba.1, rgba.2, rgba.3);
+            bdata.draw(cr);
+        }
+    }
+
+    fn draw_displayed_marker(&self, section: &CrossSection, args: DrawingArgs<'_, '_>) {
+        let (ac, cr) = (args.ac, args.cr);
+        let config = ac.config.borrow();
+
+        let x = match section.xs.get(ac.currently_displayed_index()) {
+            Some(&x) => x,
+            None => return,
+        };
+
+        let pnts = [XYCoords { x, y: 0.0 }, XYCoords { x, y: 1.0 }];
+        plot_curve_from_points(
+            cr,
+            config.active_readout_line_width,
+            config.active_readout_line_rgba,
+            pnts.iter().map(|&xy| self.convert_xy_to_screen(xy)),
+        );
+    }
+
+    fn stroke_segments(
+        &self,
+        cr: &gtk::cairo::Context,
+        line_width_pixels: f64,
+        rgba: Rgba,
+        dashed: bool,
+        segments: &[(XYCoords, XYCoords)],
+    ) {
+        if segments.is_empty() {
+            return;
+        }
+
+        cr.set_source_rgba(rgba.0, rgba.1, rgba.2, rgba.3);
+        cr.set_line_width(
+            cr.device_to_user_distance(line_width_pixels, 0.0)
+                .unwrap()
+                .0,
+        );
+
+        // The segments are short, so size the dashes in pixels or they are never seen.
+        if dashed {
+            let dash = cr.device_to_user_distance(3.0, 0.0).unwrap().0;
+            cr.set_dash(&[dash], 0.0);
+        }
+
+        for &(start, end) in segments {
+            let start = self.convert_xy_to_screen(start);
+            let end = self.convert_xy_to_screen(end);
+            cr.move_to(start.x, start.y);
+            cr.line_to(end.x, end.y);
+        }
+        cr.stroke().unwrap();
+
+        if dashed {
+            cr.set_dash(&[], 0.0);
+        }
+    }
+}
+
+/**************************************************************************************************
+ *                                   Cross section grid
+ **************************************************************************************************/
+/// The loaded soundings interpolated to a common set of rows, indexed by [column][row].
+struct CrossSection {
+    xs: Vec<f64>,
+    rh: Vec<Vec<Option<f64>>>,
+    omega: Vec<Vec<Option<f64>>>,
+    temperature: Vec<Vec<Option<f64>>>,
+    wind: Vec<Vec<Option<WindSpdDir<Knots>>>>,
+}
+
+impl CrossSection {
+    fn new(anals: &[Rc<RefCell<Analysis>>], axis: TimeHeightAxis, geometry: SkewTGeometry) -> Self {
+        let xs = time_series_positions(anals);
+
+        let mut section = CrossSection {
+            xs,
+            rh: Vec::with_capacity(anals.len()),
+            omega: Vec::with_capacity(anals.len()),
+            temperature: Vec::with_capacity(anals.len()),
+            wind: Vec::with_capacity(anals.len()),
+        };
+
+        for anal in anals {
+            let anal = anal.borrow();
+            let snd = anal.sounding();
+
+            let rows: Vec<Option<DataRow>> = (0..NUM_ROWS)
+                .map(|row| {
+                    let p = row_pressure(snd, row_y(row), axis, geometry)?;
+                    sounding_analysis::linear_interpolate_sounding(snd, p).ok()
+                })
+                .collect();
+
+            section.rh.push(
+                rows.iter()
+                    .map(|row| {
+                        let row = row.as_ref()?;
+                        let t = row.temperature.into_option()?;
+                        let dp = row.dew_point.into_option()?;
+                        metfor::rh(t, dp)
+                    })
+                    .collect(),
+            );
+            section.omega.push(
+                rows.iter()
+                    .map(|row| row.as_ref()?.pvv.into_option().map(|w| w.unpack()))
+                    .collect(),
+            );
+            section.temperature.push(
+                rows.iter()
+                    .map(|row| row.as_ref()?.temperature.into_option().map(|t| t.unpack()))
+                    .collect(),
+            );
+            section.wind.push(
+                rows.iter()
+                    .map(|row| row.as_ref()?.wind.into_option())
+                    .collect(),
+            );
+        }
+
+        section
+    }
+
+    /// The left and right edges of each column, half way to its neighbors.
+    fn column_edges(&self) -> Vec<(f64, f64)> {
+        let xs = &self.xs;
+
+        xs.iter()
+            .enumerate()
+            .map(|(i, &x)| {
+                let left = if i > 0 {
+                    (xs[i - 1] + x) / 2.0
+                } else {
+                    x - TIME_MARGIN
+                };
+                let right = if i + 1 < xs.len() {
+                    (x + xs[i + 1]) / 2.0
+                } else {
+                    x + TIME_MARGIN
+                };
+                (left, right)
+            })
+            .collect()
+    }
+
+    /// Line segments where `values` cross `level`, found with marching squares.
+    fn contour(&self, values: &[Vec<Option<f64>>], level: f64) -> Vec<(XYCoords, XYCoords)> {
+        let xs = &self.xs;
+        let mut segments = vec![];
+
+        for i in 0..xs.len().saturating_sub(1) {
+            for j in 0..(NUM_ROWS - 1) {
+                // Corners counter-clockwise from the lower left.
+                let vals = match (
+                    values[i][j],
+                    values[i + 1][j],
+                    values[i + 1][j + 1],
+                    values[i][j + 1],
+                ) {
+                    (Some(a), Some(b), Some(c), Some(d)) => [a, b, c, d],
+                    _ => continue,
+                };
+                let pnts = [
+                    XYCoords {
+                        x: xs[i],
+                        y: row_y(j),
+                    },
+                    XYCoords {
+                        x: xs[i + 1],
+                        y: row_y(j),
+                    },
+                    XYCoords {
+                        x: xs[i + 1],
+                        y: row_y(j + 1),
+                    },
+                    XYCoords {
+                        x: xs[i],
+                        y: row_y(j + 1),
+                    },
+                ];
+
+                let crossings: Vec<XYCoords> = (0..4)
+                    .filter_map(|k| {
+                        let l = (k + 1) % 4;
+                        let (v0, v1) = (vals[k], vals[l]);
+                        if (v0 >= level) == (v1 >= level) {
+                            return None;
+                        }
+
+                        let frac = (level - v0) / (v1 - v0);
+                        Some(XYCoords {
+                            x: pnts[k].x + frac * (pnts[l].x - pnts[k].x),
+                            y: pnts[k].y + frac * (pnts[l].y - pnts[k].y),
+                        })
+                    })
+                    .collect();
+
+                // A saddle has four crossings, just pair them up in order around the cell.
+                for pair in crossings.chunks_exact(2) {
+                    segments.push((pair[0], pair[1]));
+                }
+            }
+        }
+
+        segments
+    }
+}
+
+/// The y coordinate of a row in the cross section.
+fn row_y(row: usize) -> f64 {
+    row as f64 / (NUM_ROWS - 1) as f64
+}
+
+/// The y coordinate of a height above ground level.
+fn height_to_y(agl: Meters) -> f64 {
+    agl.unpack() / config::MAX_TIME_HEIGHT_HEIGHT.unpack()
+}
+
+/// The pressure of the row at `y`, or `None` if it is below ground or above the sounding.
+fn row_pressure(
+    snd: &Sounding,
+    y: f64,
+    axis: TimeHeightAxis,
+    geometry: SkewTGeometry,
+) -> Option<HectoPascal> {
+    match axis {
+        TimeHeightAxis::Pressure => {
+            let sfc_p = snd
+                .bottom_up()
+                .filter_map(|row| row.pressure.into_option())
+                .next()?;
+            let p = convert_y_to_pressure(y, geometry);
+
+            if p <= sfc_p {
+                Some(p)
+            } else {
+                None
+            }
+        }
+        TimeHeightAxis::Height => {
+            let agl = y * config::MAX_TIME_HEIGHT_HEIGHT.unpack();
+            pressure_at_height(snd, agl)
+        }
+    }
+}
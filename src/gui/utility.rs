@@ -1,8 +1,18 @@
 use gtk::cairo::Context;
 
-use crate::app::config::Rgba;
+use crate::analysis::{pressure_at_height, surface, Analysis};
+use crate::app::config::{HeightReference, Rgba};
 use crate::app::AppContext;
-use crate::coords::{Rect, ScreenCoords, ScreenRect};
+use crate::coords::{
+    convert_pressure_to_y, convert_y_to_pressure, Rect, ScreenCoords, ScreenRect, XYCoords,
+};
+use crate::gui::PlotContextExt;
+use metfor::{Meters, Quantity};
+use std::{cell::RefCell, rc::Rc};
+
+/// Space left before the first time and after the last time on plots of the whole series, as a
+/// fraction of the width.
+pub const TIME_MARGIN: f64 = 0.03;
 
 // Draw a curve connecting a list of points.
 pub fn plot_curve_from_points<I>(cr: &Context, line_width_pixels: f64, rgba: Rgba, points: I)
@@ -158,6 +168,182 @@ pub fn check_overlap_then_add(
     vector.push(label_pair);
 }
 
+// The x coordinate of each sounding on a plot of the whole series, spaced by valid time if every
+// sounding has one.
+pub fn time_series_positions(anals: &[Rc<RefCell<Analysis>>]) -> Vec<f64> {
+    let n = anals.len();
+
+    let times: Option<Vec<_>> = anals
+        .iter()
+        .map(|anal| anal.borrow().sounding().valid_time())
+        .collect();
+
+    let fractions: Vec<f64> = match times {
+        Some(times) if n > 1 && times[n - 1] > times[0] => {
+            let start = times[0];
+            let span = (times[n - 1] - start).num_seconds() as f64;
+            times
+                .iter()
+                .map(|&vt| (vt - start).num_seconds() as f64 / span)
+                .collect()
+        }
+        _ if n > 1 => (0..n).map(|i| i as f64 / (n - 1) as f64).collect(),
+        _ => vec![0.5; n],
+    };
+
+    fractions
+        .into_iter()
+        .map(|frac| TIME_MARGIN + frac * (1.0 - 2.0 * TIME_MARGIN))
+        .collect()
+}
+
+// The index of the position closest to x.
+pub fn closest_position(positions: &[f64], x: f64) -> Option<usize> {
+    positions
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| (*a - x).abs().total_cmp(&(*b - x).abs()))
+        .map(|(i, _)| i)
+}
+
+// Label the valid times along the bottom of a plot of the whole series.
+pub fn collect_time_labels<P: PlotContextExt>(
+    plot: &P,
+    args: DrawingArgs<'_, '_>,
+    anals: &[Rc<RefCell<Analysis>>],
+    positions: &[f64],
+    labels: &mut Vec<(String, ScreenRect)>,
+) {
+    let (ac, cr) = (args.ac, args.cr);
+
+    let screen_edges = plot.calculate_plot_edges(cr, ac);
+    let ScreenRect { lower_left, .. } = screen_edges;
+
+    for (anal, &x) in anals.iter().zip(positions) {
+        let label = match anal.borrow().sounding().valid_time() {
+            Some(vt) => vt.format("%d/%HZ").to_string(),
+            None => continue,
+        };
+
+        let extents = cr.text_extents(&label).unwrap();
+
+        let ScreenCoords { x: screen_x, .. } = plot.convert_xy_to_screen(XYCoords { x, y: 0.0 });
+        let screen_x = screen_x - extents.width() / 2.0;
+
+        let label_lower_left = ScreenCoords {
+            x: screen_x,
+            y: lower_left.y,
+        };
+        let label_upper_right = ScreenCoords {
+            x: screen_x + extents.width(),
+            y: lower_left.y + extents.height(),
+        };
+
+        let pair = (
+            label,
+            ScreenRect {
+                lower_left: label_lower_left,
+                upper_right: label_upper_right,
+            },
+        );
+
+        check_overlap_then_add(cr, ac, labels, &screen_edges, pair);
+    }
+}
+
+// Label heights in the displayed sounding along the right side of a plot with pressure on the
+// vertical axis.
+pub fn collect_height_labels<P: PlotContextExt>(
+    plot: &P,
+    args: DrawingArgs<'_, '_>,
+    labels: &mut Vec<(String, ScreenRect)>,
+) {
+    let (ac, cr) = (args.ac, args.cr);
+    let config = ac.config.borrow();
+
+    if !config.show_height_axis {
+        return;
+    }
+
+    let anal = match ac.get_sounding_for_display() {
+        Some(anal) => anal,
+        None => return,
+    };
+    let anal = anal.borrow();
+    let snd = anal.sounding();
+
+    let sfc_h = match surface(snd) {
+        Some((_, sfc_h)) => sfc_h,
+        None => return,
+    };
+
+    let screen_edges = plot.calculate_plot_edges(cr, ac);
+    let ScreenRect {
+        lower_left,
+        upper_right,
+    } = screen_edges;
+
+    let geometry = ac.skew_t.geometry();
+    let bottom_p = convert_y_to_pressure(plot.convert_screen_to_xy(lower_left).y, geometry);
+    let top_p = convert_y_to_pressure(plot.convert_screen_to_xy(upper_right).y, geometry);
+
+    let units = config.height_units;
+    let (base, reference) = match config.height_axis_reference {
+        HeightReference::Agl => (0.0, "AGL"),
+        HeightReference::Msl => (units.value(sfc_h), "MSL"),
+    };
+
+    // Pick a spacing that gives a handful of labels on screen, using a scale height of 8 km to
+    // estimate how deep the visible layer is.
+    let depth = units.value(Meters(8_000.0 * (bottom_p / top_p).ln()));
+    let step = [100.0, 200.0, 500.0, 1_000.0, 2_000.0, 5_000.0, 10_000.0]
+        .into_iter()
+        .find(|&step| depth / step <= 10.0)
+        .unwrap_or(20_000.0);
+
+    // Start at the first label above the ground.
+    let mut height = (base / step).floor() * step + step;
+    while let Some(p) = pressure_at_height(snd, units.to_meters(height - base).unpack()) {
+        if p < top_p {
+            break;
+        }
+
+        let label = format!("{:.0} {} {}", height, units.label(), reference);
+        height += step;
+
+        if p > bottom_p {
+            continue;
+        }
+
+        let extents = cr.text_extents(&label).unwrap();
+
+        let ScreenCoords { y: screen_y, .. } = plot.convert_xy_to_screen(XYCoords {
+            x: 0.0,
+            y: convert_pressure_to_y(p, geometry),
+        });
+        let screen_y = screen_y - extents.height() / 2.0;
+
+        let label_lower_left = ScreenCoords {
+            x: upper_right.x - extents.width(),
+            y: screen_y,
+        };
+        let label_upper_right = ScreenCoords {
+            x: upper_right.x,
+            y: screen_y + extents.height(),
+        };
+
+        let pair = (
+            label,
+            ScreenRect {
+                lower_left: label_lower_left,
+                upper_right: label_upper_right,
+            },
+        );
+
+        check_overlap_then_add(cr, ac, labels, &screen_edges, pair);
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct DrawingArgs<'a, 'b> {
     pub ac: &'a AppContext,
@@ -75,7 +75,20 @@ pub(crate) fn load_config_from_file(
         })
         .map_err(Box::new)?;
 
-    let config = serde_yaml::from_str::<app::config::Config>(&config)?;
+    let mut config = serde_yaml::from_str::<app::config::Config>(&config)?;
+
+    // Keep the rest of the configuration if the skew-t can't be drawn, so it isn't lost the next
+    // time the configuration is saved.
+    if let Err(err) = config.skew_t_geometry() {
+        eprintln!("Using the default skew-t geometry: {}", err);
+
+        let default = app::config::Config::default();
+        config.skew_t_max_p = default.skew_t_max_p;
+        config.skew_t_min_p = default.skew_t_min_p;
+        config.skew_t_min_t = default.skew_t_min_t;
+        config.skew_t_max_t = default.skew_t_max_t;
+        config.skew_t_skew = default.skew_t_skew;
+    }
 
     *app.config.borrow_mut() = config;
 
@@ -83,6 +96,8 @@ pub(crate) fn load_config_from_file(
         app.config.borrow_mut().last_open_file = last_file;
     }
 
+    app.update_skew_t_geometry();
+
     app.mark_background_dirty();
     app.mark_data_dirty();
     app.mark_data_dirty();